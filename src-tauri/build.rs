@@ -1,3 +1,58 @@
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
 fn main() {
+    embed_web_assets();
     tauri_build::build()
 }
+
+// 把前端构建产物（../dist）生成静态资源表，供内置 Web 服务器直接从二进制中读取
+fn embed_web_assets() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let dist_dir = manifest_dir.join("../dist");
+    println!("cargo:rerun-if-changed={}", dist_dir.display());
+
+    let mut files = Vec::new();
+    if dist_dir.is_dir() {
+        collect_files(&dist_dir, &mut files);
+    }
+    files.sort();
+
+    let mut out = String::from("pub static WEB_ASSETS: &[Asset] = &[\n");
+    for file in &files {
+        let relative = file.strip_prefix(&dist_dir).unwrap()
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+        let data = fs::read(file).unwrap();
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        let etag = format!("\"{:016x}\"", hasher.finish());
+
+        out.push_str(&format!(
+            "    Asset {{ path: {:?}, etag: {:?}, data: include_bytes!({:?}) }},\n",
+            relative,
+            etag,
+            file.canonicalize().unwrap().display().to_string(),
+        ));
+    }
+    out.push_str("];\n");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("web_assets.rs"), out).unwrap();
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
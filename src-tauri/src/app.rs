@@ -1,18 +1,19 @@
 use tauri::{State, AppHandle, Emitter, Manager};
-use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, Weak};
 use log::info;
-use chrono::Utc;
-use serde::Serialize;
-use sysinfo::{System, SystemExt, CpuExt, DiskExt, NetworksExt, NetworkExt};
-use lazy_static::lazy_static;
+use tokio::sync::broadcast;
 
-use crate::config::{self, Config};
-use crate::database;
+use crate::auth;
+use crate::config::{self, AuthSettings, BasicAuthConfig, Config, TokenScope};
+use crate::database::{self, EventRecord, RunRecord};
 use crate::healthcheck::{self, HealthChecker, CheckResult};
-use crate::orchestrator::Orchestrator;
+use crate::orchestrator::{ApplicationStatus, InstanceStatus, Job, Operation, Orchestrator, RestartPlan, StartPlan};
 use crate::process::ProcessManager;
-use crate::web::WebServer;
+use crate::scheduler;
+use crate::supervisor;
+use crate::system::{self, ServerHealthResponse};
+use crate::web::{self, ListenConfig, WebServer, WebServerStatus, WebState};
 
 pub struct App {
     app_handle: AppHandle,
@@ -23,67 +24,72 @@ pub struct App {
     web_server: Arc<WebServer>,
 }
 
-#[derive(Serialize)]
-pub struct NetworkUsage {
-    #[serde(rename = "in")]
-    pub in_kb_s: f64,
-    pub out: f64,
-}
-
-#[derive(Serialize)]
-pub struct ServerHealthResponse {
-    pub cpu: f64,
-    pub memory: f64,
-    pub disk: f64,
-    pub network: NetworkUsage,
-    pub uptime: u64,
-    #[serde(rename = "lastUpdated")]
-    pub last_updated: String,
-    #[serde(rename = "os")]
-    pub os_name: String,
-    #[serde(rename = "kernel")]
-    pub kernel_version: String,
-    #[serde(rename = "cpuModel")]
-    pub cpu_model: String,
-    #[serde(rename = "totalMemory")]
-    pub total_memory_human: String,
-}
-
-struct PrevNetworkSample {
-    in_bytes: u64,
-    out_bytes: u64,
-    timestamp: Instant,
-}
-
-lazy_static! {
-    static ref PREV_NETWORK: Mutex<Option<PrevNetworkSample>> = Mutex::new(None);
-}
-
 impl App {
     pub fn new(app_handle: AppHandle) -> Self {
+        // Web 客户端的事件通道
+        let (events, _) = broadcast::channel(1024);
+        
         // 创建日志回调
         let app_handle_log = app_handle.clone();
+        let events_log = events.clone();
         let log_callback = Arc::new(move |task_id: &str, log: &str| {
             // 发送日志事件到前端
-            let _ = app_handle_log.emit(&format!("log:{}", task_id), log);
+            let event = format!("log:{}", task_id);
+            let _ = app_handle_log.emit(&event, log);
+            web::publish(&events_log, event, log);
         });
         
-        // 创建健康检查回调
-        let app_handle_health = app_handle.clone();
-        let health_check_callback = Arc::new(move |result: CheckResult| {
-            // 发送健康检查结果到前端
-            let _ = app_handle_health.emit(&format!("healthcheck:{}", result.task_id), &result);
+        // 创建编排任务回调
+        let app_handle_job = app_handle.clone();
+        let events_job = events.clone();
+        let job_callback = Arc::new(move |job: &Job| {
+            // 发送任务进度到前端
+            let _ = app_handle_job.emit("job:progress", job);
+            web::publish(&events_job, "job:progress", job);
         });
         
         // 初始化各个模块
         let process_manager = Arc::new(ProcessManager::new(log_callback));
-        let orchestrator = Arc::new(Orchestrator::new(process_manager.clone()));
+        let runtime = tauri::async_runtime::handle().inner().clone();
+        let orchestrator = Arc::new(Orchestrator::new(process_manager.clone(), runtime, job_callback));
+        
+        // 创建健康检查回调
+        let app_handle_health = app_handle.clone();
+        let events_health = events.clone();
+        let orchestrator_health = orchestrator.clone();
+        let health_check_callback = Arc::new(move |result: CheckResult| {
+            // 发送健康检查结果到前端，连续失败时按 onUnhealthy 处理
+            let event = format!("healthcheck:{}", result.task_id);
+            let _ = app_handle_health.emit(&event, &result);
+            web::publish(&events_health, event, &result);
+            orchestrator_health.report_health(&result);
+        });
         let health_checker = Arc::new(HealthChecker::new(health_check_callback));
-        let web_server = Arc::new(WebServer::new(8899));
+        orchestrator.set_health_checker(health_checker.clone());
+        let config = Arc::new(Mutex::new(None));
+        let app_handle_web = app_handle.clone();
+        let web_server = Arc::new_cyclic(|server: &Weak<WebServer>| {
+            // 通过 Web 接口修改监听配置时重启 Web 服务器
+            let server = server.clone();
+            let on_listen_change = Arc::new(move |listen: ListenConfig| {
+                if let Some(web_server) = server.upgrade() {
+                    restart_web_server(web_server, app_handle_web.clone(), listen);
+                }
+            });
+            WebServer::new(WebState {
+                config: config.clone(),
+                process_manager: process_manager.clone(),
+                orchestrator: orchestrator.clone(),
+                health_checker: health_checker.clone(),
+                events,
+                tickets: Default::default(),
+                on_listen_change,
+            })
+        });
         
         Self {
             app_handle,
-            config: Arc::new(Mutex::new(None)),
+            config,
             process_manager,
             orchestrator,
             health_checker,
@@ -115,29 +121,83 @@ impl App {
             .unwrap();
         *self.config.lock().unwrap() = Some(config_manager);
         
-        // 初始化数据库
+        // 分离模式服务的日志文件目录
+        self.process_manager.set_log_dir(app_data_dir.join("logs"));
+        
+        // 启动Web服务器
+        let listen = self.config.lock().unwrap().as_ref()
+            .map(|manager| ListenConfig::from(&manager.get().settings));
+        if let Some(listen) = listen {
+            self.start_web_server(listen);
+        }
+        
+        // 在后台初始化数据库、接管分离模式的服务并自动启动，不阻塞界面
         let db_path = app_data_dir.join("service-flow.db");
         let db_path_str = db_path.to_str().unwrap().to_string();
+        let launch = self.launch_context();
         tauri::async_runtime::spawn(async move {
-            if let Err(e) = database::init(&db_path_str).await {
-                log::error!("Failed to initialize database: {}", e);
-            } else {
-                log::info!("Database initialized successfully at: {}", db_path_str);
+            match database::init(&db_path_str).await {
+                Ok(()) => {
+                    log::info!("Database initialized successfully at: {}", db_path_str);
+                    launch.orchestrator.ports().load().await;
+                    launch.process_manager.reattach().await;
+                }
+                Err(e) => log::error!("Failed to initialize database: {}", e),
             }
+            launch.run().await;
         });
         
-        // 启动Web服务器
-        let web_server = self.web_server.clone();
-        tauri::async_runtime::spawn(async move {
-            if let Err(e) = web_server.run().await {
-                log::error!("Failed to start web server: {}", e);
-            }
-        });
+        info!("ServiceFlow started successfully");
+    }
+    
+    fn launch_context(&self) -> LaunchContext {
+        LaunchContext {
+            config: self.config.clone(),
+            process_manager: self.process_manager.clone(),
+            orchestrator: self.orchestrator.clone(),
+        }
+    }
+    
+    // 异步（重新）启动Web服务器，并把结果通知前端
+    fn start_web_server(&self, listen: ListenConfig) {
+        restart_web_server(self.web_server.clone(), self.app_handle.clone(), listen);
+    }
+}
+
+// 按新的监听配置（重新）启动 Web 服务器，并把状态通知前端
+fn restart_web_server(web_server: Arc<WebServer>, app_handle: AppHandle, listen: ListenConfig) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = web_server.start(&listen).await {
+            log::error!("Failed to start web server: {}", e);
+        }
+        let _ = app_handle.emit("webserver:status", web_server.status());
+    });
+}
+
+// 启动后在后台执行的自动启动
+struct LaunchContext {
+    config: Arc<Mutex<Option<config::ConfigManager>>>,
+    process_manager: Arc<ProcessManager>,
+    orchestrator: Arc<Orchestrator>,
+}
+
+impl LaunchContext {
+    async fn run(self) {
+        let Some(snapshot) = self.config.lock().unwrap().as_ref().map(|manager| manager.get().clone()) else {
+            return;
+        };
         
-        // 注意：健康检查和自动启动已禁用，避免启动时阻塞
-        // 用户可以在界面加载后手动启动服务
+        self.orchestrator.reload(&snapshot);
+        // 重新接管的服务不经过编排启动，直接开启健康检查；之后启动的服务由编排操作开启
+        self.orchestrator.check_running(&snapshot);
         
-        info!("ServiceFlow started successfully");
+        // Settings.auto_start 开启时启动所有 auto_start 的服务，进度通过 job:progress 事件通知前端
+        if snapshot.settings.auto_start {
+            match self.orchestrator.run(snapshot.clone(), Operation::AutoStart).await {
+                Ok(job) => info!("Auto-start finished: {:?}", job.state),
+                Err(e) => log::error!("Failed to start all tasks: {}", e),
+            }
+        }
     }
 }
 
@@ -150,147 +210,178 @@ pub fn get_config(app: State<App>) -> Option<Config> {
 }
 
 #[tauri::command]
-pub fn save_config(app: State<App>, cfg: Config) -> Result<(), String> {
+pub fn save_config(app: State<App>, mut cfg: Config) -> Result<(), String> {
     log::info!("Received config to save. Groups: {}, Applications: {}", cfg.groups.len(), cfg.applications.len());
     let mut config = app.config.lock().unwrap();
     if let Some(manager) = config.as_mut() {
+        // 令牌和 Basic 认证只通过专门的命令修改，避免前端缓存的旧配置覆盖
+        cfg.settings.auth = manager.get().settings.auth.clone();
+        // 与 PUT /api/config 相同：不合法的配置和无法监听的设置不写入配置文件
+        let errors = cfg.validate();
+        if !errors.is_empty() {
+            return Err(format!("Invalid config: {}", errors.join("; ")));
+        }
+        let old_listen = ListenConfig::from(&manager.get().settings);
+        let new_listen = ListenConfig::from(&cfg.settings);
+        new_listen.validate().map_err(|e| e.to_string())?;
         manager.set(cfg);
-        manager.save().map_err(|e| e.to_string())
+        manager.save().map_err(|e| e.to_string())?;
+        app.orchestrator.reload(manager.get());
+        drop(config);
+        
+        // 监听地址、端口或 TLS 变化时重启Web服务器
+        if old_listen != new_listen {
+            app.start_web_server(new_listen);
+        }
+        Ok(())
     } else {
         Ok(())
     }
 }
 
 #[tauri::command]
-pub fn get_server_health() -> Result<ServerHealthResponse, String> {
-    let mut system = System::new_all();
-    system.refresh_all();
+pub fn get_web_server_status(app: State<App>) -> WebServerStatus {
+    app.web_server.status()
+}
 
-    // CPU usage percentage
-    let cpu = system.global_cpu_info().cpu_usage() as f64;
+// 修改认证设置并保存配置
+fn update_auth_settings<F, R>(app: &App, update: F) -> Result<R, String>
+where
+    F: FnOnce(&mut AuthSettings) -> Result<R, String>,
+{
+    let mut config = app.config.lock().unwrap();
+    let manager = config.as_mut().ok_or("Config not loaded".to_string())?;
+    let auth = manager.get_mut().settings.auth.get_or_insert_with(AuthSettings::default);
+    let result = update(auth)?;
+    manager.save().map_err(|e| e.to_string())?;
+    Ok(result)
+}
 
-    // Memory usage percentage
-    let total_memory = system.total_memory() as f64;
-    let used_memory = system.used_memory() as f64;
-    let memory = if total_memory > 0.0 {
-        (used_memory / total_memory) * 100.0
-    } else {
-        0.0
-    };
+/// 列出令牌和 Basic 认证账号，不含哈希
+#[tauri::command]
+pub fn get_auth_settings(app: State<App>) -> Option<AuthSettings> {
+    let config = app.config.lock().unwrap();
+    config.as_ref().map(|manager| manager.get().settings.auth.as_ref().map(auth::redacted).unwrap_or_default())
+}
 
-    // Disk usage percentage (use first disk as representative)
-    let mut disk_percent = 0.0;
-    if let Some(disk) = system.disks().first() {
-        let total_space = disk.total_space() as f64;
-        let available_space = disk.available_space() as f64;
-        if total_space > 0.0 {
-            let used_space = total_space - available_space;
-            disk_percent = (used_space / total_space) * 100.0;
+/// 创建 API 令牌，返回的明文只会出现这一次
+#[tauri::command]
+pub fn create_api_token(app: State<App>, name: String, scope: TokenScope) -> Result<String, String> {
+    let (token, record) = auth::issue_token(name, scope);
+    update_auth_settings(&app, |settings| {
+        settings.tokens.push(record);
+        Ok(())
+    })?;
+    Ok(token)
+}
+
+#[tauri::command]
+pub fn revoke_api_token(app: State<App>, token_id: String) -> Result<(), String> {
+    update_auth_settings(&app, |settings| {
+        let before = settings.tokens.len();
+        settings.tokens.retain(|t| t.id != token_id);
+        if settings.tokens.len() == before {
+            return Err(format!("Token not found: {}", token_id));
         }
-    }
+        Ok(())
+    })
+}
+
+/// 设置 Web 界面的 Basic 认证，密码为空时关闭
+#[tauri::command]
+pub fn set_basic_auth(app: State<App>, username: String, password: Option<String>, scope: Option<TokenScope>) -> Result<(), String> {
+    // Argon2 哈希较慢，在持有配置锁之前计算
+    let password_hash = match password.filter(|p| !p.is_empty()) {
+        Some(password) => Some(auth::hash_password(&password).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    update_auth_settings(&app, |settings| {
+        settings.basic_auth = password_hash.map(|password_hash| BasicAuthConfig {
+            username,
+            password_hash,
+            scope: scope.unwrap_or(TokenScope::Operator),
+        });
+        Ok(())
+    })
+}
 
-    // Uptime in seconds
-    let uptime = system.uptime();
+// 使用当前配置的快照提交编排操作，立即返回任务 ID
+fn submit_job(app: &App, operation: Operation) -> Result<String, String> {
+    let config = app.config.lock().unwrap().as_ref()
+        .map(|manager| manager.get().clone())
+        .ok_or("Config not loaded".to_string())?;
+    app.orchestrator.submit(config, operation).map_err(|e| e.to_string())
+}
 
-    // Network throughput: compute based on delta of cumulative bytes
-    let networks = system.networks();
-    let mut total_in = 0u64;
-    let mut total_out = 0u64;
-    for (_name, data) in networks.iter() {
-        total_in += data.received();
-        total_out += data.transmitted();
+#[tauri::command]
+pub fn get_job(app: State<App>, job_id: String) -> Option<Job> {
+    app.orchestrator.job(&job_id)
+}
+
+#[tauri::command]
+pub fn list_jobs(app: State<App>) -> Vec<Job> {
+    app.orchestrator.jobs()
+}
+
+#[tauri::command]
+pub fn cancel_job(app: State<App>, job_id: String) -> Result<(), String> {
+    if app.orchestrator.cancel(&job_id) {
+        Ok(())
+    } else {
+        Err(format!("Job is not running: {}", job_id))
     }
+}
 
-    let now = Instant::now();
-    let mut prev = PREV_NETWORK.lock().map_err(|e| e.to_string())?;
-    let (in_kb_s, out_kb_s) = if let Some(prev_sample) = &*prev {
-        let elapsed = now.duration_since(prev_sample.timestamp).as_secs_f64();
-        if elapsed > 0.0 {
-            let in_delta = total_in.saturating_sub(prev_sample.in_bytes) as f64;
-            let out_delta = total_out.saturating_sub(prev_sample.out_bytes) as f64;
-            ((in_delta / 1024.0) / elapsed, (out_delta / 1024.0) / elapsed)
-        } else {
-            (0.0, 0.0)
-        }
+/// 继续因滚动重启失败而暂停的任务
+#[tauri::command]
+pub fn resume_job(app: State<App>, job_id: String) -> Result<(), String> {
+    if app.orchestrator.resume(&job_id) {
+        Ok(())
     } else {
-        (0.0, 0.0)
-    };
+        Err(format!("Job is not paused: {}", job_id))
+    }
+}
 
-    *prev = Some(PrevNetworkSample {
-        in_bytes: total_in,
-        out_bytes: total_out,
-        timestamp: now,
-    });
+#[tauri::command]
+pub fn get_auto_start_progress(app: State<App>) -> Option<Job> {
+    app.orchestrator.latest_job(&Operation::AutoStart)
+}
 
-    let network = NetworkUsage {
-        in_kb_s,
-        out: out_kb_s,
-    };
+/// 取消正在进行的自动启动，已启动的服务保持运行
+#[tauri::command]
+pub fn cancel_auto_start(app: State<App>) -> Result<(), String> {
+    let cancelled = app.orchestrator.latest_job(&Operation::AutoStart)
+        .map(|job| app.orchestrator.cancel(&job.id))
+        .unwrap_or(false);
+    if cancelled {
+        Ok(())
+    } else {
+        Err("Auto-start is not running".to_string())
+    }
+}
 
-    // System info
-    let os_name = system
-        .name()
-        .unwrap_or_else(|| "Unknown OS".to_string());
-    let kernel_version = system
-        .kernel_version()
-        .unwrap_or_else(|| "Unknown".to_string());
-    let cpu_model = system
-        .cpus()
-        .first()
-        .map(|c| c.brand().to_string())
-        .unwrap_or_else(|| "Unknown CPU".to_string());
-    // sysinfo 的 total_memory 在不同平台单位略有差异，这里按 MB 处理，只除以 1024 得到 GB
-    let total_mem_gb = (total_memory / 1024.0).max(0.0);
-    let total_memory_human = format!("{:.0} GB", total_mem_gb.round());
-
-    let last_updated = Utc::now().to_rfc3339();
-
-    Ok(ServerHealthResponse {
-        cpu,
-        memory,
-        disk: disk_percent,
-        network,
-        uptime,
-        last_updated,
-        os_name,
-        kernel_version,
-        cpu_model,
-        total_memory_human,
-    })
+#[tauri::command]
+pub fn get_server_health() -> Result<ServerHealthResponse, String> {
+    system::server_health()
 }
 
 #[tauri::command]
-pub fn start_task(app: State<App>, task_id: String) -> Result<(), String> {
-    // 重新加载配置以确保使用最新的服务列表
-    let config = app.config.lock().unwrap();
-    if let Some(manager) = config.as_ref() {
-        app.orchestrator.set_config(manager.get().clone());
-    }
-    drop(config);
-    
-    app.orchestrator.start_task(&task_id).map_err(|e| e.to_string())
+pub fn start_task(app: State<App>, task_id: String) -> Result<String, String> {
+    submit_job(&app, Operation::StartService(task_id))
 }
 
 #[tauri::command]
-pub fn stop_task(app: State<App>, task_id: String) -> Result<(), String> {
-    // 重新加载配置以确保使用最新的服务列表
-    let config = app.config.lock().unwrap();
-    if let Some(manager) = config.as_ref() {
-        app.orchestrator.set_config(manager.get().clone());
+pub fn stop_task(app: State<App>, task_id: String, dependents: Option<bool>) -> Result<String, String> {
+    if dependents.unwrap_or(false) {
+        submit_job(&app, Operation::StopServiceAndDependents(task_id))
+    } else {
+        submit_job(&app, Operation::StopService(task_id))
     }
-    drop(config);
-    
-    app.orchestrator.stop_task(&task_id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn start_all(app: State<App>) {
-    let config = app.config.lock().unwrap();
-    if let Some(manager) = config.as_ref() {
-        if let Err(e) = app.orchestrator.start_all(manager.get()) {
-            log::error!("Failed to start all tasks: {}", e);
-        }
-    }
+pub fn start_all(app: State<App>) -> Result<String, String> {
+    submit_job(&app, Operation::StartAll)
 }
 
 #[tauri::command]
@@ -299,9 +390,42 @@ pub fn is_task_running(app: State<App>, task_id: String) -> bool {
 }
 
 #[tauri::command]
-pub fn get_health_check_result(_app: State<App>, _task_id: String) -> Option<healthcheck::CheckResult> {
-    // 目前返回None，需要实现获取健康检查结果的逻辑
-    None
+pub fn get_health_check_result(app: State<App>, task_id: String) -> Option<healthcheck::CheckResult> {
+    app.health_checker.latest_result(&task_id)
+}
+
+/// 多实例服务各实例的状态
+#[tauri::command]
+pub fn get_service_instances(app: State<App>, task_id: String) -> Result<Vec<InstanceStatus>, String> {
+    let config = app.config.lock().unwrap().as_ref()
+        .map(|manager| manager.get().clone())
+        .ok_or("Config not loaded".to_string())?;
+    app.orchestrator.instance_status(&config, &task_id, &app.health_checker)
+        .map_err(|e| e.to_string())
+}
+
+/// 调整正在运行的实例，保留的实例不会重启；任务成功后才保存新的实例数，服务未运行时只保存实例数
+#[tauri::command]
+pub fn scale_service(app: State<App>, task_id: String, replicas: u32) -> Result<String, String> {
+    if replicas == 0 {
+        return Err("Replicas must be at least 1".to_string());
+    }
+    let mut scaled = app.config.lock().unwrap().as_ref()
+        .map(|manager| manager.get().clone())
+        .ok_or("Config not loaded".to_string())?;
+    scaled.service_mut(&task_id)
+        .ok_or(format!("Service not found: {}", task_id))?
+        .replicas = Some(replicas);
+    let errors = scaled.validate();
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+
+    let job_id = app.orchestrator.submit(scaled, Operation::ScaleService { service_id: task_id.clone(), replicas })
+        .map_err(|e| e.to_string())?;
+    tauri::async_runtime::spawn(web::save_replicas_on_success(
+        app.config.clone(), app.orchestrator.clone(), job_id.clone(), task_id, replicas));
+    Ok(job_id)
 }
 
 #[tauri::command]
@@ -310,25 +434,76 @@ pub fn get_task_pid(app: State<App>, task_id: String) -> u32 {
 }
 
 #[tauri::command]
-pub fn restart_task(app: State<App>, task_id: String) -> Result<(), String> {
-    // 重新加载配置以确保使用最新的服务列表
-    let config = app.config.lock().unwrap();
-    if let Some(manager) = config.as_ref() {
-        app.orchestrator.set_config(manager.get().clone());
+pub fn restart_task(app: State<App>, task_id: String, cascade: Option<bool>) -> Result<String, String> {
+    if cascade.unwrap_or(false) {
+        submit_job(&app, Operation::RestartServiceCascade(task_id))
+    } else {
+        submit_job(&app, Operation::RestartService(task_id))
     }
-    drop(config);
-    
-    app.orchestrator.restart_task(&task_id).map_err(|e| e.to_string())
+}
+
+/// 级联重启前预览受影响的服务
+#[tauri::command]
+pub fn get_restart_plan(app: State<App>, task_id: String) -> Result<RestartPlan, String> {
+    let config = app.config.lock().unwrap().as_ref()
+        .map(|manager| manager.get().clone())
+        .ok_or("Config not loaded".to_string())?;
+    app.orchestrator.restart_plan(&config, &task_id).map_err(|e| e.to_string())
+}
+
+/// 预览启动计划，不启动任何服务；未指定目标时为全部启动
+#[tauri::command]
+pub fn plan_start(
+    app: State<App>,
+    service_id: Option<String>,
+    group_id: Option<String>,
+    application_id: Option<String>,
+) -> Result<StartPlan, String> {
+    let config = app.config.lock().unwrap().as_ref()
+        .map(|manager| manager.get().clone())
+        .ok_or("Config not loaded".to_string())?;
+    let operation = Operation::start_of(service_id, group_id, application_id);
+    app.orchestrator.plan_start(&config, &operation).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn start_group(app: State<App>, group_id: String) -> Result<String, String> {
+    submit_job(&app, Operation::StartGroup(group_id))
+}
+
+#[tauri::command]
+pub fn stop_group(app: State<App>, group_id: String) -> Result<String, String> {
+    submit_job(&app, Operation::StopGroup(group_id))
+}
+
+/// 滚动重启服务组，每次重启 batch_size 个服务（默认 1 个）
+#[tauri::command]
+pub fn rolling_restart_group(app: State<App>, group_id: String, batch_size: Option<usize>) -> Result<String, String> {
+    submit_job(&app, Operation::RollingRestartGroup { group_id, batch_size: batch_size.unwrap_or(1) })
+}
+
+#[tauri::command]
+pub fn start_application(app: State<App>, application_id: String) -> Result<String, String> {
+    submit_job(&app, Operation::StartApplication(application_id))
 }
 
 #[tauri::command]
-pub fn start_group(app: State<App>, group_id: String) -> Result<(), String> {
-    app.orchestrator.start_group(&group_id).map_err(|e| e.to_string())
+pub fn stop_application(app: State<App>, application_id: String) -> Result<String, String> {
+    submit_job(&app, Operation::StopApplication(application_id))
 }
 
 #[tauri::command]
-pub fn stop_group(app: State<App>, group_id: String) -> Result<(), String> {
-    app.orchestrator.stop_group(&group_id).map_err(|e| e.to_string())
+pub fn restart_application(app: State<App>, application_id: String) -> Result<String, String> {
+    submit_job(&app, Operation::RestartApplication(application_id))
+}
+
+#[tauri::command]
+pub fn get_application_status(app: State<App>, application_id: String) -> Result<ApplicationStatus, String> {
+    let config = app.config.lock().unwrap().as_ref()
+        .map(|manager| manager.get().clone())
+        .ok_or("Config not loaded".to_string())?;
+    app.orchestrator.application_status(&config, &application_id, &app.health_checker)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -340,7 +515,7 @@ pub fn restart_health_check(app: State<App>, task_id: String) -> Result<(), Stri
     for group in &config.get().groups {
         for task in &group.services {
             if task.id == task_id {
-                app.health_checker.restart_checking(task);
+                app.health_checker.restart_checking(&app.orchestrator.resolve_service(config.get(), task));
                 return Ok(());
             }
         }
@@ -350,118 +525,36 @@ pub fn restart_health_check(app: State<App>, task_id: String) -> Result<(), Stri
 }
 
 #[tauri::command]
-pub fn stop_all_tasks(app: State<App>) -> Result<(), String> {
-    let config = app.config.lock().unwrap();
-    let config = config.as_ref().ok_or("Config not loaded".to_string())?;
-    
-    // 停止所有任务
-    for group in &config.get().groups {
-        for task in &group.services {
-            app.orchestrator.stop_task(&task.id).map_err(|e| e.to_string())?;
-        }
-    }
-    
-    Ok(())
+pub fn stop_all_tasks(app: State<App>) -> Result<String, String> {
+    submit_job(&app, Operation::StopAll)
 }
 
+/// 服务最近的运行记录（定时运行等），最新的在前
 #[tauri::command]
-pub fn get_pid_port(pid: u32) -> Result<Option<u16>, String> {
-    use std::process::Command;
-    
-    // Windows implementation using netstat
-    #[cfg(target_os = "windows")]
-    {
-        // 1. 获取 netstat 输出（一次获取，多次查询）
-        let output = Command::new("netstat")
-            .args(&["-ano"])
-            .output()
-            .map_err(|e| e.to_string())?;
-            
-        let netstat_str = String::from_utf8_lossy(&output.stdout);
-        
-        // 定义内部闭包：查找指定 PID 的端口
-        let find_port_for_pid = |target_pid: u32| -> Option<u16> {
-            for line in netstat_str.lines() {
-                // 确保包含 LISTENING 和 TCP
-                if !line.contains("LISTENING") || !line.contains("TCP") {
-                    continue;
-                }
-                
-                // 分割并检查最后一个字段是否精确匹配 PID
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() < 5 {
-                    continue;
-                }
-                
-                // PID 通常在最后
-                if let Ok(line_pid) = parts[parts.len() - 1].parse::<u32>() {
-                    if line_pid == target_pid {
-                        // 提取端口：本地地址通常是第二个字段 (parts[1])
-                        let local_address = parts[1]; // 0.0.0.0:80
-                        if let Some(colon_idx) = local_address.rfind(':') {
-                            if let Ok(port) = local_address[colon_idx + 1..].parse::<u16>() {
-                                return Some(port);
-                            }
-                        }
-                    }
-                }
-            }
-            None
-        };
+pub async fn get_run_history(task_id: String, limit: Option<i64>) -> Result<Vec<RunRecord>, String> {
+    scheduler::run_history(&task_id, limit.unwrap_or(scheduler::DEFAULT_RUN_HISTORY)).await
+}
 
-        // 2. 尝试查找主进程的端口
-        if let Some(port) = find_port_for_pid(pid) {
-            return Ok(Some(port));
-        }
+/// 服务最近的事件（健康检查失败后的自动重启/停止等），最新的在前
+#[tauri::command]
+pub async fn get_event_history(task_id: String, limit: Option<i64>) -> Result<Vec<EventRecord>, String> {
+    supervisor::event_history(&task_id, limit.unwrap_or(supervisor::DEFAULT_EVENT_HISTORY)).await
+}
 
-        // 3. 如果主进程没找到，递归查找所有后代进程
-        // 使用 wmic 查找所有进程的 ParentProcessId，构建进程树
-        let wmic_output = Command::new("wmic")
-            .args(&["process", "get", "ProcessId,ParentProcessId"])
-            .output();
-            
-        if let Ok(output) = wmic_output {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            let mut parent_map: std::collections::HashMap<u32, Vec<u32>> = std::collections::HashMap::new();
-            
-            // 解析 wmic 输出，构建父子关系映射
-            // Format: ParentProcessId  ProcessId
-            for line in output_str.lines().skip(1) { // Skip header
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 2 {
-                    if let (Ok(parent), Ok(child)) = (parts[0].parse::<u32>(), parts[1].parse::<u32>()) {
-                        parent_map.entry(parent).or_insert_with(Vec::new).push(child);
-                    }
-                }
-            }
-            
-            // 广度优先搜索 (BFS) 查找所有后代
-            let mut queue = std::collections::VecDeque::new();
-            queue.push_back(pid);
-            
-            // 防止死循环（虽然进程树不应该有环）
-            let mut visited = std::collections::HashSet::new();
-            visited.insert(pid);
-
-            while let Some(current_pid) = queue.pop_front() {
-                // 检查当前进程的端口 (跳过主进程，因为已经查过了，但也无妨)
-                if current_pid != pid {
-                    if let Some(port) = find_port_for_pid(current_pid) {
-                        return Ok(Some(port));
-                    }
-                }
+/// 服务的命名端口，自动端口为最近一次分配的端口
+#[tauri::command]
+pub fn get_service_ports(app: State<App>, task_id: String) -> Result<BTreeMap<String, u16>, String> {
+    let config = app.config.lock().unwrap().as_ref()
+        .map(|manager| manager.get().clone())
+        .ok_or("Config not loaded".to_string())?;
+    let service = config.groups.iter()
+        .flat_map(|g| g.services.iter())
+        .find(|s| s.id == task_id)
+        .ok_or(format!("Service not found: {}", task_id))?;
+    Ok(app.orchestrator.ports().ports_of(service))
+}
 
-                // 将子进程加入队列
-                if let Some(children) = parent_map.get(&current_pid) {
-                    for &child in children {
-                        if visited.insert(child) {
-                            queue.push_back(child);
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    Ok(None)
-}
\ No newline at end of file
+#[tauri::command]
+pub fn get_pid_port(pid: u32) -> Result<Option<u16>, String> {
+    system::pid_port(pid)
+}
@@ -0,0 +1,44 @@
+/// 打包进二进制的前端构建产物
+pub struct Asset {
+    pub path: &'static str,
+    pub etag: &'static str,
+    pub data: &'static [u8],
+}
+
+// 由 build.rs 根据 ../dist 生成
+include!(concat!(env!("OUT_DIR"), "/web_assets.rs"));
+
+pub fn get(path: &str) -> Option<&'static Asset> {
+    WEB_ASSETS.iter().find(|asset| asset.path == path)
+}
+
+pub fn content_type(path: &str) -> &'static str {
+    let extension = path.rsplit('.').next().unwrap_or_default().to_ascii_lowercase();
+    match extension.as_str() {
+        "html" => "text/html; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "json" | "map" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "webp" => "image/webp",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "txt" => "text/plain; charset=utf-8",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Vite 输出到 assets/ 下的文件名带内容哈希，可以长期缓存；其余文件每次校验 ETag
+pub fn cache_control(path: &str) -> &'static str {
+    if path.starts_with("assets/") {
+        "public, max-age=31536000, immutable"
+    } else {
+        "no-cache"
+    }
+}
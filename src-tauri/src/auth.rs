@@ -0,0 +1,338 @@
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::http::{header, HeaderMap};
+use base64::Engine;
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::{ApiToken, AuthSettings, TokenScope};
+
+const HASH_SCHEME: &str = "sha256";
+const TOKEN_PREFIX: &str = "sf_";
+const TICKET_PREFIX: &str = "sft_";
+
+// WebSocket 连接票据的有效期
+const TICKET_TTL: Duration = Duration::from_secs(30);
+
+lazy_static::lazy_static! {
+    // 最近一次校验通过的密码：(保存的哈希, 以进程内随机盐计算的摘要)
+    // 浏览器的每个请求都带着 Basic 认证，缓存后不必每次都计算 Argon2
+    static ref VERIFIED_PASSWORD: Mutex<Option<(String, String)>> = Mutex::new(None);
+    static ref CACHE_SALT: String = random_hex(16);
+}
+
+/// 已通过认证的调用方
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Principal {
+    pub name: String,
+    pub scope: TokenScope,
+}
+
+// 生成新的 API 令牌明文
+fn generate_token() -> String {
+    format!("{}{}", TOKEN_PREFIX, random_hex(32))
+}
+
+// 生成令牌 ID
+fn generate_id() -> String {
+    random_hex(8)
+}
+
+/// 生成新的 API 令牌，返回明文和要保存的令牌记录（只含哈希）
+pub fn issue_token(name: String, scope: TokenScope) -> (String, ApiToken) {
+    let token = generate_token();
+    let record = ApiToken {
+        id: generate_id(),
+        name,
+        token_hash: hash_token(&token),
+        scope,
+        created_at: Some(chrono::Utc::now().to_rfc3339()),
+    };
+    (token, record)
+}
+
+/// 对 API 令牌加盐哈希，格式为 `sha256$<salt>$<digest>`
+///
+/// 令牌是 256 位随机数，无法被穷举，使用快速哈希即可；每个请求都要逐个比对令牌，也不适合用慢哈希。
+pub fn hash_token(token: &str) -> String {
+    let salt = random_hex(16);
+    format!("{}${}${}", HASH_SCHEME, salt, digest(&salt, token))
+}
+
+/// 校验令牌明文是否与保存的哈希匹配
+pub fn verify_token(token: &str, stored: &str) -> bool {
+    let mut parts = stored.splitn(3, '$');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(HASH_SCHEME), Some(salt), Some(expected)) => {
+            constant_time_eq(digest(salt, token).as_bytes(), expected.as_bytes())
+        }
+        _ => false,
+    }
+}
+
+/// 使用 Argon2id 哈希 Basic 认证密码，结果为 PHC 格式（`$argon2id$...`）
+pub fn hash_password(password: &str) -> Result<String, password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default().hash_password(password.as_bytes(), &salt)?.to_string())
+}
+
+/// 校验 Basic 认证密码，兼容旧版本保存的 `sha256$` 哈希，重新设置密码后即改为 Argon2
+pub fn verify_password(password: &str, stored: &str) -> bool {
+    if stored.starts_with(HASH_SCHEME) {
+        return verify_token(password, stored);
+    }
+
+    let fingerprint = digest(&CACHE_SALT, password);
+    if let Some((hash, cached)) = VERIFIED_PASSWORD.lock().unwrap().as_ref() {
+        if hash == stored && constant_time_eq(cached.as_bytes(), fingerprint.as_bytes()) {
+            return true;
+        }
+    }
+
+    let verified = PasswordHash::new(stored)
+        .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        .unwrap_or(false);
+    if verified {
+        *VERIFIED_PASSWORD.lock().unwrap() = Some((stored.to_string(), fingerprint));
+    }
+    verified
+}
+
+/// 去掉令牌和密码哈希的认证设置，用于在界面中列出令牌和账号
+pub fn redacted(auth: &AuthSettings) -> AuthSettings {
+    let mut auth = auth.clone();
+    for token in &mut auth.tokens {
+        token.token_hash.clear();
+    }
+    if let Some(basic) = &mut auth.basic_auth {
+        basic.password_hash.clear();
+    }
+    auth
+}
+
+/// 根据请求头（Bearer / Basic）或查询参数中的令牌识别调用方
+///
+/// 浏览器的 WebSocket 无法设置请求头，因此允许通过 `?token=` 传递 API 令牌或一次性票据；
+/// 账号密码只接受 Authorization 头，避免出现在 URL、日志和 Referer 中。
+pub fn authenticate(auth: &AuthSettings, tickets: &Tickets, headers: &HeaderMap, query_token: Option<&str>) -> Option<Principal> {
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+
+    if let Some(value) = authorization {
+        if let Some(token) = value.strip_prefix("Bearer ") {
+            return authenticate_token(auth, token.trim());
+        }
+        if let Some(encoded) = value.strip_prefix("Basic ") {
+            return authenticate_basic(auth, encoded.trim());
+        }
+        return None;
+    }
+
+    query_token.and_then(|token| {
+        if token.starts_with(TICKET_PREFIX) {
+            tickets.redeem(token)
+        } else {
+            authenticate_token(auth, token)
+        }
+    })
+}
+
+/// 一次性的 WebSocket 连接票据
+///
+/// 使用账号密码登录的网页无法把密码放进 WebSocket 地址，先用 Authorization 头换取短期票据再连接。
+#[derive(Default)]
+pub struct Tickets {
+    issued: Mutex<HashMap<String, (Principal, Instant)>>,
+}
+
+impl Tickets {
+    /// 为已认证的调用方签发票据，票据在有效期内只能使用一次
+    pub fn issue(&self, principal: &Principal) -> String {
+        let ticket = format!("{}{}", TICKET_PREFIX, random_hex(24));
+        let mut issued = self.issued.lock().unwrap();
+        issued.retain(|_, (_, at)| at.elapsed() < TICKET_TTL);
+        issued.insert(ticket.clone(), (principal.clone(), Instant::now()));
+        ticket
+    }
+
+    fn redeem(&self, ticket: &str) -> Option<Principal> {
+        let (principal, at) = self.issued.lock().unwrap().remove(ticket)?;
+        (at.elapsed() < TICKET_TTL).then_some(principal)
+    }
+}
+
+fn authenticate_token(auth: &AuthSettings, token: &str) -> Option<Principal> {
+    auth.tokens
+        .iter()
+        .find(|t| verify_token(token, &t.token_hash))
+        .map(|t| Principal {
+            name: t.name.clone(),
+            scope: t.scope,
+        })
+}
+
+fn authenticate_basic(auth: &AuthSettings, encoded: &str) -> Option<Principal> {
+    let basic = auth.basic_auth.as_ref()?;
+    let decoded = base64::engine::general_purpose::STANDARD.decode(encoded).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (username, password) = decoded.split_once(':')?;
+
+    let username_ok = constant_time_eq(username.as_bytes(), basic.username.as_bytes());
+    let password_ok = verify_password(password, &basic.password_hash);
+    if username_ok && password_ok {
+        Some(Principal {
+            name: basic.username.clone(),
+            scope: basic.scope,
+        })
+    } else {
+        None
+    }
+}
+
+fn digest(salt: &str, secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(b":");
+    hasher.update(secret.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BasicAuthConfig;
+    use axum::http::HeaderValue;
+
+    fn headers(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        headers
+    }
+
+    fn basic(username: &str, password: &str) -> HeaderMap {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+        headers(&format!("Basic {}", encoded))
+    }
+
+    fn settings_with_token(scope: TokenScope) -> (String, AuthSettings) {
+        let (token, record) = issue_token("ci".to_string(), scope);
+        let auth = AuthSettings {
+            tokens: vec![record],
+            ..AuthSettings::default()
+        };
+        (token, auth)
+    }
+
+    #[test]
+    fn scopes_are_ordered_by_privilege() {
+        assert!(TokenScope::Read < TokenScope::Operator);
+        assert!(TokenScope::Operator < TokenScope::Admin);
+        assert_eq!(
+            [TokenScope::Admin, TokenScope::Read, TokenScope::Operator].iter().max(),
+            Some(&TokenScope::Admin)
+        );
+    }
+
+    #[test]
+    fn bearer_token_authenticates_with_its_scope() {
+        let (token, auth) = settings_with_token(TokenScope::Operator);
+        let principal = authenticate(&auth, &Tickets::default(), &headers(&format!("Bearer {}", token)), None).unwrap();
+        assert_eq!(principal.name, "ci");
+        assert_eq!(principal.scope, TokenScope::Operator);
+    }
+
+    #[test]
+    fn only_the_hash_is_stored() {
+        let (token, auth) = settings_with_token(TokenScope::Read);
+        assert!(!auth.tokens[0].token_hash.contains(&token));
+        assert!(verify_token(&token, &auth.tokens[0].token_hash));
+        assert!(!verify_token(&format!("{}x", token), &auth.tokens[0].token_hash));
+    }
+
+    #[test]
+    fn unknown_token_is_rejected() {
+        let (token, auth) = settings_with_token(TokenScope::Admin);
+        let tickets = Tickets::default();
+        assert!(authenticate(&auth, &tickets, &headers("Bearer sf_unknown"), None).is_none());
+        // 请求头无效时不会退回到查询参数中的令牌
+        assert!(authenticate(&auth, &tickets, &headers("Bearer sf_unknown"), Some(&token)).is_none());
+        assert!(authenticate(&auth, &tickets, &headers("Token abc"), None).is_none());
+        assert!(authenticate(&auth, &tickets, &HeaderMap::new(), None).is_none());
+    }
+
+    #[test]
+    fn query_token_and_ticket_are_accepted() {
+        let (token, auth) = settings_with_token(TokenScope::Read);
+        let tickets = Tickets::default();
+        let principal = authenticate(&auth, &tickets, &HeaderMap::new(), Some(&token)).unwrap();
+
+        let ticket = tickets.issue(&principal);
+        assert!(authenticate(&auth, &tickets, &HeaderMap::new(), Some(&ticket)).is_some());
+        // 票据只能使用一次
+        assert!(authenticate(&auth, &tickets, &HeaderMap::new(), Some(&ticket)).is_none());
+    }
+
+    #[test]
+    fn basic_auth_uses_argon2() {
+        let password_hash = hash_password("s3cret").unwrap();
+        assert!(password_hash.starts_with("$argon2id$"));
+
+        let auth = AuthSettings {
+            basic_auth: Some(BasicAuthConfig {
+                username: "admin".to_string(),
+                password_hash,
+                scope: TokenScope::Admin,
+            }),
+            ..AuthSettings::default()
+        };
+        let tickets = Tickets::default();
+        let principal = authenticate(&auth, &tickets, &basic("admin", "s3cret"), None).unwrap();
+        assert_eq!(principal.scope, TokenScope::Admin);
+        // 第二次命中缓存，结果相同
+        assert!(authenticate(&auth, &tickets, &basic("admin", "s3cret"), None).is_some());
+        assert!(authenticate(&auth, &tickets, &basic("admin", "wrong"), None).is_none());
+        assert!(authenticate(&auth, &tickets, &basic("root", "s3cret"), None).is_none());
+    }
+
+    #[test]
+    fn legacy_sha256_password_still_verifies() {
+        let legacy = hash_token("old-password");
+        assert!(verify_password("old-password", &legacy));
+        assert!(!verify_password("other", &legacy));
+        assert!(!verify_password("old-password", "not-a-hash"));
+    }
+
+    #[test]
+    fn redacted_settings_contain_no_hashes() {
+        let (_, mut auth) = settings_with_token(TokenScope::Read);
+        auth.basic_auth = Some(BasicAuthConfig {
+            username: "admin".to_string(),
+            password_hash: hash_token("password"),
+            scope: TokenScope::Operator,
+        });
+        let redacted = redacted(&auth);
+        assert!(redacted.tokens[0].token_hash.is_empty());
+        assert_eq!(redacted.tokens[0].name, "ci");
+        assert!(redacted.basic_auth.unwrap().password_hash.is_empty());
+    }
+}
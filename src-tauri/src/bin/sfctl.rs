@@ -0,0 +1,823 @@
+// sfctl：通过 Web API 控制正在运行的 ServiceFlow
+
+use futures::StreamExt;
+use reqwest::blocking::Client;
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::process::ExitCode;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+const USAGE: &str = "\
+Usage: sfctl [--url <url>] [--token <token>] [--json] <command> [args]
+
+Commands:
+  status                              Show all services
+  start <service|group|app>           Start a service, group or application
+  stop [--dependents] <service|group|app>
+                                      Stop a service, group or application,
+                                      --dependents also stops services that require it
+  restart [--cascade] <service|group|app>
+                                      Restart a service, group or application,
+                                      --cascade also restarts services that require it
+  restart --rolling [--batch <n>] <group>
+                                      Restart running services of a group n at a time
+                                      (default 1), waiting for each batch to be healthy
+  scale <service> <replicas>          Change the number of instances of a service
+  resume <job>                        Resume a paused rolling restart
+  cancel <job>                        Cancel a running or paused job
+  plan [service|group|app]            Show the start plan without starting anything,
+                                      defaults to start-all
+  logs [-f] [-n <lines>] <service>    Print recent output, -f to follow
+                                      (use <service>#<n> for a single instance)
+  health <service>                    Show the latest health check result
+  runs [--limit <n>] <service>        Show recent runs of a scheduled service
+                                      (default 20)
+  events [--limit <n>] <service>      Show actions taken on a service, e.g. restarts
+                                      after failed health checks (default 20)
+  config get [path]                   Print the config or a value, e.g. settings.serverPort
+  config set <path> <value>           Set a config value (JSON or plain string)
+  config validate [file]              Validate a config file or the running config
+
+Options:
+  --url <url>       ServiceFlow web server (env SFCTL_URL, default http://127.0.0.1:8899)
+  --token <token>   API token (env SFCTL_TOKEN)
+  --json            Print JSON instead of tables
+";
+
+struct Options {
+    url: String,
+    token: Option<String>,
+    json: bool,
+    follow: bool,
+    lines: usize,
+    dependents: bool,
+    cascade: bool,
+    rolling: bool,
+    batch: usize,
+    limit: Option<usize>,
+    args: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)).and_then(|options| run(&options)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        url: std::env::var("SFCTL_URL").unwrap_or_else(|_| "http://127.0.0.1:8899".to_string()),
+        token: std::env::var("SFCTL_TOKEN").ok(),
+        json: false,
+        follow: false,
+        lines: 100,
+        dependents: false,
+        cascade: false,
+        rolling: false,
+        batch: 1,
+        limit: None,
+        args: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" => options.url = args.next().ok_or("Missing value for --url")?,
+            "--token" => options.token = Some(args.next().ok_or("Missing value for --token")?),
+            "--json" => options.json = true,
+            "-f" | "--follow" => options.follow = true,
+            "--dependents" => options.dependents = true,
+            "--cascade" => options.cascade = true,
+            "--rolling" => options.rolling = true,
+            "--batch" => {
+                options.batch = args.next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or("Invalid value for --batch")?;
+            }
+            "--limit" => {
+                options.limit = Some(args.next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or("Invalid value for --limit")?);
+            }
+            "-n" | "--lines" => {
+                options.lines = args.next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("Invalid value for --lines")?;
+            }
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => options.args.push(arg),
+        }
+    }
+
+    options.url = options.url.trim_end_matches('/').to_string();
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let api = ApiClient::new(options);
+    let args: Vec<&str> = options.args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        ["status"] => status(&api, options),
+        ["restart", group] if options.rolling => rolling_restart(&api, options, group),
+        [action @ ("start" | "stop" | "restart"), target] => control(&api, options, action, target),
+        ["scale", service, replicas] => scale(&api, options, service, replicas),
+        [action @ ("resume" | "cancel"), job_id] => job_action(&api, options, action, job_id),
+        ["plan"] => plan(&api, options, None),
+        ["plan", target] => plan(&api, options, Some(target)),
+        ["logs", service] => logs(&api, options, service),
+        ["health", service] => health(&api, options, service),
+        ["runs", service] => runs(&api, options, service),
+        ["events", service] => events(&api, options, service),
+        ["config", "get"] => config_get(&api, None),
+        ["config", "get", path] => config_get(&api, Some(path)),
+        ["config", "set", path, value] => config_set(&api, options, path, value),
+        ["config", "validate"] => config_validate(&api, options, None),
+        ["config", "validate", file] => config_validate(&api, options, Some(file)),
+        _ => Err(format!("Invalid command\n\n{}", USAGE)),
+    }
+}
+
+struct ApiClient {
+    client: Client,
+    url: String,
+    token: Option<String>,
+}
+
+impl ApiClient {
+    fn new(options: &Options) -> Self {
+        Self {
+            client: Client::new(),
+            url: options.url.clone(),
+            token: options.token.clone(),
+        }
+    }
+
+    fn request(&self, method: Method, path: &str, body: Option<&Value>) -> Result<Value, String> {
+        let mut request = self.client.request(method, format!("{}{}", self.url, path));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(body) = body {
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_string());
+        }
+
+        let response = request.send()
+            .map_err(|e| format!("Failed to connect to {}: {}", self.url, e))?;
+        let status = response.status();
+        let text = response.text().map_err(|e| e.to_string())?;
+
+        if !status.is_success() {
+            let message = serde_json::from_str::<Value>(&text).ok()
+                .and_then(|v| v["error"].as_str().map(String::from))
+                .unwrap_or(text);
+            return Err(format!("{} ({})", message, status));
+        }
+        if text.is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&text).map_err(|e| format!("Invalid response: {}", e))
+    }
+
+    fn get(&self, path: &str) -> Result<Value, String> {
+        self.request(Method::GET, path, None)
+    }
+
+    fn post(&self, path: &str, body: Option<&Value>) -> Result<Value, String> {
+        self.request(Method::POST, path, body)
+    }
+
+    fn put(&self, path: &str, body: &Value) -> Result<Value, String> {
+        self.request(Method::PUT, path, Some(body))
+    }
+
+    // 提交编排操作并等待对应任务结束
+    fn submit(&self, path: &str) -> Result<Value, String> {
+        let job = self.run_job(path)?;
+        check_job(&job)?;
+        Ok(job)
+    }
+
+    // 提交编排任务并等待结束，返回任务的最终状态
+    fn run_job(&self, path: &str) -> Result<Value, String> {
+        let response = self.post(path, None)?;
+        let job_id = response["jobId"].as_str()
+            .ok_or("Invalid response: missing jobId".to_string())?;
+        self.wait_job(job_id)
+    }
+
+    fn wait_job(&self, job_id: &str) -> Result<Value, String> {
+        loop {
+            let job = self.get(&format!("/api/jobs/{}", encode(job_id)))?;
+            if job["state"] != "running" {
+                return Ok(job);
+            }
+            std::thread::sleep(JOB_POLL_INTERVAL);
+        }
+    }
+}
+
+fn check_job(job: &Value) -> Result<(), String> {
+    match job["state"].as_str() {
+        Some("failed") => Err(job["error"].as_str().unwrap_or("Job failed").to_string()),
+        Some("cancelled") => Err(format!("Job {} was cancelled", text(&job["id"]))),
+        Some("paused") => Err(format!(
+            "{} (job {} is paused, run `sfctl resume {}` to continue or `sfctl cancel {}` to stop)",
+            job["error"].as_str().unwrap_or("Job paused"),
+            text(&job["id"]),
+            text(&job["id"]),
+            text(&job["id"]),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn status(api: &ApiClient, options: &Options) -> Result<(), String> {
+    let services = api.get("/api/services")?;
+    let mut rows = Vec::new();
+
+    for service in services.as_array().into_iter().flatten() {
+        let id = service["id"].as_str().unwrap_or_default();
+        let running = service["running"].as_bool().unwrap_or(false);
+        let pid = service["pid"].as_u64();
+
+        let (port, uptime) = match (running, pid) {
+            (true, Some(pid)) => {
+                let port = api.get(&format!("/api/processes/{}/port", pid)).ok()
+                    .and_then(|port| port.as_u64());
+                let uptime = api.get(&format!("/api/services/{}/stats", encode(id))).ok()
+                    .and_then(|stats| stats["uptime"].as_u64());
+                (port, uptime)
+            }
+            _ => (None, None),
+        };
+
+        let health = match service["health"]["status"].as_bool() {
+            Some(true) => "healthy",
+            Some(false) => "unhealthy",
+            None => "-",
+        };
+
+        // 多实例服务显示运行中的实例数，已退出的进程显示退出码
+        let mut state = match service["exitCode"].as_i64() {
+            Some(code) if !running => format!("exited ({})", code),
+            _ => if running { "running" } else { "stopped" }.to_string(),
+        };
+        if let Some(replicas) = service["replicas"].as_u64() {
+            let instances = service["instances"].as_array().cloned().unwrap_or_default();
+            let up = instances.iter().filter(|i| i["running"].as_bool().unwrap_or(false)).count();
+            state = format!("{} ({}/{})", state, up, replicas);
+        }
+
+        rows.push(json!({
+            "id": id,
+            "name": service["name"],
+            "group": service["groupId"],
+            "state": state,
+            "instances": service["instances"],
+            "health": health,
+            "pid": pid,
+            "port": port,
+            "ports": service["ports"],
+            "uptime": uptime,
+        }));
+    }
+
+    if options.json {
+        return print_json(&Value::Array(rows));
+    }
+
+    let table: Vec<Vec<String>> = rows.iter()
+        .map(|row| vec![
+            text(&row["id"]),
+            text(&row["group"]),
+            text(&row["state"]),
+            text(&row["health"]),
+            text(&row["pid"]),
+            format_ports(&row["port"], &row["ports"]),
+            row["uptime"].as_u64().map(format_uptime).unwrap_or_else(|| "-".to_string()),
+        ])
+        .collect();
+    print_table(&["SERVICE", "GROUP", "STATE", "HEALTH", "PID", "PORT", "UPTIME"], &table);
+    Ok(())
+}
+
+// 根据配置判断目标是服务、分组还是应用
+fn control(api: &ApiClient, options: &Options, action: &str, target: &str) -> Result<(), String> {
+    let config = api.get("/api/config")?;
+    let groups = config["groups"].as_array().cloned().unwrap_or_default();
+
+    let is_service = groups.iter()
+        .flat_map(|g| g["services"].as_array().cloned().unwrap_or_default())
+        .any(|s| s["id"] == target);
+    let is_group = groups.iter().any(|g| g["id"] == target);
+    let is_application = config["applications"].as_array().into_iter().flatten()
+        .any(|a| a["id"] == target);
+
+    // 启动时输出每个服务的结果
+    if action == "start" && (is_service || is_group || is_application) {
+        let collection = if is_service { "services" } else if is_group { "groups" } else { "applications" };
+        let job = api.run_job(&format!("/api/{}/{}/start", collection, encode(target)))?;
+        print_outcomes(options, &job)?;
+        return check_job(&job);
+    }
+
+    let kind = if is_service {
+        let query = match action {
+            "stop" if options.dependents => "?dependents=true",
+            "restart" if options.cascade => "?cascade=true",
+            _ => "",
+        };
+        api.submit(&format!("/api/services/{}/{}{}", encode(target), action, query))?;
+        "service"
+    } else if is_group {
+        control_group(api, action, target)?;
+        "group"
+    } else if is_application {
+        api.submit(&format!("/api/applications/{}/{}", encode(target), action))?;
+        "application"
+    } else {
+        return Err(format!("No service, group or application with id '{}'", target));
+    };
+
+    if options.json {
+        return print_json(&json!({ "target": target, "kind": kind, "action": action, "ok": true }));
+    }
+    let past = match action {
+        "start" => "Started",
+        "stop" => "Stopped",
+        _ => "Restarted",
+    };
+    println!("{} {} {}", past, kind, target);
+    Ok(())
+}
+
+fn scale(api: &ApiClient, options: &Options, service: &str, replicas: &str) -> Result<(), String> {
+    let replicas: u32 = replicas.parse()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or(format!("Invalid replica count: {}", replicas))?;
+    let response = api.post(&format!("/api/services/{}/scale", encode(service)), Some(&json!({ "replicas": replicas })))?;
+    let job_id = response["jobId"].as_str()
+        .ok_or("Invalid response: missing jobId".to_string())?;
+    let job = api.wait_job(job_id)?;
+    check_job(&job)?;
+    if options.json {
+        return print_json(&job);
+    }
+    println!("Scaled {} to {} instances", service, replicas);
+    Ok(())
+}
+
+fn rolling_restart(api: &ApiClient, options: &Options, group_id: &str) -> Result<(), String> {
+    let job = api.submit(&format!("/api/groups/{}/rolling-restart?batchSize={}", encode(group_id), options.batch))?;
+    if options.json {
+        return print_json(&job);
+    }
+    println!("Restarted {} services of group {}", text(&job["total"]), group_id);
+    Ok(())
+}
+
+fn job_action(api: &ApiClient, options: &Options, action: &str, job_id: &str) -> Result<(), String> {
+    api.post(&format!("/api/jobs/{}/{}", encode(job_id), action), None)?;
+    if action == "cancel" {
+        if options.json {
+            return print_json(&json!({ "jobId": job_id, "action": action, "ok": true }));
+        }
+        println!("Cancelled job {}", job_id);
+        return Ok(());
+    }
+
+    // 继续后等待任务结束（或再次暂停）
+    let job = api.wait_job(job_id)?;
+    if options.json {
+        print_json(&job)?;
+    }
+    check_job(&job)?;
+    if !options.json {
+        println!("Job {} finished", job_id);
+    }
+    Ok(())
+}
+
+fn print_outcomes(options: &Options, job: &Value) -> Result<(), String> {
+    let outcomes = job["outcomes"].as_object().cloned().unwrap_or_default();
+    if options.json {
+        return print_json(&json!({ "state": job["state"], "outcomes": outcomes }));
+    }
+
+    let rows: Vec<Vec<String>> = outcomes.iter()
+        .map(|(id, outcome)| vec![
+            id.clone(),
+            text(&outcome["status"]),
+            outcome["reason"].as_str().unwrap_or("-").to_string(),
+        ])
+        .collect();
+    print_table(&["SERVICE", "RESULT", "REASON"], &rows);
+    Ok(())
+}
+
+fn control_group(api: &ApiClient, action: &str, group_id: &str) -> Result<(), String> {
+    let group_id = encode(group_id);
+    match action {
+        "restart" => {
+            api.submit(&format!("/api/groups/{}/stop", group_id))?;
+            api.submit(&format!("/api/groups/{}/start", group_id))?;
+        }
+        _ => {
+            api.submit(&format!("/api/groups/{}/{}", group_id, action))?;
+        }
+    }
+    Ok(())
+}
+
+fn plan(api: &ApiClient, options: &Options, target: Option<&str>) -> Result<(), String> {
+    let query = match target {
+        Some(target) => {
+            let config = api.get("/api/config")?;
+            let groups = config["groups"].as_array().cloned().unwrap_or_default();
+            let key = if groups.iter().flat_map(|g| g["services"].as_array().cloned().unwrap_or_default()).any(|s| s["id"] == target) {
+                "service"
+            } else if groups.iter().any(|g| g["id"] == target) {
+                "group"
+            } else if config["applications"].as_array().into_iter().flatten().any(|a| a["id"] == target) {
+                "application"
+            } else {
+                return Err(format!("No service, group or application with id '{}'", target));
+            };
+            format!("?{}={}", key, encode(target))
+        }
+        None => String::new(),
+    };
+
+    let plan = api.get(&format!("/api/plan/start{}", query))?;
+    if options.json {
+        return print_json(&plan);
+    }
+
+    let batches = plan["batches"].as_array().cloned().unwrap_or_default();
+    if batches.is_empty() {
+        println!("Nothing to start");
+    }
+    for (index, batch) in batches.iter().enumerate() {
+        println!("Batch {}:", index + 1);
+        for service in batch["services"].as_array().into_iter().flatten() {
+            let mut notes = Vec::new();
+            for dep in service["dependencies"].as_array().into_iter().flatten() {
+                let mut note = format!("{} {}", text(&dep["type"]), text(&dep["serviceId"]));
+                if let Some(timeout) = dep["timeout"].as_u64() {
+                    note += &format!(" +{}ms", timeout);
+                }
+                if dep["healthGate"].as_bool().unwrap_or(false) {
+                    note += " healthy";
+                }
+                notes.push(note);
+            }
+            for after in service["after"].as_array().into_iter().flatten() {
+                notes.push(format!("after {}", text(after)));
+            }
+            for conflict in service["conflicts"].as_array().into_iter().flatten() {
+                let id = text(&conflict["serviceId"]);
+                match (conflict["running"].as_bool().unwrap_or(false), conflict["resolution"].as_str()) {
+                    (true, Some("replace")) => notes.push(format!("replaces {}", id)),
+                    (true, _) => notes.push(format!("conflicts {} (running)", id)),
+                    _ => notes.push(format!("conflicts {}", id)),
+                }
+            }
+            let notes = if notes.is_empty() { String::new() } else { format!(" [{}]", notes.join(", ")) };
+            println!("  {}{}", text(&service["serviceId"]), notes);
+        }
+        if let Some(delay) = batch["delay"].as_u64().filter(|d| *d > 0) {
+            println!("  wait {}ms", delay);
+        }
+    }
+
+    for skipped in plan["skipped"].as_array().into_iter().flatten() {
+        println!("Skip {} ({})", text(&skipped["serviceId"]), text(&skipped["reason"]));
+    }
+    Ok(())
+}
+
+fn logs(api: &ApiClient, options: &Options, service: &str) -> Result<(), String> {
+    let lines = api.get(&format!("/api/services/{}/logs?lines={}", encode(service), options.lines))?;
+    for line in lines.as_array().into_iter().flatten() {
+        print_log_line(options, service, line);
+    }
+
+    if !options.follow {
+        return Ok(());
+    }
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    runtime.block_on(follow_logs(options, service))
+}
+
+async fn follow_logs(options: &Options, service: &str) -> Result<(), String> {
+    let mut url = options.url.replacen("http", "ws", 1) + "/ws";
+    if let Some(token) = &options.token {
+        url = format!("{}?token={}", url, encode(token));
+    }
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(url.as_str()).await
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+
+    let event_name = format!("log:{}", service);
+    while let Some(message) = socket.next().await {
+        match message.map_err(|e| e.to_string())? {
+            Message::Text(text) => {
+                let Ok(event) = serde_json::from_str::<Value>(&text) else { continue };
+                if event["event"] == event_name.as_str() {
+                    print_log_line(options, service, &event["payload"]);
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn print_log_line(options: &Options, service: &str, line: &Value) {
+    if options.json {
+        println!("{}", json!({ "service": service, "line": line }));
+    } else {
+        println!("{}", line.as_str().unwrap_or_default());
+    }
+}
+
+fn health(api: &ApiClient, options: &Options, service: &str) -> Result<(), String> {
+    let result = api.get(&format!("/api/services/{}/health", encode(service)))?;
+    if options.json {
+        return print_json(&result);
+    }
+    if result.is_null() {
+        println!("{}: no health check result", service);
+        return Ok(());
+    }
+
+    let status = if result["status"].as_bool().unwrap_or(false) { "healthy" } else { "unhealthy" };
+    println!("{}: {} ({})", service, status, result["message"].as_str().unwrap_or_default());
+    Ok(())
+}
+
+fn runs(api: &ApiClient, options: &Options, service: &str) -> Result<(), String> {
+    let mut path = format!("/api/services/{}/runs", encode(service));
+    if let Some(limit) = options.limit {
+        path = format!("{}?limit={}", path, limit);
+    }
+    let runs = api.get(&path)?;
+    if options.json {
+        return print_json(&runs);
+    }
+
+    if let Some(next_run) = api.get(&format!("/api/services/{}", encode(service))).ok()
+        .and_then(|service| service["nextRun"].as_str().map(str::to_string))
+    {
+        println!("Next run: {}\n", next_run);
+    }
+    let table: Vec<Vec<String>> = runs.as_array().into_iter()
+        .flatten()
+        .map(|run| vec![
+            text(&run["id"]),
+            text(&run["startedAt"]),
+            text(&run["trigger"]),
+            text(&run["status"]),
+            text(&run["exitCode"]),
+            format_duration_ms(run["durationMs"].as_u64().unwrap_or(0)),
+            run["logExcerpt"].as_str().and_then(|log| log.lines().last()).unwrap_or("-").to_string(),
+        ])
+        .collect();
+    print_table(&["ID", "STARTED", "TRIGGER", "STATUS", "EXIT", "DURATION", "LAST OUTPUT"], &table);
+    Ok(())
+}
+
+fn events(api: &ApiClient, options: &Options, service: &str) -> Result<(), String> {
+    let mut path = format!("/api/services/{}/events", encode(service));
+    if let Some(limit) = options.limit {
+        path = format!("{}?limit={}", path, limit);
+    }
+    let events = api.get(&path)?;
+    if options.json {
+        return print_json(&events);
+    }
+
+    let table: Vec<Vec<String>> = events.as_array().into_iter()
+        .flatten()
+        .map(|event| vec![
+            text(&event["createdAt"]),
+            text(&event["action"]),
+            if event["success"].as_bool().unwrap_or(false) { "ok" } else { "failed" }.to_string(),
+            text(&event["message"]),
+        ])
+        .collect();
+    print_table(&["TIME", "ACTION", "RESULT", "MESSAGE"], &table);
+    Ok(())
+}
+
+// 读取配置，令牌没有 admin 权限时读取隐藏了环境变量值、启动参数和钩子的配置
+fn read_config(api: &ApiClient) -> Result<Value, String> {
+    api.get("/api/config").or_else(|e| {
+        if e.ends_with(&format!("({})", reqwest::StatusCode::FORBIDDEN)) {
+            api.get("/api/config/redacted")
+        } else {
+            Err(e)
+        }
+    })
+}
+
+fn config_get(api: &ApiClient, path: Option<&str>) -> Result<(), String> {
+    let config = read_config(api)?;
+    let value = match path {
+        Some(path) => lookup(&config, path).ok_or(format!("No config value at '{}'", path))?,
+        None => &config,
+    };
+    match value {
+        Value::String(s) => println!("{}", s),
+        _ => print_json(value)?,
+    }
+    Ok(())
+}
+
+fn config_set(api: &ApiClient, options: &Options, path: &str, value: &str) -> Result<(), String> {
+    let mut config = api.get("/api/config")?;
+    let new_value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+
+    let slot = lookup_mut(&mut config, path).ok_or(format!("No config value at '{}'", path))?;
+    *slot = new_value.clone();
+
+    let result = api.post("/api/config/validate", Some(&config))?;
+    if !result["valid"].as_bool().unwrap_or(false) {
+        print_validation(&result);
+        return Err("Config was not saved".to_string());
+    }
+
+    api.put("/api/config", &config)?;
+    if options.json {
+        return print_json(&json!({ "path": path, "value": new_value }));
+    }
+    println!("{} = {}", path, new_value);
+    Ok(())
+}
+
+fn config_validate(api: &ApiClient, options: &Options, file: Option<&str>) -> Result<(), String> {
+    let config = match file {
+        Some(file) => {
+            let content = std::fs::read_to_string(file)
+                .map_err(|e| format!("Failed to read {}: {}", file, e))?;
+            serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", file, e))?
+        }
+        None => read_config(api)?,
+    };
+
+    let result = api.post("/api/config/validate", Some(&config))?;
+    if options.json {
+        print_json(&result)?;
+    } else {
+        print_validation(&result);
+    }
+
+    if result["valid"].as_bool().unwrap_or(false) {
+        Ok(())
+    } else {
+        Err("Config is invalid".to_string())
+    }
+}
+
+fn print_validation(result: &Value) {
+    if result["valid"].as_bool().unwrap_or(false) {
+        println!("Config is valid");
+        return;
+    }
+    for error in result["errors"].as_array().into_iter().flatten() {
+        println!("- {}", error.as_str().unwrap_or_default());
+    }
+}
+
+// 按点分路径查找配置值；数组既可以用下标，也可以用元素的 id
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |current, key| match current {
+        Value::Object(map) => map.get(key),
+        Value::Array(items) => match key.parse::<usize>() {
+            Ok(index) => items.get(index),
+            Err(_) => items.iter().find(|item| item["id"] == key),
+        },
+        _ => None,
+    })
+}
+
+fn lookup_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    let mut current = value;
+    let mut keys = path.split('.').peekable();
+    while let Some(key) = keys.next() {
+        current = match current {
+            // 对象的最后一级允许新增字段
+            Value::Object(map) => {
+                if keys.peek().is_none() {
+                    map.entry(key).or_insert(Value::Null)
+                } else {
+                    map.get_mut(key)?
+                }
+            }
+            Value::Array(items) => match key.parse::<usize>() {
+                Ok(index) => items.get_mut(index)?,
+                Err(_) => items.iter_mut().find(|item| item["id"] == key)?,
+            },
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn print_json(value: &Value) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", text);
+    Ok(())
+}
+
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells.iter().zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(headers.to_vec()));
+    for row in rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+// 监听端口后面附上命名端口，例如 "8080 (http=8080)"
+fn format_ports(port: &Value, ports: &Value) -> String {
+    let named: Vec<String> = ports.as_object().into_iter()
+        .flatten()
+        .map(|(name, port)| format!("{}={}", name, text(port)))
+        .collect();
+    match (port.is_null(), named.is_empty()) {
+        (_, true) => text(port),
+        (true, false) => named.join(","),
+        (false, false) => format!("{} ({})", text(port), named.join(",")),
+    }
+}
+
+fn format_duration_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else {
+        format_uptime(ms / 1000)
+    }
+}
+
+fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes, secs) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60, seconds % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+// 对路径和查询参数中的 ID 做百分号编码
+fn encode(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
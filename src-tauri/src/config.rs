@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use thiserror::Error;
@@ -56,6 +56,9 @@ pub struct ProcessConfig {
     pub graceful_shutdown_timeout: Option<u64>,
     pub restart_on_crash: Option<bool>,
     pub env_file: Option<String>,
+    // 分离模式：服务在 ServiceFlow 退出后继续运行，输出写入日志文件
+    #[serde(default)]
+    pub detached: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,6 +84,27 @@ pub struct HealthCheckConfig {
     pub failure_threshold: u32,
     pub success_threshold: u32,
     pub start_period: Option<u64>,
+    #[serde(default)]
+    pub on_unhealthy: UnhealthyAction,
+}
+
+/// 健康检查失败后的处理：连续失败 failure_threshold 次后执行 action，
+/// 连续成功 success_threshold 次后才算恢复健康（重新计算自动重启次数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnhealthyPolicy {
+    pub action: UnhealthyAction,
+    pub failure_threshold: u32,
+    pub success_threshold: u32,
+}
+
+// 连续健康检查失败达到阈值后的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnhealthyAction {
+    #[default]
+    None,     // 只显示检查结果
+    Restart,  // 重启服务，次数和间隔使用 retryConfig 的限制
+    Stop,     // 停止服务
 }
 
 // 依赖类型
@@ -109,6 +133,143 @@ pub struct DependencyConfig {
     pub r#type: DependencyType,
     pub timeout: Option<u64>,
     pub health_check_required: Option<bool>,
+    // 冲突依赖的处理方式，默认报错
+    pub resolution: Option<ConflictResolution>,
+}
+
+// 冲突处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictResolution {
+    Error,    // 冲突服务在运行时拒绝启动
+    Replace,  // 先停止冲突服务，启动失败时恢复
+}
+
+// 命名端口
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PortSpec {
+    Fixed(u16),
+    Auto(AutoPort),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoPort {
+    Auto,
+}
+
+// 生命周期钩子：preStart 失败时放弃启动，其他钩子失败只记录警告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecycleHooks {
+    pub pre_start: Option<HookConfig>,
+    pub post_start: Option<HookConfig>,
+    pub pre_stop: Option<HookConfig>,
+    pub post_stop: Option<HookConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookConfig {
+    pub command: String,
+    pub args: Option<Vec<String>>,
+    // 超时（秒），默认 30 秒
+    pub timeout: Option<u64>,
+}
+
+// 文件监视配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchConfig {
+    // 监视的目录，默认为服务的工作目录
+    #[serde(default)]
+    pub paths: Vec<String>,
+    // 相对于监视目录的 glob，默认匹配所有文件
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    // 最后一次变化后等待的毫秒数，期间的变化合并为一次
+    #[serde(default = "default_watch_debounce")]
+    pub debounce: u64,
+    #[serde(default)]
+    pub action: WatchAction,
+    // action 为 signal 时发送的信号，默认 HUP
+    pub signal: Option<String>,
+    // 重启时同时重启依赖该服务的服务
+    #[serde(default = "default_true")]
+    pub cascade: bool,
+}
+
+fn default_watch_debounce() -> u64 {
+    500
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchAction {
+    #[default]
+    Restart,  // 重启服务
+    Signal,   // 向进程发送信号（例如让服务重新加载）
+}
+
+// 服务类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceKind {
+    #[default]
+    Daemon,   // 常驻服务，进程在运行即为正常
+    Oneshot,  // 一次性任务，退出码为 0 即为成功，依赖方等待它运行结束
+}
+
+// 定时运行配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleConfig {
+    // cron 表达式，支持 5 段（分 时 日 月 周）或带秒的 6 段
+    pub cron: String,
+    // IANA 时区（例如 Asia/Shanghai），默认使用本地时区
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub overlap: OverlapPolicy,
+    // 单次运行的超时（秒），超时后终止运行
+    pub timeout: Option<u64>,
+}
+
+impl ScheduleConfig {
+    /// 解析 cron 表达式和时区
+    pub fn parse(&self) -> Result<(cron::Schedule, Option<chrono_tz::Tz>), String> {
+        let expression = self.cron.trim();
+        // cron 库要求带秒的格式，5 段表达式在第 0 秒触发
+        let expression = if expression.split_whitespace().count() == 5 {
+            format!("0 {}", expression)
+        } else {
+            expression.to_string()
+        };
+        let schedule = expression.parse::<cron::Schedule>()
+            .map_err(|e| format!("Invalid cron expression '{}': {}", self.cron, e))?;
+        let timezone = match &self.timezone {
+            Some(name) => Some(name.parse::<chrono_tz::Tz>()
+                .map_err(|_| format!("Unknown timezone: {}", name))?),
+            None => None,
+        };
+        Ok((schedule, timezone))
+    }
+}
+
+// 上一次运行尚未结束时如何处理新的触发
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlapPolicy {
+    #[default]
+    Skip,   // 跳过本次运行
+    Queue,  // 等上一次运行结束后再运行（最多排队一次）
+    Kill,   // 终止上一次运行后再运行
 }
 
 // 保留旧的 HealthCheck 结构体以兼容旧配置
@@ -121,6 +282,11 @@ pub struct HealthCheck {
     pub url: Option<String>,
     pub interval: u64,
     pub timeout: u64,
+    // 连续失败多少次后执行 on_unhealthy，默认 3
+    #[serde(default)]
+    pub failure_threshold: Option<u32>,
+    #[serde(default)]
+    pub on_unhealthy: UnhealthyAction,
 }
 
 // 统一的健康检查配置，支持新旧两种格式
@@ -167,6 +333,27 @@ pub struct Service {
     pub health_check: Option<HealthCheckUnion>,
     #[serde(default)]
     pub metrics: Option<ServiceMetrics>,
+    // 实例数：设置后每个实例单独运行，ID 为 `服务ID#序号`
+    #[serde(default)]
+    pub replicas: Option<u32>,
+    // 第一个实例的端口，第 N 个实例的 ${PORT} 为 basePort + N
+    #[serde(default)]
+    pub base_port: Option<u16>,
+    // 命名端口：固定端口或 "auto"（启动时分配空闲端口），通过 ${port.名称} 或 ${svc.服务ID.port.名称} 引用
+    #[serde(default)]
+    pub ports: Option<BTreeMap<String, PortSpec>>,
+    // 定时运行：按 cron 表达式启动一次性运行
+    #[serde(default)]
+    pub schedule: Option<ScheduleConfig>,
+    // 服务类型：常驻服务或一次性任务
+    #[serde(default)]
+    pub kind: ServiceKind,
+    // 生命周期钩子
+    #[serde(default)]
+    pub hooks: Option<LifecycleHooks>,
+    // 开发模式：监视文件变化后自动重启或发送信号
+    #[serde(default)]
+    pub watch: Option<WatchConfig>,
     
     // 运行时信息
     #[serde(default)]
@@ -183,6 +370,142 @@ pub struct Service {
     pub last_crash_time: Option<String>,
 }
 
+impl Service {
+    /// 是否以分离模式运行
+    pub fn is_detached(&self) -> bool {
+        self.process_config.as_ref()
+            .and_then(|p| p.detached)
+            .unwrap_or(false)
+    }
+
+    /// 是否为一次性任务
+    pub fn is_oneshot(&self) -> bool {
+        self.kind == ServiceKind::Oneshot
+    }
+
+    /// 健康检查失败后的处理方式，未设置处理方式或健康检查未启用时为 None
+    pub fn unhealthy_policy(&self) -> Option<UnhealthyPolicy> {
+        let policy = match self.health_check.as_ref()? {
+            HealthCheckUnion::Old(hc) => {
+                let threshold = hc.failure_threshold.unwrap_or(DEFAULT_FAILURE_THRESHOLD);
+                // 旧格式没有 successThreshold，连续成功同样次数才算恢复健康
+                UnhealthyPolicy { action: hc.on_unhealthy, failure_threshold: threshold, success_threshold: threshold }
+            }
+            HealthCheckUnion::New(hc) if hc.enabled => UnhealthyPolicy {
+                action: hc.on_unhealthy,
+                failure_threshold: hc.failure_threshold,
+                success_threshold: hc.success_threshold,
+            },
+            HealthCheckUnion::New(_) => return None,
+        };
+        (policy.action != UnhealthyAction::None).then_some(policy)
+    }
+
+    /// 是否为多实例服务
+    pub fn is_replicated(&self) -> bool {
+        self.replicas.is_some()
+    }
+
+    /// 实例数，未设置 replicas 时为 1
+    pub fn replica_count(&self) -> u32 {
+        self.replicas.unwrap_or(1)
+    }
+
+    /// 第 `index` 个实例：ID 带上序号，参数、环境变量、工作目录和健康检查中的
+    /// `${INSTANCE}` 替换为序号、`${PORT}` 替换为 basePort + 序号
+    pub fn instance(&self, index: u32) -> Service {
+        let port = self.base_port.map(|port| port.saturating_add(index as u16));
+        let expand = |value: &str| {
+            let value = value.replace("${INSTANCE}", &index.to_string());
+            match port {
+                Some(port) => value.replace("${PORT}", &port.to_string()),
+                None => value,
+            }
+        };
+
+        let mut instance = self.clone();
+        if self.is_replicated() {
+            instance.id = instance_id(&self.id, index);
+            // 实例本身只对应一个进程
+            instance.replicas = None;
+        }
+        instance.args = self.args.as_ref().map(|args| args.iter().map(|a| expand(a)).collect());
+        let mut env: HashMap<String, String> = self.env.iter()
+            .flatten()
+            .map(|(key, value)| (key.clone(), expand(value)))
+            .collect();
+        if self.is_replicated() {
+            env.insert("INSTANCE".to_string(), index.to_string());
+            if let Some(port) = port {
+                env.insert("PORT".to_string(), port.to_string());
+            }
+        }
+        instance.env = if env.is_empty() && self.env.is_none() { None } else { Some(env) };
+        instance.work_dir = self.work_dir.as_deref().map(expand);
+        match &mut instance.health_check {
+            Some(HealthCheckUnion::Old(health_check)) => {
+                health_check.url = health_check.url.as_deref().map(expand);
+                health_check.host = health_check.host.as_deref().map(expand);
+                if health_check.port.is_none() {
+                    health_check.port = port;
+                }
+            }
+            Some(HealthCheckUnion::New(health_check)) => {
+                if let Some(check) = health_check.config.as_object_mut() {
+                    for key in ["url", "host"] {
+                        if let Some(serde_json::Value::String(value)) = check.get_mut(key) {
+                            *value = expand(value);
+                        }
+                    }
+                    if let Some(port) = port {
+                        check.entry("port").or_insert(port.into());
+                    }
+                }
+            }
+            None => {}
+        }
+        instance
+    }
+
+    /// 所有实例，未设置 replicas 时只有服务本身
+    pub fn instances(&self) -> Vec<Service> {
+        (0..self.replica_count()).map(|index| self.instance(index)).collect()
+    }
+
+    /// 启动前需要先启动的服务：旧的 dependencies 列表加上 dependencyConfigs 中的必需和可选依赖
+    pub fn start_dependencies(&self) -> Vec<&str> {
+        let dep_config_ids = self.dependency_configs.iter()
+            .flatten()
+            .filter(|d| !matches!(d.r#type, DependencyType::Conflict))
+            .map(|d| d.service_id.as_str());
+
+        let mut deps: Vec<&str> = Vec::new();
+        for dep in self.dependencies.iter().map(|d| d.as_str()).chain(dep_config_ids) {
+            if !deps.contains(&dep) {
+                deps.push(dep);
+            }
+        }
+        deps
+    }
+}
+
+/// 多实例服务中实例的 ID 与序号之间的分隔符
+pub const INSTANCE_SEPARATOR: char = '#';
+
+// 未设置 failureThreshold 时连续失败多少次算作不健康
+const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// 多实例服务中第 `index` 个实例的 ID
+pub fn instance_id(service_id: &str, index: u32) -> String {
+    format!("{}{}{}", service_id, INSTANCE_SEPARATOR, index)
+}
+
+/// 拆分实例 ID，返回服务 ID 和序号
+pub fn split_instance_id(instance_id: &str) -> Option<(&str, u32)> {
+    let (service_id, index) = instance_id.rsplit_once(INSTANCE_SEPARATOR)?;
+    Some((service_id, index.parse().ok()?))
+}
+
 // 保留旧的 Task 结构体作为别名，用于向后兼容
 pub type Task = Service;
 
@@ -204,6 +527,12 @@ pub struct ServiceGroup {
     pub dependencies: Vec<String>,
     #[serde(default)]
     pub startup_strategy: Option<StartupStrategy>,
+    // 组内同时启动的服务数上限，未设置时不限制
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+    // 原子启动：任一服务启动失败或未通过健康检查时，停止本次启动的服务
+    #[serde(default)]
+    pub atomic: bool,
 }
 
 // 保留旧的 Group 结构体作为别名，用于向后兼容
@@ -222,19 +551,84 @@ pub struct Application {
     pub updated_at: String,
 }
 
+// Web API 令牌权限范围，按权限从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+    Read,     // 只读：查看状态、日志
+    Operator, // 操作员：启动/停止服务
+    Admin,    // 管理员：读写配置
+}
+
+// API 令牌，只保存哈希值，明文仅在创建时返回一次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub token_hash: String,
+    pub scope: TokenScope,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+// Web 界面的 HTTP Basic 认证
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BasicAuthConfig {
+    pub username: String,
+    pub password_hash: String,
+    #[serde(default = "default_basic_auth_scope")]
+    pub scope: TokenScope,
+}
+
+fn default_basic_auth_scope() -> TokenScope {
+    TokenScope::Operator
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthSettings {
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+    #[serde(default)]
+    pub basic_auth: Option<BasicAuthConfig>,
+    // 是否对外开放无需认证的 /healthz
+    #[serde(default)]
+    pub public_healthz: bool,
+}
+
+// Web 服务器 TLS 配置（PEM 格式证书和私钥）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TlsSettings {
+    pub cert_path: String,
+    pub key_path: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Settings {
     pub server_port: u16,
+    #[serde(default = "default_bind_address")]
+    pub bind_address: String,
+    #[serde(default)]
+    pub tls: Option<TlsSettings>,
     pub auto_start: bool,
     #[serde(default = "default_theme")]
     pub theme: String,
+    #[serde(default)]
+    pub auth: Option<AuthSettings>,
 }
 
 fn default_theme() -> String {
     "system".to_string()
 }
 
+fn default_bind_address() -> String {
+    "127.0.0.1".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
@@ -249,8 +643,11 @@ impl Default for Config {
         Self {
             settings: Settings {
                 server_port: 8899,
+                bind_address: default_bind_address(),
+                tls: None,
                 auto_start: false,
                 theme: "system".to_string(),
+                auth: None,
             },
             groups: Vec::new(),
             applications: Vec::new(),
@@ -258,6 +655,263 @@ impl Default for Config {
     }
 }
 
+impl Config {
+    /// 检查配置中的 ID 和引用关系，返回发现的问题
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut group_ids = HashSet::new();
+        let mut service_ids = HashSet::new();
+        
+        for group in &self.groups {
+            if !group_ids.insert(group.id.as_str()) {
+                errors.push(format!("Duplicate group id: {}", group.id));
+            }
+            for service in &group.services {
+                if !service_ids.insert(service.id.as_str()) {
+                    errors.push(format!("Duplicate service id: {}", service.id));
+                }
+                if service.id.contains(INSTANCE_SEPARATOR) {
+                    errors.push(format!("Service id must not contain '{}': {}", INSTANCE_SEPARATOR, service.id));
+                }
+                if service.replicas == Some(0) {
+                    errors.push(format!("Service {} must have at least one replica", service.id));
+                }
+                if service.is_oneshot() && service.is_replicated() {
+                    errors.push(format!("Oneshot service {} cannot have replicas", service.id));
+                }
+                // 自动端口按服务分配，所有实例会拿到同一个端口
+                if service.is_replicated() {
+                    for (name, spec) in service.ports.iter().flatten() {
+                        if matches!(spec, PortSpec::Auto(_)) {
+                            errors.push(format!(
+                                "Service {}: automatic port '{}' cannot be used with replicas, use basePort and ${{PORT}} instead",
+                                service.id, name));
+                        }
+                    }
+                }
+                if service.is_oneshot() && service.is_detached() {
+                    errors.push(format!("Oneshot service {} cannot run detached", service.id));
+                }
+                if let Some(watch) = &service.watch {
+                    let has_work_dir = service.work_dir.as_deref().is_some_and(|wd| !wd.trim().is_empty());
+                    if watch.paths.is_empty() && !has_work_dir {
+                        errors.push(format!("Service {} watches files but has no watch paths or workDir", service.id));
+                    }
+                    for pattern in watch.include.iter().chain(watch.exclude.iter()) {
+                        if let Err(e) = globset::Glob::new(pattern) {
+                            errors.push(format!("Service {}: invalid watch glob '{}': {}", service.id, pattern, e));
+                        }
+                    }
+                    if let Some(signal) = &watch.signal {
+                        if crate::process::parse_signal(signal).is_none() {
+                            errors.push(format!("Service {}: unknown signal {}", service.id, signal));
+                        }
+                    }
+                }
+                if let Some(policy) = service.unhealthy_policy() {
+                    if policy.failure_threshold == 0 {
+                        errors.push(format!("Service {}: health check failureThreshold must be at least 1", service.id));
+                    }
+                    if policy.success_threshold == 0 {
+                        errors.push(format!("Service {}: health check successThreshold must be at least 1", service.id));
+                    }
+                }
+                if let Some(schedule) = &service.schedule {
+                    if let Err(e) = schedule.parse() {
+                        errors.push(format!("Service {}: {}", service.id, e));
+                    }
+                    if service.is_replicated() {
+                        errors.push(format!("Scheduled service {} cannot have replicas", service.id));
+                    }
+                }
+            }
+        }
+        
+        for group in &self.groups {
+            for dep in &group.dependencies {
+                if !group_ids.contains(dep.as_str()) {
+                    errors.push(format!("Group {} depends on unknown group {}", group.id, dep));
+                }
+            }
+            for service in &group.services {
+                if service.path.trim().is_empty() {
+                    errors.push(format!("Service {} has no executable path", service.id));
+                }
+                let dep_config_ids = service.dependency_configs.iter()
+                    .flatten()
+                    .map(|d| &d.service_id);
+                for dep in service.dependencies.iter().chain(dep_config_ids) {
+                    if !service_ids.contains(dep.as_str()) {
+                        errors.push(format!("Service {} depends on unknown service {}", service.id, dep));
+                    }
+                }
+            }
+        }
+        
+        for application in &self.applications {
+            for group_id in &application.group_ids {
+                if !group_ids.contains(group_id.as_str()) {
+                    errors.push(format!("Application {} references unknown group {}", application.id, group_id));
+                }
+            }
+        }
+        
+        if let Err(cycle) = self.group_order() {
+            errors.push(format!("Circular group dependency: {}", cycle.join(" → ")));
+        }
+        let all_services: Vec<&str> = self.groups.iter()
+            .flat_map(|g| g.services.iter())
+            .map(|s| s.id.as_str())
+            .collect();
+        if let Some(cycle) = self.find_dependency_cycle(&all_services) {
+            errors.push(format!("Circular dependency: {}", cycle.join(" → ")));
+        }
+        
+        errors
+    }
+
+    /// 按 ID 查找服务（可修改）
+    pub fn service_mut(&mut self, service_id: &str) -> Option<&mut Service> {
+        self.groups.iter_mut()
+            .flat_map(|g| g.services.iter_mut())
+            .find(|s| s.id == service_id)
+    }
+
+    /// 与服务冲突的服务及处理方式
+    ///
+    /// 冲突是对称的：任意一方声明即可；任意一方声明为 replace 时按 replace 处理。
+    pub fn conflicts_of(&self, service_id: &str) -> Vec<(&str, ConflictResolution)> {
+        let mut conflicts: Vec<(&str, ConflictResolution)> = Vec::new();
+        for service in self.groups.iter().flat_map(|g| g.services.iter()) {
+            for dep in service.dependency_configs.iter().flatten() {
+                if !matches!(dep.r#type, DependencyType::Conflict) {
+                    continue;
+                }
+                let other = if service.id == service_id {
+                    dep.service_id.as_str()
+                } else if dep.service_id == service_id {
+                    service.id.as_str()
+                } else {
+                    continue;
+                };
+                if other == service_id {
+                    continue;
+                }
+                let resolution = dep.resolution.unwrap_or(ConflictResolution::Error);
+                match conflicts.iter_mut().find(|(id, _)| *id == other) {
+                    Some(existing) if resolution == ConflictResolution::Replace => existing.1 = resolution,
+                    Some(_) => {}
+                    None => conflicts.push((other, resolution)),
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// 从指定服务出发沿启动依赖查找循环，返回循环路径，例如 `[a, b, c, a]`
+    ///
+    /// 未知的服务 ID 会被忽略，由 `validate` 单独报告。
+    pub fn find_dependency_cycle(&self, roots: &[&str]) -> Option<Vec<String>> {
+        let services: HashMap<&str, &Service> = self.groups.iter()
+            .flat_map(|g| g.services.iter())
+            .map(|s| (s.id.as_str(), s))
+            .collect();
+
+        let mut finished = HashSet::new();
+        let mut path = Vec::new();
+        roots.iter().find_map(|root| find_cycle_from(root, &services, &mut finished, &mut path))
+    }
+
+    /// 服务组的启动顺序：被依赖的组在前，没有依赖关系的组按 `order` 排序（相同时保持配置顺序）
+    ///
+    /// 未知的组依赖会被忽略；存在循环依赖时返回循环路径，例如 `[a, b, a]`。
+    pub fn group_order(&self) -> Result<Vec<&ServiceGroup>, Vec<String>> {
+        let known: HashSet<&str> = self.groups.iter().map(|g| g.id.as_str()).collect();
+
+        let mut remaining: Vec<(usize, &ServiceGroup)> = self.groups.iter().enumerate().collect();
+        remaining.sort_by_key(|(index, group)| (group.order, *index));
+
+        let mut order: Vec<&ServiceGroup> = Vec::new();
+        let mut placed = HashSet::new();
+        while !remaining.is_empty() {
+            // 每次取出依赖已全部就位、排序最靠前的组
+            let Some(position) = remaining.iter()
+                .position(|(_, group)| group_dependencies(group, &known).iter().all(|d| placed.contains(d))) else {
+                return Err(find_group_cycle(remaining.iter().map(|(_, g)| *g).collect(), &known));
+            };
+            let (_, group) = remaining.remove(position);
+            placed.insert(group.id.as_str());
+            order.push(group);
+        }
+
+        Ok(order)
+    }
+}
+
+// 深度优先搜索，path 为当前搜索路径，遇到路径上的服务即为循环
+fn find_cycle_from<'a>(
+    id: &'a str,
+    services: &HashMap<&'a str, &'a Service>,
+    finished: &mut HashSet<&'a str>,
+    path: &mut Vec<&'a str>,
+) -> Option<Vec<String>> {
+    if let Some(start) = path.iter().position(|p| *p == id) {
+        let mut cycle: Vec<String> = path[start..].iter().map(|p| p.to_string()).collect();
+        cycle.push(id.to_string());
+        return Some(cycle);
+    }
+    if finished.contains(id) {
+        return None;
+    }
+    let service = services.get(id)?;
+
+    path.push(id);
+    for dep in service.start_dependencies() {
+        if let Some(cycle) = find_cycle_from(dep, services, finished, path) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    finished.insert(id);
+    None
+}
+
+// 组依赖的其他已知组
+fn group_dependencies<'a>(group: &'a ServiceGroup, known: &HashSet<&str>) -> Vec<&'a str> {
+    group.dependencies.iter()
+        .map(|d| d.as_str())
+        .filter(|d| known.contains(d))
+        .collect()
+}
+
+// 在无法排序的组中沿依赖关系查找一条循环路径
+fn find_group_cycle(groups: Vec<&ServiceGroup>, known: &HashSet<&str>) -> Vec<String> {
+    let mut path: Vec<&str> = Vec::new();
+    let mut current = groups[0];
+    loop {
+        if let Some(start) = path.iter().position(|id| *id == current.id) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|id| id.to_string()).collect();
+            cycle.push(current.id.clone());
+            return cycle;
+        }
+        path.push(current.id.as_str());
+        // 剩余的组都至少依赖一个剩余的组，因此一定能走回已经过的组
+        current = group_dependencies(current, known).iter()
+            .find_map(|dep| groups.iter().find(|g| g.id == *dep))
+            .copied()
+            .unwrap_or(current);
+    }
+}
+
+impl RetryConfig {
+    /// 第 attempt 次（从 0 开始）重试前的等待时间：retryDelay × backoffMultiplier^attempt
+    pub fn delay(&self, attempt: u32) -> std::time::Duration {
+        let multiplier = self.backoff_multiplier.unwrap_or(1.0) as f64;
+        let millis = self.retry_delay as f64 * multiplier.powi(attempt as i32);
+        std::time::Duration::from_millis(millis as u64)
+    }
+}
+
 impl Default for RetryConfig {
     fn default() -> Self {
         Self {
@@ -322,12 +976,15 @@ impl ConfigManager {
         &self.config
     }
     
-    #[allow(dead_code)]
     pub fn get_mut(&mut self) -> &mut Config {
         &mut self.config
     }
     
-    pub fn set(&mut self, config: Config) {
+    pub fn set(&mut self, mut config: Config) {
+        // 前端不回传认证设置时保留已有的令牌，避免保存配置时被清空
+        if config.settings.auth.is_none() {
+            config.settings.auth = self.config.settings.auth.take();
+        }
         self.config = config;
     }
 }
@@ -366,4 +1023,145 @@ pub fn update_config(config: Config) -> Result<(), ConfigError> {
     } else {
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn service(id: &str, dependencies: &[&str]) -> Service {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": id,
+            "path": "/bin/true",
+            "autoStart": true,
+            "dependencies": dependencies,
+        }))
+        .unwrap()
+    }
+
+    fn group(id: &str, order: i32, dependencies: &[&str], services: Vec<Service>) -> ServiceGroup {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": id,
+            "startupDelay": 0,
+            "order": order,
+            "dependencies": dependencies,
+            "services": services,
+        }))
+        .unwrap()
+    }
+
+    fn config(groups: Vec<ServiceGroup>) -> Config {
+        Config { groups, ..Config::default() }
+    }
+
+    fn conflicting(mut service: Service, other: &str, resolution: Option<ConflictResolution>) -> Service {
+        service.dependency_configs.get_or_insert_with(Vec::new).push(DependencyConfig {
+            service_id: other.to_string(),
+            r#type: DependencyType::Conflict,
+            timeout: None,
+            health_check_required: None,
+            resolution,
+        });
+        service
+    }
+
+    fn order_ids(config: &Config) -> Vec<&str> {
+        config.group_order().unwrap().iter().map(|g| g.id.as_str()).collect()
+    }
+
+    #[test]
+    fn groups_start_after_their_dependencies() {
+        let config = config(vec![
+            group("web", 0, &["db"], vec![]),
+            group("db", 5, &[], vec![]),
+            group("tools", 1, &[], vec![]),
+        ]);
+        assert_eq!(order_ids(&config), ["tools", "db", "web"]);
+    }
+
+    #[test]
+    fn unknown_group_dependencies_are_ignored() {
+        let config = config(vec![group("web", 0, &["missing"], vec![])]);
+        assert_eq!(order_ids(&config), ["web"]);
+    }
+
+    #[test]
+    fn group_cycle_reports_the_path() {
+        let config = config(vec![
+            group("tools", 0, &[], vec![]),
+            group("a", 1, &["b"], vec![]),
+            group("b", 2, &["c"], vec![]),
+            group("c", 3, &["a"], vec![]),
+        ]);
+        assert_eq!(config.group_order().unwrap_err(), ["a", "b", "c", "a"]);
+        assert!(config.validate().contains(&"Circular group dependency: a → b → c → a".to_string()));
+    }
+
+    #[test]
+    fn group_self_dependency_is_a_cycle() {
+        let config = config(vec![group("a", 0, &["a"], vec![])]);
+        assert_eq!(config.group_order().unwrap_err(), ["a", "a"]);
+    }
+
+    #[test]
+    fn service_cycle_reports_the_path() {
+        let config = config(vec![group("g", 0, &[], vec![
+            service("api", &["cache"]),
+            service("cache", &["db"]),
+            service("db", &["api"]),
+            service("worker", &["api"]),
+        ])]);
+        assert_eq!(
+            config.find_dependency_cycle(&["worker"]),
+            Some(vec!["api".to_string(), "cache".to_string(), "db".to_string(), "api".to_string()])
+        );
+        assert!(config.validate().iter().any(|e| e.starts_with("Circular dependency: ")));
+    }
+
+    #[test]
+    fn acyclic_services_have_no_cycle() {
+        let config = config(vec![group("g", 0, &[], vec![
+            service("api", &["db", "cache"]),
+            service("cache", &["db"]),
+            service("db", &["unknown"]),
+        ])]);
+        assert_eq!(config.find_dependency_cycle(&["api", "cache", "db"]), None);
+    }
+
+    #[test]
+    fn conflicts_are_symmetric() {
+        let config = config(vec![group("g", 0, &[], vec![
+            conflicting(service("nginx", &[]), "apache", None),
+            service("apache", &[]),
+            service("other", &[]),
+        ])]);
+        assert_eq!(config.conflicts_of("nginx"), [("apache", ConflictResolution::Error)]);
+        assert_eq!(config.conflicts_of("apache"), [("nginx", ConflictResolution::Error)]);
+        assert!(config.conflicts_of("other").is_empty());
+    }
+
+    #[test]
+    fn replace_on_either_side_wins() {
+        let config = config(vec![group("g", 0, &[], vec![
+            conflicting(service("blue", &[]), "green", None),
+            conflicting(service("green", &[]), "blue", Some(ConflictResolution::Replace)),
+        ])]);
+        // 双方都声明时只列出一次，任意一方为 replace 即按 replace 处理
+        assert_eq!(config.conflicts_of("blue"), [("green", ConflictResolution::Replace)]);
+        assert_eq!(config.conflicts_of("green"), [("blue", ConflictResolution::Replace)]);
+    }
+
+    #[test]
+    fn conflicts_are_not_start_dependencies() {
+        let config = config(vec![group("g", 0, &[], vec![
+            conflicting(service("a", &[]), "b", None),
+            conflicting(service("b", &[]), "a", None),
+            conflicting(service("c", &[]), "c", None),
+        ])]);
+        assert!(config.find_dependency_cycle(&["a", "b"]).is_none());
+        // 与自身的冲突没有意义，不会被列出
+        assert!(config.conflicts_of("c").is_empty());
+    }
+}
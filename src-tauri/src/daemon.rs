@@ -0,0 +1,319 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use sysinfo::{Pid, System, SystemExt};
+use tokio::runtime::Handle;
+use tokio::sync::broadcast;
+
+use crate::auth;
+use crate::config::{self, AuthSettings, Config, TokenScope};
+use crate::database;
+use crate::healthcheck::{CheckResult, HealthChecker};
+use crate::orchestrator::{Job, JobState, Operation, Orchestrator};
+use crate::process::ProcessManager;
+use crate::web::{self, ListenConfig, WebServer, WebState};
+
+// 与 tauri.conf.json 中的 identifier 保持一致，保证和桌面端使用同一份数据目录
+const APP_IDENTIFIER: &str = "com.serviceflow.app";
+
+/// 无界面（守护进程）模式的启动参数
+#[derive(Debug)]
+pub struct DaemonOptions {
+    pub config_path: PathBuf,
+    pub data_dir: PathBuf,
+    pub pid_file: PathBuf,
+    // 指定 `--create-token` 时只创建令牌并退出，不启动服务
+    pub create_token: Option<TokenScope>,
+    pub token_name: String,
+}
+
+impl DaemonOptions {
+    /// 解析命令行参数，未指定 `--headless` 时返回 `None`
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Self>, String> {
+        let mut headless = false;
+        let mut config_path = None;
+        let mut data_dir = None;
+        let mut pid_file = None;
+        let mut create_token = None;
+        let mut token_name = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or(format!("Missing value for {}", name));
+            match arg.as_str() {
+                "--headless" => headless = true,
+                "--config" => config_path = Some(PathBuf::from(value("--config")?)),
+                "--data-dir" => data_dir = Some(PathBuf::from(value("--data-dir")?)),
+                "--pid-file" => pid_file = Some(PathBuf::from(value("--pid-file")?)),
+                "--create-token" => create_token = Some(parse_scope(&value("--create-token")?)?),
+                "--token-name" => token_name = Some(value("--token-name")?),
+                // 其余参数交给 Tauri 处理
+                _ => {}
+            }
+        }
+
+        if !headless && create_token.is_none() {
+            return Ok(None);
+        }
+
+        let data_dir = match data_dir {
+            Some(dir) => dir,
+            None => dirs::data_dir()
+                .ok_or("Failed to determine data directory, use --data-dir".to_string())?
+                .join(APP_IDENTIFIER),
+        };
+
+        Ok(Some(Self {
+            config_path: config_path.unwrap_or_else(|| data_dir.join("config.json")),
+            pid_file: pid_file.unwrap_or_else(|| data_dir.join("service-flow.pid")),
+            data_dir,
+            create_token,
+            token_name: token_name.unwrap_or_else(|| "cli".to_string()),
+        }))
+    }
+}
+
+fn parse_scope(value: &str) -> Result<TokenScope, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| format!("Invalid token scope: {} (expected read, operator or admin)", value))
+}
+
+/// 在配置文件中创建 API 令牌并把明文输出到标准输出，用于首次配置 Web 访问
+///
+/// 运行中的实例只在内存中保存配置，之后保存时会覆盖这里写入的令牌，因此要求先停止服务。
+pub fn create_token(options: &DaemonOptions, scope: TokenScope) -> Result<String, String> {
+    if let Some(pid) = running_pid(&options.pid_file) {
+        return Err(format!("ServiceFlow is running (PID {}), stop it before creating a token", pid));
+    }
+
+    let config_path = options.config_path.to_str()
+        .ok_or(format!("Invalid config path: {}", options.config_path.display()))?;
+    let mut manager = config::ConfigManager::new(config_path).map_err(|e| e.to_string())?;
+    let (token, record) = auth::issue_token(options.token_name.clone(), scope);
+    manager.get_mut().settings.auth.get_or_insert_with(AuthSettings::default).tokens.push(record);
+    manager.save().map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+/// 以无界面模式运行，直到收到 SIGTERM / Ctrl+C
+pub fn run(options: DaemonOptions) -> Result<(), String> {
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    runtime.block_on(run_async(options))
+}
+
+async fn run_async(options: DaemonOptions) -> Result<(), String> {
+    log::info!("ServiceFlow starting in headless mode...");
+
+    fs::create_dir_all(&options.data_dir).map_err(|e| e.to_string())?;
+    write_pid_file(&options.pid_file)?;
+
+    let result = serve(&options).await;
+
+    if let Err(e) = fs::remove_file(&options.pid_file) {
+        log::warn!("Failed to remove PID file {}: {}", options.pid_file.display(), e);
+    }
+
+    result
+}
+
+async fn serve(options: &DaemonOptions) -> Result<(), String> {
+    // 初始化配置
+    let config_path = options.config_path.to_str()
+        .ok_or(format!("Invalid config path: {}", options.config_path.display()))?;
+    log::info!("Loading config from: {}", config_path);
+    let config_manager = config::ConfigManager::new(config_path).map_err(|e| e.to_string())?;
+    let config = Arc::new(Mutex::new(Some(config_manager)));
+
+    // 初始化数据库
+    let db_path = options.data_dir.join("service-flow.db");
+    let database_ready = match database::init(&db_path.to_string_lossy()).await {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("Failed to initialize database: {}", e);
+            false
+        }
+    };
+
+    // 日志和健康检查结果写入日志并推送给 Web 客户端
+    let (events, _) = broadcast::channel(1024);
+    let events_log = events.clone();
+    let log_callback = Arc::new(move |task_id: &str, log: &str| {
+        log::info!(target: "service", "[{}] {}", task_id, log);
+        web::publish(&events_log, format!("log:{}", task_id), log);
+    });
+    let events_job = events.clone();
+    let job_callback = Arc::new(move |job: &Job| {
+        if job.state == JobState::Failed {
+            log::warn!("Job {} ({:?}) failed: {}", job.id, job.operation, job.error.as_deref().unwrap_or_default());
+        }
+        web::publish(&events_job, "job:progress", job);
+    });
+
+    let process_manager = Arc::new(ProcessManager::new(log_callback));
+    process_manager.set_log_dir(options.data_dir.join("logs"));
+    let orchestrator = Arc::new(Orchestrator::new(process_manager.clone(), Handle::current(), job_callback));
+    // 健康检查连续失败时按 onUnhealthy 重启或停止服务
+    let events_health = events.clone();
+    let orchestrator_health = orchestrator.clone();
+    let health_check_callback = Arc::new(move |result: CheckResult| {
+        if !result.status {
+            log::warn!("Health check failed for {}: {}", result.task_id, result.message);
+        }
+        web::publish(&events_health, format!("healthcheck:{}", result.task_id), &result);
+        orchestrator_health.report_health(&result);
+    });
+    let health_checker = Arc::new(HealthChecker::new(health_check_callback));
+    orchestrator.set_health_checker(health_checker.clone());
+    let web_server = Arc::new_cyclic(|server: &Weak<WebServer>| {
+        // 通过 Web 接口修改监听配置时重启监听器
+        let server = server.clone();
+        let on_listen_change = Arc::new(move |listen: ListenConfig| {
+            let Some(web_server) = server.upgrade() else {
+                return;
+            };
+            tokio::spawn(async move {
+                if let Err(e) = web_server.start(&listen).await {
+                    log::error!("Failed to restart web server: {}", e);
+                }
+            });
+        });
+        WebServer::new(WebState {
+            config: config.clone(),
+            process_manager: process_manager.clone(),
+            orchestrator: orchestrator.clone(),
+            health_checker: health_checker.clone(),
+            events,
+            tickets: Default::default(),
+            on_listen_change,
+        })
+    });
+
+    // 重新接管上次以分离模式运行的服务
+    if database_ready {
+        orchestrator.ports().load().await;
+        process_manager.reattach().await;
+    }
+    
+    let snapshot = current_config(&config)?;
+    web_server.start(&ListenConfig::from(&snapshot.settings)).await
+        .map_err(|e| e.to_string())?;
+
+    log::info!("ServiceFlow started successfully (PID {})", std::process::id());
+
+    // 自动启动服务，期间收到退出信号时取消自动启动；编排启动的服务会开启健康检查，重新接管的服务在这里开启
+    orchestrator.reload(&snapshot);
+    orchestrator.check_running(&snapshot);
+    let startup = async {
+        if snapshot.settings.auto_start {
+            match orchestrator.run(snapshot.clone(), Operation::AutoStart).await {
+                Ok(job) => log::info!("Auto-start finished: {:?}", job.state),
+                Err(e) => log::error!("Failed to start all tasks: {}", e),
+            }
+        }
+    };
+    let shutdown = wait_for_shutdown();
+    tokio::pin!(shutdown);
+    tokio::select! {
+        _ = &mut shutdown => {}
+        _ = startup => shutdown.await,
+    }
+    log::info!("Shutting down, stopping services in dependency order...");
+
+    orchestrator.stop_triggers();
+    orchestrator.cancel_all();
+    web_server.stop().await;
+
+    // 使用关闭时的最新配置，以覆盖运行期间通过 API 修改过的服务
+    let mut shutdown_config = current_config(&config)?;
+    for service in shutdown_config.groups.iter().flat_map(|g| g.services.iter()) {
+        health_checker.stop_checking(&service.id);
+    }
+    // 分离模式的服务在退出后继续运行
+    for group in &mut shutdown_config.groups {
+        group.services.retain(|service| !service.is_detached());
+    }
+    if let Err(e) = orchestrator.run(shutdown_config, Operation::StopAll).await {
+        log::error!("Failed to stop all tasks: {}", e);
+    }
+
+    log::info!("ServiceFlow stopped");
+    Ok(())
+}
+
+fn current_config(config: &Arc<Mutex<Option<config::ConfigManager>>>) -> Result<Config, String> {
+    config.lock().unwrap().as_ref()
+        .map(|manager| manager.get().clone())
+        .ok_or("Config not loaded".to_string())
+}
+
+// PID 文件中记录的进程仍在运行时返回其 PID
+fn running_pid(path: &Path) -> Option<usize> {
+    let pid = fs::read_to_string(path).ok()?.trim().parse::<usize>().ok()?;
+    System::new().refresh_process(Pid::from(pid)).then_some(pid)
+}
+
+// 写入 PID 文件，若文件中的进程仍在运行则拒绝启动
+fn write_pid_file(path: &Path) -> Result<(), String> {
+    if let Some(pid) = running_pid(path) {
+        return Err(format!("ServiceFlow is already running (PID {}, {})", pid, path.display()));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(path, std::process::id().to_string())
+        .map_err(|e| format!("Failed to write PID file {}: {}", path.display(), e))
+}
+
+async fn wait_for_shutdown() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = match signal(SignalKind::terminate()) {
+            Ok(terminate) => terminate,
+            Err(e) => {
+                log::error!("Failed to listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                return;
+            }
+        };
+        tokio::select! {
+            _ = terminate.recv() => log::info!("Received SIGTERM"),
+            _ = tokio::signal::ctrl_c() => log::info!("Received SIGINT"),
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        log::info!("Received Ctrl+C");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<DaemonOptions>, String> {
+        DaemonOptions::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn create_token_does_not_require_headless() {
+        let options = parse(&["--create-token", "admin", "--data-dir", "/tmp/sf"]).unwrap().unwrap();
+        assert_eq!(options.create_token, Some(TokenScope::Admin));
+        assert_eq!(options.token_name, "cli");
+        assert_eq!(options.config_path, PathBuf::from("/tmp/sf/config.json"));
+    }
+
+    #[test]
+    fn invalid_scope_is_rejected() {
+        assert!(parse(&["--headless", "--create-token", "root"]).is_err());
+        assert!(parse(&["--create-token"]).is_err());
+    }
+
+    #[test]
+    fn desktop_mode_without_flags() {
+        assert!(parse(&["--some-tauri-flag"]).unwrap().is_none());
+    }
+}
@@ -1,5 +1,7 @@
+use serde::Serialize;
 use sqlx::SqlitePool;
 use thiserror::Error;
+use std::future::Future;
 use std::sync::Arc;
 
 #[derive(Error, Debug)]
@@ -12,6 +14,46 @@ pub enum DatabaseError {
     InitializationError(String),
 }
 
+/// 分离模式下运行的进程，用于 ServiceFlow 重启后重新接管
+#[derive(Debug, Clone)]
+pub struct DetachedProcessRecord {
+    pub task_id: String,
+    pub pid: u32,
+    // 进程启动时间（Unix 时间戳，秒），用于防止 PID 被复用
+    pub start_time: u64,
+    pub log_path: String,
+}
+
+/// 一次性运行（例如定时运行）的记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+    pub id: i64,
+    pub service_id: String,
+    // 触发方式：schedule（定时）或 start（一次性服务随启动运行）
+    pub trigger: String,
+    // succeeded / failed / timedOut / killed / skipped
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub started_at: String,
+    pub duration_ms: u64,
+    // 运行期间最后的若干行输出
+    pub log_excerpt: String,
+}
+
+/// 服务事件记录，例如健康检查失败后自动执行的操作
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventRecord {
+    pub id: i64,
+    pub service_id: String,
+    // 执行的操作：restart / stop / restartLimitReached
+    pub action: String,
+    pub success: bool,
+    pub message: String,
+    pub created_at: String,
+}
+
 pub struct DatabaseManager {
     pool: Arc<SqlitePool>,
 }
@@ -58,6 +100,74 @@ impl DatabaseManager {
         .execute(&*self.pool)
         .await?;
         
+        // 创建分离进程表
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS detached_processes (
+                task_id TEXT PRIMARY KEY,
+                pid INTEGER NOT NULL,
+                start_time INTEGER NOT NULL,
+                log_path TEXT NOT NULL,
+                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+        "#)
+        .execute(&*self.pool)
+        .await?;
+        
+        // 创建运行记录表
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS service_runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                service_id TEXT NOT NULL,
+                trigger TEXT NOT NULL,
+                status TEXT NOT NULL,
+                exit_code INTEGER,
+                started_at TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                log_excerpt TEXT NOT NULL
+            )
+        "#)
+        .execute(&*self.pool)
+        .await?;
+        
+        sqlx::query(r#"
+            CREATE INDEX IF NOT EXISTS idx_service_runs_service ON service_runs (service_id, id)
+        "#)
+        .execute(&*self.pool)
+        .await?;
+        
+        // 创建服务事件表
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS service_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                service_id TEXT NOT NULL,
+                action TEXT NOT NULL,
+                success BOOLEAN NOT NULL,
+                message TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )
+        "#)
+        .execute(&*self.pool)
+        .await?;
+        
+        sqlx::query(r#"
+            CREATE INDEX IF NOT EXISTS idx_service_events_service ON service_events (service_id, id)
+        "#)
+        .execute(&*self.pool)
+        .await?;
+        
+        // 创建自动端口分配表
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS port_allocations (
+                service_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                port INTEGER NOT NULL,
+                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (service_id, name)
+            )
+        "#)
+        .execute(&*self.pool)
+        .await?;
+        
         Ok(())
     }
     
@@ -123,21 +233,195 @@ impl DatabaseManager {
             None => Ok(None),
         }
     }
+    
+    pub async fn save_detached_process(&self, record: &DetachedProcessRecord) -> Result<(), DatabaseError> {
+        sqlx::query(r#"
+            INSERT OR REPLACE INTO detached_processes (task_id, pid, start_time, log_path)
+            VALUES (?, ?, ?, ?)
+        "#)
+        .bind(&record.task_id)
+        .bind(record.pid as i64)
+        .bind(record.start_time as i64)
+        .bind(&record.log_path)
+        .execute(&*self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn remove_detached_process(&self, task_id: &str) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM detached_processes WHERE task_id = ?")
+            .bind(task_id)
+            .execute(&*self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn save_port_allocation(&self, service_id: &str, name: &str, port: u16) -> Result<(), DatabaseError> {
+        sqlx::query(r#"
+            INSERT OR REPLACE INTO port_allocations (service_id, name, port)
+            VALUES (?, ?, ?)
+        "#)
+        .bind(service_id)
+        .bind(name)
+        .bind(port as i64)
+        .execute(&*self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn get_port_allocations(&self) -> Result<Vec<(String, String, u16)>, DatabaseError> {
+        let rows = sqlx::query_as::<_, (String, String, i64)>(r#"
+            SELECT service_id, name, port FROM port_allocations
+        "#)
+        .fetch_all(&*self.pool)
+        .await?;
+        
+        Ok(rows.into_iter()
+            .map(|(service_id, name, port)| (service_id, name, port as u16))
+            .collect())
+    }
+    
+    pub async fn add_run(&self, record: &RunRecord) -> Result<(), DatabaseError> {
+        sqlx::query(r#"
+            INSERT INTO service_runs (service_id, trigger, status, exit_code, started_at, duration_ms, log_excerpt)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#)
+        .bind(&record.service_id)
+        .bind(&record.trigger)
+        .bind(&record.status)
+        .bind(record.exit_code)
+        .bind(&record.started_at)
+        .bind(record.duration_ms as i64)
+        .bind(&record.log_excerpt)
+        .execute(&*self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// 服务最近的运行记录，最新的在前
+    pub async fn get_runs(&self, service_id: &str, limit: i64) -> Result<Vec<RunRecord>, DatabaseError> {
+        let rows = sqlx::query_as::<_, (i64, String, String, String, Option<i32>, String, i64, String)>(r#"
+            SELECT id, service_id, trigger, status, exit_code, started_at, duration_ms, log_excerpt
+            FROM service_runs
+            WHERE service_id = ?
+            ORDER BY id DESC
+            LIMIT ?
+        "#)
+        .bind(service_id)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await?;
+        
+        Ok(rows.into_iter()
+            .map(|(id, service_id, trigger, status, exit_code, started_at, duration_ms, log_excerpt)| RunRecord {
+                id,
+                service_id,
+                trigger,
+                status,
+                exit_code,
+                started_at,
+                duration_ms: duration_ms as u64,
+                log_excerpt,
+            })
+            .collect())
+    }
+    
+    pub async fn add_event(&self, record: &EventRecord) -> Result<(), DatabaseError> {
+        sqlx::query(r#"
+            INSERT INTO service_events (service_id, action, success, message, created_at)
+            VALUES (?, ?, ?, ?, ?)
+        "#)
+        .bind(&record.service_id)
+        .bind(&record.action)
+        .bind(record.success)
+        .bind(&record.message)
+        .bind(&record.created_at)
+        .execute(&*self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// 服务最近的事件，最新的在前
+    pub async fn get_events(&self, service_id: &str, limit: i64) -> Result<Vec<EventRecord>, DatabaseError> {
+        let rows = sqlx::query_as::<_, (i64, String, String, bool, String, String)>(r#"
+            SELECT id, service_id, action, success, message, created_at
+            FROM service_events
+            WHERE service_id = ?
+            ORDER BY id DESC
+            LIMIT ?
+        "#)
+        .bind(service_id)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await?;
+        
+        Ok(rows.into_iter()
+            .map(|(id, service_id, action, success, message, created_at)| EventRecord {
+                id,
+                service_id,
+                action,
+                success,
+                message,
+                created_at,
+            })
+            .collect())
+    }
+    
+    pub async fn get_detached_processes(&self) -> Result<Vec<DetachedProcessRecord>, DatabaseError> {
+        let rows = sqlx::query_as::<_, (String, i64, i64, String)>(r#"
+            SELECT task_id, pid, start_time, log_path FROM detached_processes
+        "#)
+        .fetch_all(&*self.pool)
+        .await?;
+        
+        Ok(rows.into_iter()
+            .map(|(task_id, pid, start_time, log_path)| DetachedProcessRecord {
+                task_id,
+                pid: pid as u32,
+                start_time: start_time as u64,
+                log_path,
+            })
+            .collect())
+    }
 }
 
 // 全局数据库实例
 lazy_static::lazy_static! {
     pub static ref DATABASE_MANAGER: std::sync::Mutex<Option<Arc<DatabaseManager>>> = std::sync::Mutex::new(None);
+    // 数据库所在的运行时，供同步代码提交数据库操作
+    static ref RUNTIME: std::sync::Mutex<Option<tokio::runtime::Handle>> = std::sync::Mutex::new(None);
 }
 
 pub async fn init(db_path: &str) -> Result<(), DatabaseError> {
     let db_manager = DatabaseManager::new(db_path).await?;
     db_manager.init().await?;
     *DATABASE_MANAGER.lock().unwrap() = Some(Arc::new(db_manager));
+    *RUNTIME.lock().unwrap() = Some(tokio::runtime::Handle::current());
     Ok(())
 }
 
-#[allow(dead_code)]
+/// 在同步代码中提交数据库操作，数据库未初始化时忽略
+pub fn spawn<F, Fut>(operation: F)
+where
+    F: FnOnce(Arc<DatabaseManager>) -> Fut,
+    Fut: Future<Output = Result<(), DatabaseError>> + Send + 'static,
+{
+    let (Some(db), Some(runtime)) = (get_db(), RUNTIME.lock().unwrap().clone()) else {
+        return;
+    };
+    let future = operation(db);
+    runtime.spawn(async move {
+        if let Err(e) = future.await {
+            log::error!("Database operation failed: {}", e);
+        }
+    });
+}
+
 pub fn get_db() -> Option<Arc<DatabaseManager>> {
     DATABASE_MANAGER.lock().unwrap().clone()
 }
\ No newline at end of file
@@ -1,11 +1,17 @@
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use tokio::spawn;
 use reqwest::blocking::Client;
+use reqwest::Method;
 use std::net::TcpStream;
-use crate::config::{Task, HealthCheckUnion, HealthCheck};
+use crate::config::{split_instance_id, Task, HealthCheckUnion, HealthCheck, HealthCheckConfig};
+
+// 检查命令是否结束的间隔
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CheckResult {
@@ -16,9 +22,109 @@ pub struct CheckResult {
 
 pub type HealthCheckCallback = Arc<dyn Fn(CheckResult) + Send + Sync + 'static>;
 
+// 检查方式，新旧两种配置格式都转换为它
+#[derive(Debug, Clone)]
+enum Probe {
+    Tcp { host: String, port: u16 },
+    Http { url: String, method: Option<String>, headers: HashMap<String, String>, expected_status: Option<u16> },
+    Command { command: String, args: Vec<String> },
+    // 配置有误，每次检查都失败并给出原因
+    Invalid(String),
+}
+
+#[derive(Debug, Clone)]
+struct CheckSpec {
+    probe: Probe,
+    interval: Duration,
+    timeout: Duration,
+    // 单次检查失败后立即重试的次数
+    retries: u32,
+    // 启动后等待多久再开始检查
+    start_period: Duration,
+}
+
+impl CheckSpec {
+    // 服务的健康检查，未配置或新格式中 enabled 为 false 时为 None
+    fn of(task: &Task) -> Option<Self> {
+        match task.health_check.as_ref()? {
+            HealthCheckUnion::Old(health_check) => Some(Self::from_old(health_check)),
+            HealthCheckUnion::New(health_check) if health_check.enabled => Some(Self::from_new(health_check)),
+            HealthCheckUnion::New(_) => None,
+        }
+    }
+
+    fn from_old(health_check: &HealthCheck) -> Self {
+        let probe = match health_check.r#type.as_str() {
+            "tcp" => match health_check.port.unwrap_or(0) {
+                0 => Probe::Invalid("TCP port not specified".to_string()),
+                port => Probe::Tcp {
+                    host: health_check.host.clone().unwrap_or_else(|| "localhost".to_string()),
+                    port,
+                },
+            },
+            "http" => match &health_check.url {
+                Some(url) => Probe::Http { url: url.clone(), method: None, headers: HashMap::new(), expected_status: None },
+                None => Probe::Invalid("HTTP URL not specified".to_string()),
+            },
+            other => Probe::Invalid(format!("Unknown health check type: {}", other)),
+        };
+        Self {
+            probe,
+            interval: Duration::from_secs(health_check.interval),
+            timeout: Duration::from_secs(health_check.timeout),
+            retries: 0,
+            start_period: Duration::ZERO,
+        }
+    }
+
+    // 新格式：interval、timeout 为秒，startPeriod 为毫秒，检查参数在 config 中
+    fn from_new(health_check: &HealthCheckConfig) -> Self {
+        let config = &health_check.config;
+        let text = |key: &str| config.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let probe = match health_check.r#type.as_str() {
+            "tcp" => match config.get("port").and_then(|v| v.as_u64()).and_then(|p| u16::try_from(p).ok()) {
+                Some(port) if port != 0 => Probe::Tcp {
+                    host: text("host").unwrap_or_else(|| "localhost".to_string()),
+                    port,
+                },
+                _ => Probe::Invalid("TCP port not specified".to_string()),
+            },
+            "http" => match text("url") {
+                Some(url) => Probe::Http {
+                    url,
+                    method: text("method"),
+                    headers: config.get("headers")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or_default(),
+                    expected_status: config.get("expectedStatus").and_then(|v| v.as_u64()).and_then(|s| u16::try_from(s).ok()),
+                },
+                None => Probe::Invalid("HTTP URL not specified".to_string()),
+            },
+            "command" => match text("command") {
+                Some(command) => Probe::Command {
+                    command,
+                    args: config.get("args")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or_default(),
+                },
+                None => Probe::Invalid("Health check command not specified".to_string()),
+            },
+            other => Probe::Invalid(format!("Unknown health check type: {}", other)),
+        };
+        Self {
+            probe,
+            interval: Duration::from_secs(health_check.interval),
+            timeout: Duration::from_secs(health_check.timeout),
+            retries: health_check.retries,
+            start_period: Duration::from_millis(health_check.start_period.unwrap_or(0)),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct HealthChecker {
     checks: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+    results: Arc<Mutex<HashMap<String, CheckResult>>>,
     callback: HealthCheckCallback,
     http_client: Client,
 }
@@ -27,44 +133,59 @@ impl HealthChecker {
     pub fn new(callback: HealthCheckCallback) -> Self {
         Self {
             checks: Arc::new(Mutex::new(HashMap::new())),
+            results: Arc::new(Mutex::new(HashMap::new())),
             callback,
             http_client: Client::new(),
         }
     }
     
+    /// 开始健康检查，多实例服务的每个实例分别检查
     pub fn start_checking(&self, task: &Task) {
-        // 从 HealthCheckUnion 中提取旧格式的 HealthCheck
-        let health_check = match &task.health_check {
-            Some(HealthCheckUnion::Old(hc)) => Some(hc.clone()),
-            Some(HealthCheckUnion::New(_)) => {
-                // 新格式暂不支持，跳过
-                log::warn!("New health check format not yet supported for task: {}", task.id);
-                None
+        if task.is_replicated() {
+            for instance in task.instances() {
+                self.start_checking_process(&instance);
             }
-            None => None,
-        };
-        
-        if let Some(health_check) = health_check {
+        } else {
+            self.start_checking_process(task);
+        }
+    }
+    
+    fn start_checking_process(&self, task: &Task) {
+        if let Some(spec) = CheckSpec::of(task) {
             let task_id = task.id.clone();
             let callback = self.callback.clone();
+            let results = self.results.clone();
             let http_client = self.http_client.clone();
             let task_id_for_handle = task.id.clone();
             
             // 启动健康检查任务
             let handle = spawn(async move {
+                sleep(spec.start_period).await;
                 loop {
-                    let result = Self::check(&health_check, &http_client);
+                    // 检查可能阻塞（HTTP 请求、命令），放到阻塞线程中执行
+                    let check_spec = spec.clone();
+                    let client = http_client.clone();
+                    let result = tokio::task::spawn_blocking(move || Self::check(&check_spec, &client)).await
+                        .unwrap_or_else(|e| (false, format!("Health check panicked: {}", e)));
                     let check_result = CheckResult {
                         task_id: task_id.clone(),
                         status: result.0,
                         message: result.1,
                     };
                     
-                    // 调用回调
+                    // 保存最近一次结果并调用回调，实例的结果同时汇总到所属服务
+                    let summary = {
+                        let mut results = results.lock().unwrap();
+                        results.insert(task_id.clone(), check_result.clone());
+                        split_instance_id(&task_id).and_then(|(service_id, _)| instance_summary(&results, service_id))
+                    };
                     callback(check_result);
+                    if let Some(summary) = summary {
+                        callback(summary);
+                    }
                     
                     // 等待指定的时间间隔
-                    sleep(Duration::from_secs(health_check.interval)).await;
+                    sleep(spec.interval).await;
                 }
             });
             
@@ -74,10 +195,25 @@ impl HealthChecker {
         }
     }
     
+    /// 停止健康检查，包括服务的所有实例
     pub fn stop_checking(&self, task_id: &str) {
+        let is_target = |id: &str| id == task_id || split_instance_id(id).map(|(s, _)| s) == Some(task_id);
         let mut checks = self.checks.lock().unwrap();
-        if let Some(handle) = checks.remove(task_id) {
-            handle.abort();
+        checks.retain(|id, handle| {
+            if is_target(id) {
+                handle.abort();
+            }
+            !is_target(id)
+        });
+        self.results.lock().unwrap().retain(|id, _| !is_target(id));
+    }
+    
+    /// 获取最近一次健康检查结果，多实例服务汇总所有实例：全部健康才算健康
+    pub fn latest_result(&self, task_id: &str) -> Option<CheckResult> {
+        let results = self.results.lock().unwrap();
+        match results.get(task_id) {
+            Some(result) => Some(result.clone()),
+            None => instance_summary(&results, task_id),
         }
     }
     
@@ -86,47 +222,155 @@ impl HealthChecker {
         self.start_checking(task);
     }
     
-    fn check(health_check: &HealthCheck, http_client: &Client) -> (bool, String) {
-        match health_check.r#type.as_str() {
-            "tcp" => Self::check_tcp(health_check),
-            "http" => Self::check_http(health_check, http_client),
-            _ => (false, format!("Unknown health check type: {}", health_check.r#type)),
+    // 执行一次检查，失败时按 retries 立即重试
+    fn check(spec: &CheckSpec, http_client: &Client) -> (bool, String) {
+        let mut result = Self::check_once(spec, http_client);
+        for _ in 0..spec.retries {
+            if result.0 {
+                break;
+            }
+            result = Self::check_once(spec, http_client);
         }
+        result
     }
     
-    fn check_tcp(health_check: &HealthCheck) -> (bool, String) {
-        let host = health_check.host.as_ref().map(|s| s.as_str()).unwrap_or("localhost");
-        let port = health_check.port.unwrap_or(0);
-        
-        if port == 0 {
-            return (false, "TCP port not specified".to_string());
+    fn check_once(spec: &CheckSpec, http_client: &Client) -> (bool, String) {
+        match &spec.probe {
+            Probe::Tcp { host, port } => Self::check_tcp(host, *port),
+            Probe::Http { url, method, headers, expected_status } => {
+                Self::check_http(http_client, url, method.as_deref(), headers, *expected_status, spec.timeout)
+            }
+            Probe::Command { command, args } => Self::check_command(command, args, spec.timeout),
+            Probe::Invalid(message) => (false, message.clone()),
         }
-        
+    }
+    
+    fn check_tcp(host: &str, port: u16) -> (bool, String) {
         match TcpStream::connect(format!("{}:{}", host, port)) {
             Ok(_) => (true, "TCP connection successful".to_string()),
             Err(e) => (false, format!("TCP connection failed: {}", e)),
         }
     }
     
-    fn check_http(health_check: &HealthCheck, http_client: &Client) -> (bool, String) {
-        let url = match &health_check.url {
-            Some(url) => url,
-            None => return (false, "HTTP URL not specified".to_string()),
+    fn check_http(
+        http_client: &Client,
+        url: &str,
+        method: Option<&str>,
+        headers: &HashMap<String, String>,
+        expected_status: Option<u16>,
+        timeout: Duration,
+    ) -> (bool, String) {
+        let method = match method.map(|m| Method::from_bytes(m.to_uppercase().as_bytes())) {
+            Some(Ok(method)) => method,
+            Some(Err(_)) => return (false, format!("Invalid HTTP method: {}", method.unwrap_or_default())),
+            None => Method::GET,
         };
+        let mut request = http_client.request(method, url).timeout(timeout);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
         
-        let timeout = Duration::from_secs(health_check.timeout);
-        
-        match http_client.get(url).timeout(timeout).send() {
+        match request.send() {
             Ok(response) => {
-                if response.status().is_success() {
-                    (true, format!("HTTP check successful: {}", response.status()))
+                let status = response.status();
+                let success = match expected_status {
+                    Some(expected) => status.as_u16() == expected,
+                    None => status.is_success(),
+                };
+                if success {
+                    (true, format!("HTTP check successful: {}", status))
                 } else {
-                    (false, format!("HTTP check failed: {}", response.status()))
+                    (false, format!("HTTP check failed: {}", status))
                 }
             }
             Err(e) => (false, format!("HTTP request failed: {}", e)),
         }
     }
+    
+    // 命令退出码为 0 即为健康，超时后终止命令
+    fn check_command(command: &str, args: &[String], timeout: Duration) -> (bool, String) {
+        let mut child = match Command::new(command)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => return (false, format!("Failed to run {}: {}", command, e)),
+        };
+        let started = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) if status.success() => return (true, "Command check successful".to_string()),
+                Ok(Some(status)) => return (false, format!("Command check failed: {}", status)),
+                Ok(None) if started.elapsed() >= timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return (false, format!("Command check timed out after {}s", timeout.as_secs()));
+                }
+                Ok(None) => thread::sleep(COMMAND_POLL_INTERVAL),
+                Err(e) => return (false, format!("Command check failed: {}", e)),
+            }
+        }
+    }
+}
+
+/// 是否配置了可以立即执行的健康检查
+pub fn can_probe(task: &Task) -> bool {
+    CheckSpec::of(task).is_some()
+}
+
+/// 立即执行一次健康检查（阻塞），未配置或不支持的健康检查返回 None
+///
+/// 多实例服务检查所有实例，全部健康才算健康。
+pub fn probe(task: &Task) -> Option<CheckResult> {
+    if task.is_replicated() {
+        let results: Vec<CheckResult> = task.instances().iter().filter_map(probe).collect();
+        if results.is_empty() {
+            return None;
+        }
+        return Some(summarize(&task.id, &results.iter().collect::<Vec<_>>()));
+    }
+
+    let spec = CheckSpec::of(task)?;
+    let (status, message) = HealthChecker::check(&spec, &Client::new());
+    Some(CheckResult {
+        task_id: task.id.clone(),
+        status,
+        message,
+    })
+}
+
+// 汇总服务所有实例最近一次的检查结果
+fn instance_summary(results: &HashMap<String, CheckResult>, service_id: &str) -> Option<CheckResult> {
+    let mut instances: Vec<&CheckResult> = results.iter()
+        .filter(|(id, _)| split_instance_id(id).map(|(s, _)| s) == Some(service_id))
+        .map(|(_, result)| result)
+        .collect();
+    if instances.is_empty() {
+        return None;
+    }
+    instances.sort_by_key(|result| split_instance_id(&result.task_id).map(|(_, index)| index));
+    Some(summarize(service_id, &instances))
+}
+
+// 汇总多个实例的检查结果，消息中列出不健康的实例
+fn summarize(task_id: &str, instances: &[&CheckResult]) -> CheckResult {
+    let unhealthy: Vec<String> = instances.iter()
+        .filter(|result| !result.status)
+        .map(|result| format!("{}: {}", result.task_id, result.message))
+        .collect();
+    let message = if unhealthy.is_empty() {
+        format!("{} instances healthy", instances.len())
+    } else {
+        format!("{}/{} instances unhealthy ({})", unhealthy.len(), instances.len(), unhealthy.join("; "))
+    };
+    CheckResult {
+        task_id: task_id.to_string(),
+        status: unhealthy.is_empty(),
+        message,
+    }
 }
 
 impl Drop for HealthChecker {
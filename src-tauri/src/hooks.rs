@@ -0,0 +1,151 @@
+// 生命周期钩子：服务启动前后、停止前后运行的命令
+
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use crate::config::{HookConfig, Service};
+use crate::process::ProcessManager;
+
+// 未设置超时时钩子最长运行时间（秒）
+const DEFAULT_HOOK_TIMEOUT: u64 = 30;
+
+// 检查钩子是否结束的间隔
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// 错误信息中保留的钩子输出行数
+const HOOK_OUTPUT_LINES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreStart,
+    PostStart,
+    PreStop,
+    PostStop,
+}
+
+impl fmt::Display for HookStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HookStage::PreStart => "preStart",
+            HookStage::PostStart => "postStart",
+            HookStage::PreStop => "preStop",
+            HookStage::PostStop => "postStop",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("Failed to run {0} hook: {1}")]
+    Spawn(HookStage, String),
+
+    #[error("{0} hook failed with exit code {1:?}: {2}")]
+    Failed(HookStage, Option<i32>, String),
+
+    #[error("{0} hook timed out after {1}s: {2}")]
+    Timeout(HookStage, u64, String),
+}
+
+/// 运行服务的钩子（未配置时直接返回），输出同时写入服务日志
+///
+/// 钩子在服务的工作目录中运行，继承服务的环境变量，另外设置 SERVICE_ID 和 SERVICE_HOOK。
+pub fn run(pm: &ProcessManager, service: &Service, stage: HookStage) -> Result<(), HookError> {
+    let Some(hook) = hook_of(service, stage) else {
+        return Ok(());
+    };
+    log::info!("Running {} hook of {}: {}", stage, service.id, hook.command);
+
+    let mut cmd = Command::new(&hook.command);
+    if let Some(args) = &hook.args {
+        cmd.args(args);
+    }
+    if let Some(work_dir) = service.work_dir.as_deref().map(str::trim).filter(|wd| !wd.is_empty()) {
+        cmd.current_dir(work_dir);
+    }
+    if let Some(env) = &service.env {
+        cmd.envs(env);
+    }
+    cmd.env("SERVICE_ID", &service.id)
+        .env("SERVICE_HOOK", stage.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().map_err(|e| HookError::Spawn(stage, e.to_string()))?;
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(read_output(stdout, output.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(read_output(stderr, output.clone()));
+    }
+
+    let timeout = hook.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT);
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if started.elapsed() >= Duration::from_secs(timeout) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            Ok(None) => thread::sleep(HOOK_POLL_INTERVAL),
+            Err(e) => return Err(HookError::Spawn(stage, e.to_string())),
+        }
+    };
+    // 钩子启动的后台进程可能仍持有输出管道，超时后不等待读取线程
+    if status.is_some() {
+        for reader in readers {
+            let _ = reader.join();
+        }
+    }
+
+    let lines = output.lock().unwrap().clone();
+    for line in &lines {
+        pm.log_line(&service.id, &format!("[{}] {}", stage, line));
+    }
+    let excerpt = lines.iter()
+        .skip(lines.len().saturating_sub(HOOK_OUTPUT_LINES))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match status {
+        None => Err(HookError::Timeout(stage, timeout, excerpt)),
+        Some(status) if status.success() => Ok(()),
+        Some(status) => Err(HookError::Failed(stage, status.code(), excerpt)),
+    }
+}
+
+/// 运行钩子，失败时只记录警告（除 preStart 外的钩子失败不影响操作）
+pub fn run_or_warn(pm: &ProcessManager, service: &Service, stage: HookStage) {
+    if let Err(e) = run(pm, service, stage) {
+        log::warn!("Hook of {} failed: {}", service.id, e);
+    }
+}
+
+fn hook_of(service: &Service, stage: HookStage) -> Option<&HookConfig> {
+    let hooks = service.hooks.as_ref()?;
+    match stage {
+        HookStage::PreStart => hooks.pre_start.as_ref(),
+        HookStage::PostStart => hooks.post_start.as_ref(),
+        HookStage::PreStop => hooks.pre_stop.as_ref(),
+        HookStage::PostStop => hooks.post_stop.as_ref(),
+    }
+}
+
+fn read_output<R: Read + Send + 'static>(output: R, lines: Arc<Mutex<Vec<String>>>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            lines.lock().unwrap().push(line);
+        }
+    })
+}
@@ -5,11 +5,20 @@ use log::LevelFilter;
 use simple_logger::SimpleLogger;
 
 mod app;
+mod assets;
+mod auth;
 mod config;
+mod daemon;
 mod database;
 mod healthcheck;
+mod hooks;
 mod orchestrator;
+mod ports;
 mod process;
+mod scheduler;
+mod supervisor;
+mod system;
+mod watcher;
 mod web;
 
 fn main() {
@@ -18,6 +27,32 @@ fn main() {
         .with_level(LevelFilter::Info)
         .init()
         .unwrap();
+    
+    // 无界面模式：不启动 Tauri，直接运行服务编排和 Web 服务器
+    match daemon::DaemonOptions::from_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => {
+            if let Some(scope) = options.create_token {
+                match daemon::create_token(&options, scope) {
+                    Ok(token) => println!("{}", token),
+                    Err(e) => {
+                        log::error!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+            if let Err(e) = daemon::run(options) {
+                log::error!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            log::error!("{}", e);
+            std::process::exit(2);
+        }
+    }
 
     tauri::Builder::default()
         .invoke_handler(tauri::generate_handler![
@@ -35,7 +70,30 @@ fn main() {
             app::restart_health_check,
             app::stop_all_tasks,
             app::get_server_health,
-            app::get_pid_port
+            app::get_run_history,
+            app::get_event_history,
+            app::get_service_ports,
+            app::get_pid_port,
+            app::get_auth_settings,
+            app::create_api_token,
+            app::revoke_api_token,
+            app::set_basic_auth,
+            app::get_web_server_status,
+            app::get_auto_start_progress,
+            app::cancel_auto_start,
+            app::get_job,
+            app::list_jobs,
+            app::cancel_job,
+            app::resume_job,
+            app::start_application,
+            app::stop_application,
+            app::restart_application,
+            app::get_application_status,
+            app::get_restart_plan,
+            app::plan_start,
+            app::rolling_restart_group,
+            app::get_service_instances,
+            app::scale_service
         ])
         .setup(|app| {
             // 初始化应用
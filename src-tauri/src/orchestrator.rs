@@ -1,334 +1,2001 @@
-use std::sync::{Arc, Mutex};
-use std::collections::{HashSet, HashMap};
-use crate::config::{Config, Service, DependencyType, StartupStrategy};
-use crate::process::ProcessManager;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::collections::{BTreeMap, HashSet, HashMap};
+use crate::config::{instance_id, split_instance_id, Application, Config, ConflictResolution, Service, ServiceGroup, DependencyType, StartupStrategy};
+use crate::healthcheck::{self, CheckResult, HealthChecker};
+use crate::hooks::{self, HookStage};
+use crate::ports::{PortAllocator, PortError};
+use crate::scheduler::{RunStatus, Scheduler};
+use crate::supervisor::Supervisor;
+use crate::watcher::FileWatcher;
+use crate::process::{ProcessError, ProcessManager};
+use chrono::Utc;
+use futures::future;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use std::time::Duration;
-use std::thread;
+use tokio::runtime::Handle;
+use tokio::sync::{watch, Notify, Semaphore};
+use tokio_util::sync::CancellationToken;
+use std::time::{Duration, Instant};
 
 #[derive(Error, Debug)]
 #[allow(dead_code)]
 pub enum OrchestratorError {
     #[error("Process error: {0}")]
     ProcessError(#[from] crate::process::ProcessError),
-    
+
     #[error("Task not found: {0}")]
     TaskNotFound(String),
-    
+
     #[error("Group not found: {0}")]
     GroupNotFound(String),
-    
-    #[error("Circular dependency detected")]
-    CircularDependency,
-    
+
+    #[error("Application not found: {0}")]
+    ApplicationNotFound(String),
+
+    #[error("Circular dependency: {0}")]
+    CircularDependency(String),
+
+    #[error("Service {0} depends on unknown service {1}")]
+    MissingDependency(String, String),
+
+    #[error("Circular group dependency: {0}")]
+    CircularGroupDependency(String),
+
     #[error("Conflicting service: {0} conflicts with {1}")]
     ConflictingService(String, String),
+
+    #[error("Dependency of {0} failed: {1}")]
+    DependencyFailed(String, String),
+
+    #[error("Service {0} is running as a single instance, restart it to use replicas")]
+    NotReplicated(String),
+
+    #[error("Group {0} was rolled back")]
+    RolledBack(String),
+
+    #[error("Not a start operation: {0}")]
+    NotStartOperation(String),
+
+    #[error("Service {0} did not become healthy: {1}")]
+    Unhealthy(String, String),
+
+    #[error("Run of {0} failed: {1}")]
+    RunFailed(String, String),
+
+    #[error("Port error: {0}")]
+    Port(#[from] PortError),
+
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+// 保留的已结束任务数量
+const MAX_FINISHED_JOBS: usize = 100;
+
+// 等待服务健康的默认超时（毫秒），可通过 timeoutConfig.startTimeout 修改
+const DEFAULT_HEALTH_GATE_TIMEOUT: u64 = 30000;
+const HEALTH_GATE_INTERVAL: u64 = 1000;
+
+/// 编排操作，每次提交都会创建一个任务（Job）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "target", rename_all = "camelCase")]
+pub enum Operation {
+    AutoStart,  // 程序启动时的自动启动，与 StartAll 相同
+    StartAll,
+    StopAll,
+    StartService(String),
+    StopService(String),
+    StopServiceAndDependents(String),  // 同时停止依赖该服务的服务
+    RestartService(String),
+    RestartServiceCascade(String),  // 重启服务以及依赖它的服务
+    StartGroup(String),
+    StopGroup(String),
+    StartApplication(String),
+    StopApplication(String),
+    RestartApplication(String),
+    // 滚动重启：每次重启组内 batch_size 个正在运行的服务，健康后再继续
+    #[serde(rename_all = "camelCase")]
+    RollingRestartGroup { group_id: String, batch_size: usize },
+    // 调整多实例服务的实例数，不影响保留的实例
+    #[serde(rename_all = "camelCase")]
+    ScaleService { service_id: String, replicas: u32 },
+}
+
+impl Operation {
+    /// 按目标生成启动操作，未指定目标时为全部启动
+    pub fn start_of(service_id: Option<String>, group_id: Option<String>, application_id: Option<String>) -> Self {
+        match (service_id, group_id, application_id) {
+            (Some(id), _, _) => Operation::StartService(id),
+            (None, Some(id), _) => Operation::StartGroup(id),
+            (None, None, Some(id)) => Operation::StartApplication(id),
+            (None, None, None) => Operation::StartAll,
+        }
+    }
+
+    // 提交前检查目标是否存在，启动类操作还会检查依赖关系，保证出错时还没有启动任何服务
+    fn validate(&self, config: &Config) -> Result<(), OrchestratorError> {
+        match self {
+            Operation::AutoStart | Operation::StartAll => {
+                config.group_order()
+                    .map_err(|cycle| OrchestratorError::CircularGroupDependency(cycle.join(" → ")))?;
+                let roots: Vec<String> = config.groups.iter()
+                    .flat_map(|g| g.services.iter())
+                    .filter(|s| s.auto_start)
+                    .map(|s| s.id.clone())
+                    .collect();
+                check_dependencies(config, &roots)
+            }
+            Operation::StartService(id) | Operation::RestartServiceCascade(id) => {
+                find_service(config, id)?;
+                check_dependencies(config, std::slice::from_ref(id))
+            }
+            Operation::StartGroup(id) => {
+                let group = find_group(config, id)?;
+                let roots: Vec<String> = group.services.iter().map(|s| s.id.clone()).collect();
+                check_dependencies(config, &roots)
+            }
+            Operation::StartApplication(id) | Operation::RestartApplication(id) => {
+                let application = find_application(config, id)?;
+                check_dependencies(config, &application_services(config, application))
+            }
+            Operation::StopService(id) | Operation::RestartService(id) | Operation::StopServiceAndDependents(id) => {
+                find_service(config, id).map(|_| ())
+            }
+            Operation::StopGroup(id) => find_group(config, id).map(|_| ()),
+            Operation::RollingRestartGroup { group_id, .. } => find_group(config, group_id).map(|_| ()),
+            Operation::ScaleService { service_id, .. } => find_service(config, service_id).map(|_| ()),
+            Operation::StopApplication(id) => find_application(config, id).map(|_| ()),
+            Operation::StopAll => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Paused,  // 滚动重启中有服务未通过健康检查，等待继续或取消
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    /// 任务是否还未结束
+    pub fn is_active(&self) -> bool {
+        matches!(self, JobState::Running | JobState::Paused)
+    }
+}
+
+/// 启动操作中单个服务的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum ServiceOutcome {
+    Started,
+    // 一次性服务运行成功结束
+    Completed,
+    AlreadyRunning,
+    Failed { reason: String },
+    // 未启动：前置服务失败、所在的原子组已回滚，或全部启动时未设置自动启动
+    Skipped { reason: String },
+    // 原子组启动失败后已停止
+    RolledBack,
+    RollbackFailed { reason: String },
+}
+
+/// 编排任务的状态和进度
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: String,
+    pub operation: Operation,
+    pub state: JobState,
+    pub total: usize,
+    pub completed: usize,
+    // 正在启动/停止的服务
+    pub active: Vec<String>,
+    pub failed: Vec<String>,
+    // 失败服务的错误信息
+    pub errors: BTreeMap<String, String>,
+    // 启动操作中各服务的结果
+    pub outcomes: BTreeMap<String, ServiceOutcome>,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// 应用的汇总状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApplicationState {
+    Up,        // 所有服务都在运行且健康
+    Degraded,  // 部分服务未运行或健康检查失败
+    Down,      // 没有服务在运行
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationStatus {
+    pub id: String,
+    pub state: ApplicationState,
+    pub total: usize,
+    pub running: Vec<String>,
+    pub unhealthy: Vec<String>,
+}
+
+/// 多实例服务中单个实例的状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceStatus {
+    pub id: String,
+    pub index: u32,
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub port: Option<u16>,
+    pub health: Option<CheckResult>,
+}
+
+/// 级联重启的影响范围
+///
+/// `layers[0]` 是被重启的服务，之后每一层都（以必需依赖方式）依赖前面的层；
+/// 停止时从最后一层开始，启动时从第一层开始，每层健康后再启动下一层。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartPlan {
+    pub service_id: String,
+    pub layers: Vec<Vec<String>>,
+    // 依赖该服务但未在运行的服务，不会被启动
+    pub skipped: Vec<String>,
+}
+
+/// 启动计划中服务的一个依赖
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedDependency {
+    pub service_id: String,
+    pub r#type: DependencyType,
+    // 依赖在本次启动后需要等待的毫秒数
+    pub timeout: Option<u64>,
+    // 启动前等待该依赖通过健康检查（一次性服务为等待运行成功结束）
+    pub health_gate: bool,
+}
+
+/// 启动前需要检查的冲突服务
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedConflict {
+    pub service_id: String,
+    // 冲突服务当前是否在运行
+    pub running: bool,
+    // 运行中的冲突服务会在启动前停止（replace），否则启动失败
+    pub resolution: ConflictResolution,
+}
+
+/// 启动计划中的一个服务
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedStart {
+    pub service_id: String,
+    pub group_id: String,
+    pub dependencies: Vec<PlannedDependency>,
+    // 必须先成功启动的其他服务：顺序启动中的前一个服务、所依赖的组中的服务
+    pub after: Vec<String>,
+    pub conflicts: Vec<PlannedConflict>,
+    // 启动后等待的毫秒数
+    pub delay: u64,
+    // 所属的原子组：启动后需要通过健康检查，组内有服务失败时回滚
+    pub atomic_group: Option<String>,
+}
+
+/// 同时启动的一批服务
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanBatch {
+    pub services: Vec<PlannedStart>,
+    // 本批完成后等待的毫秒数（服务组的启动延迟）
+    pub delay: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipReason {
+    AlreadyRunning,
+    NotAutoStart,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedService {
+    pub service_id: String,
+    pub reason: SkipReason,
 }
 
+/// 启动计划：启动操作会按批次依次执行，同一批内的服务并发启动
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartPlan {
+    pub operation: Operation,
+    pub batches: Vec<PlanBatch>,
+    pub skipped: Vec<SkippedService>,
+}
+
+// 执行启动计划时各服务的结果
+#[derive(Default)]
+struct PlanProgress {
+    // 服务是否启动成功
+    outcomes: HashMap<String, bool>,
+    started_at: HashMap<String, Instant>,
+    // 按启动顺序排列的已启动服务
+    started: Vec<String>,
+    // 已经回滚的原子组
+    rolled_back: HashSet<String>,
+    // 限制了并发数的组
+    limits: HashMap<String, Arc<Semaphore>>,
+}
+
+pub type JobCallback = Arc<dyn Fn(&Job) + Send + Sync + 'static>;
+
+struct JobEntry {
+    state: watch::Sender<Job>,
+    cancel: CancellationToken,
+    resume: Arc<Notify>,
+}
+
+// 正在执行的任务：配置快照、取消令牌和进度
+struct JobContext {
+    config: Config,
+    cancel: CancellationToken,
+    state: watch::Sender<Job>,
+    callback: JobCallback,
+    resume: Arc<Notify>,
+}
+
+impl JobContext {
+    fn update<F: FnOnce(&mut Job)>(&self, f: F) {
+        self.state.send_modify(f);
+        let job = self.state.borrow().clone();
+        (self.callback)(&job);
+    }
+
+    fn check_cancelled(&self) -> Result<(), OrchestratorError> {
+        if self.cancel.is_cancelled() {
+            Err(OrchestratorError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    // 等待指定毫秒数，期间取消则立即返回
+    async fn sleep(&self, millis: u64) -> Result<(), OrchestratorError> {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_millis(millis)) => Ok(()),
+            _ = self.cancel.cancelled() => Err(OrchestratorError::Cancelled),
+        }
+    }
+
+    // 暂停任务，直到继续或取消
+    async fn pause(&self, reason: String) -> Result<(), OrchestratorError> {
+        self.update(|j| {
+            j.state = JobState::Paused;
+            j.error = Some(reason);
+        });
+        tokio::select! {
+            _ = self.resume.notified() => {}
+            _ = self.cancel.cancelled() => return Err(OrchestratorError::Cancelled),
+        }
+        self.update(|j| {
+            j.state = JobState::Running;
+            j.error = None;
+        });
+        Ok(())
+    }
+
+    // 记录没有经过 step 的失败（例如依赖失败而跳过）
+    fn fail(&self, task_id: &str, error: &OrchestratorError) {
+        self.update(|j| {
+            j.completed += 1;
+            j.failed.push(task_id.to_string());
+            j.errors.insert(task_id.to_string(), error.to_string());
+        });
+    }
+}
+
+/// 服务编排：所有操作都在 tokio 上以任务的形式异步执行，可查询进度和取消
 #[derive(Clone)]
 pub struct Orchestrator {
     pm: Arc<ProcessManager>,
-    config: Arc<Mutex<Option<Config>>>,
+    runtime: Handle,
+    jobs: Arc<Mutex<BTreeMap<u64, JobEntry>>>,
+    job_callback: JobCallback,
+    next_job_id: Arc<AtomicU64>,
+    ports: Arc<PortAllocator>,
+    scheduler: Arc<Scheduler>,
+    watcher: Arc<FileWatcher>,
+    supervisor: Arc<Supervisor>,
+    // 启动服务后开始健康检查，停止后结束检查
+    health_checker: Arc<OnceLock<Arc<HealthChecker>>>,
 }
 
 impl Orchestrator {
-    pub fn new(pm: Arc<ProcessManager>) -> Self {
+    pub fn new(pm: Arc<ProcessManager>, runtime: Handle, job_callback: JobCallback) -> Self {
+        let ports = Arc::new(PortAllocator::new());
+        let scheduler = Arc::new(Scheduler::new(pm.clone(), ports.clone(), runtime.clone()));
+        let watcher = Arc::new(FileWatcher::new(pm.clone(), runtime.clone()));
+        let supervisor = Arc::new(Supervisor::new(pm.clone(), runtime.clone()));
         Self {
             pm,
-            config: Arc::new(Mutex::new(None)),
-        }
-    }
-    
-    pub fn set_config(&self, config: Config) {
-        let mut config_guard = self.config.lock().unwrap();
-        *config_guard = Some(config);
-    }
-    
-    pub fn start_all(&self, config: &Config) -> Result<(), OrchestratorError> {
-        // 保存配置
-        self.set_config(config.clone());
-        
-        // 检查循环依赖
-        self.check_dependencies(config)?;
-        
-        // 按依赖顺序启动所有服务
-        for group in &config.groups {
+            runtime,
+            jobs: Arc::new(Mutex::new(BTreeMap::new())),
+            job_callback,
+            next_job_id: Arc::new(AtomicU64::new(1)),
+            ports,
+            scheduler,
+            watcher,
+            supervisor,
+            health_checker: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// 设置健康检查器，之后由编排操作在启动和停止服务时开始或结束检查
+    ///
+    /// 健康检查的回调需要引用编排器，因此在两者都创建后调用一次。
+    pub fn set_health_checker(&self, health_checker: Arc<HealthChecker>) {
+        let _ = self.health_checker.set(health_checker);
+    }
+
+    /// 为已经在运行的服务（例如重新接管的分离模式服务）开启健康检查
+    pub fn check_running(&self, config: &Config) {
+        for service in config.groups.iter().flat_map(|g| g.services.iter()) {
+            if self.pm.is_running(&service.id) {
+                self.start_health_check(&self.resolve_service(config, service));
+            }
+        }
+    }
+
+    /// 应用配置：重新安排定时运行、文件监视和健康检查失败的处理，启动时和配置修改后调用
+    pub fn reload(&self, config: &Config) {
+        self.scheduler.sync(config);
+        self.watcher.sync(self, config);
+        self.supervisor.sync(config);
+    }
+
+    /// 停止定时运行、文件监视和健康检查失败的处理，退出前调用
+    pub fn stop_triggers(&self) {
+        self.scheduler.clear();
+        self.watcher.clear();
+        self.supervisor.clear();
+    }
+
+    /// 处理健康检查结果，连续失败达到阈值时按 onUnhealthy 重启或停止服务
+    pub fn report_health(&self, result: &CheckResult) {
+        self.supervisor.report(self, result);
+    }
+
+    /// 定时运行
+    pub fn scheduler(&self) -> &Scheduler {
+        &self.scheduler
+    }
+
+    /// 服务的命名端口分配
+    pub fn ports(&self) -> &PortAllocator {
+        &self.ports
+    }
+
+    /// 展开服务配置中的端口引用，供健康检查等使用启动时的实际端口
+    pub fn resolve_service(&self, config: &Config, service: &Service) -> Service {
+        self.ports.interpolate(config, service).unwrap_or_else(|e| {
+            log::warn!("Failed to resolve ports of {}: {}", service.id, e);
+            service.clone()
+        })
+    }
+
+    /// 提交操作并立即返回任务 ID，操作使用提交时的配置快照执行
+    pub fn submit(&self, config: Config, operation: Operation) -> Result<String, OrchestratorError> {
+        operation.validate(&config)?;
+        if let Operation::ScaleService { service_id, .. } = &operation {
+            self.check_scalable(service_id)?;
+        }
+
+        let number = self.next_job_id.fetch_add(1, Ordering::Relaxed);
+        let job = Job {
+            id: number.to_string(),
+            operation: operation.clone(),
+            state: JobState::Running,
+            total: 0,
+            completed: 0,
+            active: Vec::new(),
+            failed: Vec::new(),
+            errors: BTreeMap::new(),
+            outcomes: BTreeMap::new(),
+            error: None,
+            started_at: Utc::now().to_rfc3339(),
+            finished_at: None,
+        };
+        let job_id = job.id.clone();
+        (self.job_callback)(&job);
+
+        let (state, _) = watch::channel(job);
+        let cancel = CancellationToken::new();
+        let resume = Arc::new(Notify::new());
+        let context = JobContext {
+            config,
+            cancel: cancel.clone(),
+            state: state.clone(),
+            callback: self.job_callback.clone(),
+            resume: resume.clone(),
+        };
+        self.jobs.lock().unwrap().insert(number, JobEntry { state, cancel, resume });
+
+        let orchestrator = self.clone();
+        self.runtime.spawn(async move {
+            orchestrator.execute(context, operation).await;
+            orchestrator.prune_jobs();
+        });
+
+        Ok(job_id)
+    }
+
+    /// 提交操作并等待完成
+    pub async fn run(&self, config: Config, operation: Operation) -> Result<Job, OrchestratorError> {
+        let job_id = self.submit(config, operation)?;
+        self.wait(&job_id).await
+            .ok_or(OrchestratorError::Internal(format!("Job {} disappeared", job_id)))
+    }
+
+    /// 等待任务结束，任务不存在时返回 None
+    pub async fn wait(&self, job_id: &str) -> Option<Job> {
+        let mut receiver = {
+            let jobs = self.jobs.lock().unwrap();
+            jobs.get(&job_id.parse().ok()?)?.state.subscribe()
+        };
+        let job = receiver.wait_for(|job| !job.state.is_active()).await.ok()?;
+        let job = job.clone();
+        Some(job)
+    }
+
+    pub fn job(&self, job_id: &str) -> Option<Job> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(&job_id.parse().ok()?).map(|entry| entry.state.borrow().clone())
+    }
+
+    /// 所有保留的任务，最新的在前
+    pub fn jobs(&self) -> Vec<Job> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.values().rev().map(|entry| entry.state.borrow().clone()).collect()
+    }
+
+    /// 最近一次指定操作的任务
+    pub fn latest_job(&self, operation: &Operation) -> Option<Job> {
+        self.jobs().into_iter().find(|job| &job.operation == operation)
+    }
+
+    /// 取消任务，正在执行的单个启动/停止会先完成，返回任务是否仍在运行
+    pub fn cancel(&self, job_id: &str) -> bool {
+        let jobs = self.jobs.lock().unwrap();
+        let Some(entry) = job_id.parse().ok().and_then(|number: u64| jobs.get(&number)) else {
+            return false;
+        };
+        let running = entry.state.borrow().state.is_active();
+        if running {
+            entry.cancel.cancel();
+        }
+        running
+    }
+
+    /// 继续暂停的任务，返回任务是否处于暂停状态
+    pub fn resume(&self, job_id: &str) -> bool {
+        let jobs = self.jobs.lock().unwrap();
+        let Some(entry) = job_id.parse().ok().and_then(|number: u64| jobs.get(&number)) else {
+            return false;
+        };
+        let paused = entry.state.borrow().state == JobState::Paused;
+        if paused {
+            entry.resume.notify_one();
+        }
+        paused
+    }
+
+    /// 取消所有正在运行的任务
+    pub fn cancel_all(&self) {
+        for entry in self.jobs.lock().unwrap().values() {
+            entry.cancel.cancel();
+        }
+    }
+
+    /// 应用的汇总状态，根据应用内各服务的运行状态和最近一次健康检查结果计算
+    pub fn application_status(&self, config: &Config, application_id: &str, health_checker: &HealthChecker) -> Result<ApplicationStatus, OrchestratorError> {
+        let application = find_application(config, application_id)?;
+        let services = application_services(config, application);
+
+        let running: Vec<String> = services.iter()
+            .filter(|id| self.pm.is_running(id))
+            .cloned()
+            .collect();
+        let unhealthy: Vec<String> = running.iter()
+            .filter(|id| health_checker.latest_result(id).map(|r| !r.status).unwrap_or(false))
+            .cloned()
+            .collect();
+
+        let state = if running.is_empty() {
+            ApplicationState::Down
+        } else if running.len() == services.len() && unhealthy.is_empty() {
+            ApplicationState::Up
+        } else {
+            ApplicationState::Degraded
+        };
+
+        Ok(ApplicationStatus {
+            id: application.id.clone(),
+            state,
+            total: services.len(),
+            running,
+            unhealthy,
+        })
+    }
+
+    /// 启动操作的执行计划（不会启动任何服务）
+    ///
+    /// 支持启动服务、服务组、应用和全部启动，返回的批次与实际执行时一致。
+    pub fn plan_start(&self, config: &Config, operation: &Operation) -> Result<StartPlan, OrchestratorError> {
+        operation.validate(config)?;
+
+        let (roots, groups): (Vec<String>, Vec<&ServiceGroup>) = match operation {
+            Operation::AutoStart | Operation::StartAll => {
+                let roots = config.groups.iter()
+                    .flat_map(|g| g.services.iter())
+                    .filter(|s| s.auto_start)
+                    .map(|s| s.id.clone())
+                    .collect();
+                (roots, config.groups.iter().collect())
+            }
+            Operation::StartService(id) => (vec![id.clone()], Vec::new()),
+            Operation::StartGroup(id) => {
+                let group = find_group(config, id)?;
+                (group.services.iter().map(|s| s.id.clone()).collect(), vec![group])
+            }
+            Operation::StartApplication(id) | Operation::RestartApplication(id) => {
+                let application = find_application(config, id)?;
+                let groups = config.groups.iter().filter(|g| application.group_ids.contains(&g.id)).collect();
+                (application_services(config, application), groups)
+            }
+            _ => return Err(OrchestratorError::NotStartOperation(format!("{:?}", operation))),
+        };
+
+        // 需要启动的服务（依赖在前），已经在运行的服务跳过
+        let closure = start_closure(config, &roots);
+        let mut skipped = Vec::new();
+        let mut planned: Vec<String> = Vec::new();
+        for id in dependency_order(config) {
+            if closure.contains(&id) {
+                if self.pm.is_running(&id) {
+                    skipped.push(SkippedService { service_id: id, reason: SkipReason::AlreadyRunning });
+                } else {
+                    planned.push(id);
+                }
+            } else if matches!(operation, Operation::AutoStart | Operation::StartAll) {
+                skipped.push(SkippedService { service_id: id, reason: SkipReason::NotAutoStart });
+            }
+        }
+        let planned_set: HashSet<&str> = planned.iter().map(|id| id.as_str()).collect();
+
+        // 顺序启动的组：组内的服务按依赖顺序依次启动，启动后等待服务的启动延迟
+        let mut after: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut delays: HashMap<&str, u64> = HashMap::new();
+        for group in &groups {
             let strategy = group.startup_strategy.as_ref().unwrap_or(&StartupStrategy::Sequential);
-            
-            match strategy {
-                StartupStrategy::Sequential => {
-                    // 顺序启动
-                    for service in &group.services {
-                        if service.auto_start {
-                            self.start_task(&service.id)?;
-                            // 等待启动延迟
-                            if service.startup_delay > 0 {
-                                thread::sleep(Duration::from_millis(service.startup_delay));
-                            }
-                        }
-                    }
+            if !matches!(strategy, StartupStrategy::Sequential) {
+                continue;
+            }
+            let chain: Vec<&String> = planned.iter()
+                .filter(|id| roots.contains(id) && group.services.iter().any(|s| &s.id == *id))
+                .collect();
+            for pair in chain.windows(2) {
+                after.entry(pair[1].as_str()).or_default().push(pair[0].clone());
+            }
+            for id in chain {
+                delays.insert(id.as_str(), find_service(config, id)?.startup_delay);
+            }
+        }
+
+        // 依赖的组中的服务全部启动后才启动本组
+        let group_of = |id: &str| config.groups.iter().find(|g| g.services.iter().any(|s| s.id == id));
+        for group in &groups {
+            for dep_group in group.dependencies.iter().filter(|d| groups.iter().any(|g| &g.id == *d)) {
+                let blockers: Vec<String> = planned.iter()
+                    .filter(|id| group_of(id).map(|g| &g.id) == Some(dep_group))
+                    .cloned()
+                    .collect();
+                for id in planned.iter().filter(|id| group_of(id).map(|g| &g.id) == Some(&group.id)) {
+                    after.entry(id.as_str()).or_default().extend(blockers.iter().cloned());
                 }
-                StartupStrategy::Parallel => {
-                    // 并行启动（不等待）
-                    for service in &group.services {
-                        if service.auto_start {
-                            let _ = self.start_task(&service.id);
-                        }
-                    }
+            }
+        }
+
+        // 按最长路径分批：每个服务排在它所有前置服务之后的第一批
+        let predecessors = |id: &str| -> Result<Vec<String>, OrchestratorError> {
+            let service = find_service(config, id)?;
+            let mut preds: Vec<String> = service.start_dependencies().into_iter()
+                .filter(|d| planned_set.contains(d))
+                .map(String::from)
+                .collect();
+            preds.extend(after.get(id).into_iter().flatten().cloned());
+            Ok(preds)
+        };
+        let mut levels: HashMap<String, usize> = HashMap::new();
+        let mut remaining = planned.clone();
+        while !remaining.is_empty() {
+            let mut next = Vec::new();
+            for id in remaining.iter() {
+                let preds = predecessors(id)?;
+                if preds.iter().all(|p| levels.contains_key(p)) {
+                    let level = preds.iter().map(|p| levels[p] + 1).max().unwrap_or(0);
+                    levels.insert(id.clone(), level);
+                } else {
+                    next.push(id.clone());
                 }
-                StartupStrategy::Mixed => {
-                    // 混合模式：根据依赖关系决定
-                    self.start_group_mixed(&group.id)?;
+            }
+            if next.len() == remaining.len() {
+                return Err(OrchestratorError::CircularDependency(next.join(", ")));
+            }
+            remaining = next;
+        }
+
+        let batch_count = levels.values().max().map_or(0, |max| max + 1);
+        let mut batches: Vec<PlanBatch> = (0..batch_count)
+            .map(|_| PlanBatch { services: Vec::new(), delay: 0 })
+            .collect();
+        for id in &planned {
+            let service = find_service(config, id)?;
+            let mut after_ids: Vec<String> = Vec::new();
+            for blocker in after.get(id.as_str()).into_iter().flatten() {
+                if !after_ids.contains(blocker) {
+                    after_ids.push(blocker.clone());
                 }
             }
-            
-            // 组级别的启动延迟
-            if group.startup_delay > 0 {
-                thread::sleep(Duration::from_millis(group.startup_delay));
+            // 启动单个原子组时，组外的依赖服务也随组一起回滚
+            let atomic_group = match operation {
+                Operation::StartGroup(group_id) if groups.iter().any(|g| &g.id == group_id && g.atomic) => Some(group_id.clone()),
+                _ => group_of(id)
+                    .filter(|g| g.atomic && groups.iter().any(|scoped| scoped.id == g.id))
+                    .map(|g| g.id.clone()),
+            };
+            batches[levels[id]].services.push(PlannedStart {
+                service_id: id.clone(),
+                group_id: group_of(id).map(|g| g.id.clone()).unwrap_or_default(),
+                dependencies: planned_dependencies(config, service, &planned_set),
+                after: after_ids,
+                conflicts: self.planned_conflicts(config, id, &planned_set)?,
+                delay: delays.get(id.as_str()).copied().unwrap_or(0),
+                atomic_group,
+            });
+        }
+
+        // 组的启动延迟放在该组最后一批之后
+        for group in &groups {
+            let last = planned.iter()
+                .filter(|id| group.services.iter().any(|s| &s.id == *id))
+                .map(|id| levels[id])
+                .max();
+            if let Some(last) = last {
+                batches[last].delay = batches[last].delay.max(group.startup_delay);
             }
         }
-        
+
+        Ok(StartPlan {
+            operation: operation.clone(),
+            batches,
+            skipped,
+        })
+    }
+
+    // 服务的冲突（双向）：冲突服务也在计划中，或正在运行且不能替换时报错
+    fn planned_conflicts(&self, config: &Config, task_id: &str, planned: &HashSet<&str>) -> Result<Vec<PlannedConflict>, OrchestratorError> {
+        let mut conflicts = Vec::new();
+        for (other, resolution) in config.conflicts_of(task_id) {
+            let running = self.pm.is_running(other);
+            if planned.contains(other) || (running && resolution == ConflictResolution::Error) {
+                return Err(OrchestratorError::ConflictingService(task_id.to_string(), other.to_string()));
+            }
+            conflicts.push(PlannedConflict {
+                service_id: other.to_string(),
+                running,
+                resolution,
+            });
+        }
+        Ok(conflicts)
+    }
+
+    /// 多实例服务各实例的状态，包括缩容后仍在运行的实例；未设置 replicas 的服务返回空列表
+    pub fn instance_status(&self, config: &Config, service_id: &str, health_checker: &HealthChecker) -> Result<Vec<InstanceStatus>, OrchestratorError> {
+        let service = find_service(config, service_id)?;
+        if !service.is_replicated() {
+            return Ok(Vec::new());
+        }
+
+        let mut indexes: Vec<u32> = (0..service.replica_count()).collect();
+        for id in self.pm.instance_ids(service_id) {
+            if let Some((_, index)) = split_instance_id(&id) {
+                if !indexes.contains(&index) {
+                    indexes.push(index);
+                }
+            }
+        }
+        indexes.sort_unstable();
+
+        Ok(indexes.into_iter()
+            .map(|index| {
+                let id = instance_id(service_id, index);
+                let running = self.pm.is_running(&id);
+                InstanceStatus {
+                    index,
+                    running,
+                    pid: if running { self.pm.get_pid(&id) } else { None },
+                    port: service.base_port.map(|port| port.saturating_add(index as u16)),
+                    health: health_checker.latest_result(&id),
+                    id,
+                }
+            })
+            .collect())
+    }
+
+    /// 级联重启的预览：会被重启的服务及其分层
+    pub fn restart_plan(&self, config: &Config, task_id: &str) -> Result<RestartPlan, OrchestratorError> {
+        find_service(config, task_id)?;
+
+        let dependents = required_dependents(config, &[task_id.to_string()]);
+        let mut depth: HashMap<String, usize> = HashMap::from([(task_id.to_string(), 0)]);
+        // 按依赖顺序计算每个服务到被重启服务的最长路径，作为所在的层
+        for id in dependency_order(config) {
+            if !dependents.contains(&id) {
+                continue;
+            }
+            let service = find_service(config, &id)?;
+            let level = required_dependencies_of(service).iter()
+                .filter_map(|dep| depth.get(dep))
+                .max()
+                .map_or(1, |level| level + 1);
+            depth.insert(id, level);
+        }
+
+        let mut layers: Vec<Vec<String>> = Vec::new();
+        let mut skipped = Vec::new();
+        for id in dependency_order(config) {
+            let Some(&level) = depth.get(&id) else { continue };
+            if id != task_id && !self.pm.is_running(&id) {
+                skipped.push(id);
+                continue;
+            }
+            if layers.len() <= level {
+                layers.resize(level + 1, Vec::new());
+            }
+            layers[level].push(id);
+        }
+        layers.retain(|layer| !layer.is_empty());
+
+        Ok(RestartPlan {
+            service_id: task_id.to_string(),
+            layers,
+            skipped,
+        })
+    }
+
+    // 只保留最近的已结束任务
+    fn prune_jobs(&self) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let finished: Vec<u64> = jobs.iter()
+            .filter(|(_, entry)| !entry.state.borrow().state.is_active())
+            .map(|(number, _)| *number)
+            .collect();
+        for number in finished.iter().take(finished.len().saturating_sub(MAX_FINISHED_JOBS)) {
+            jobs.remove(number);
+        }
+    }
+
+    async fn execute(&self, job: JobContext, operation: Operation) {
+        let result = match &operation {
+            Operation::AutoStart | Operation::StartAll | Operation::StartService(_)
+            | Operation::StartGroup(_) | Operation::StartApplication(_) => self.start(&job, &operation).await,
+            Operation::StopAll => self.stop_all(&job).await,
+            Operation::StopService(id) => {
+                job.update(|j| j.total = 1);
+                self.stop_service(&job, id).await
+            }
+            Operation::StopServiceAndDependents(id) => self.stop_service_and_dependents(&job, id).await,
+            Operation::RestartServiceCascade(id) => self.restart_cascade(&job, id).await,
+            Operation::RestartService(id) => {
+                job.update(|j| j.total = 1);
+                self.restart_service(&job, id).await
+            }
+            Operation::StopGroup(id) => self.stop_group(&job, id).await,
+            Operation::RollingRestartGroup { group_id, batch_size } => self.rolling_restart(&job, group_id, *batch_size).await,
+            Operation::ScaleService { service_id, replicas } => self.scale(&job, service_id, *replicas).await,
+            Operation::StopApplication(id) => self.stop_application(&job, id).await,
+            Operation::RestartApplication(id) => match self.stop_application(&job, id).await {
+                Ok(()) => self.start(&job, &operation).await,
+                Err(e) => Err(e),
+            },
+        };
+
+        if let Err(e) = &result {
+            if !matches!(e, OrchestratorError::Cancelled) {
+                log::error!("{:?} failed: {}", operation, e);
+            }
+        }
+        job.update(|j| {
+            j.active.clear();
+            j.finished_at = Some(Utc::now().to_rfc3339());
+            match result {
+                Ok(()) if j.failed.is_empty() => j.state = JobState::Succeeded,
+                Ok(()) => {
+                    j.state = JobState::Failed;
+                    let failures: Vec<String> = j.failed.iter()
+                        .map(|id| match j.errors.get(id) {
+                            Some(e) => format!("{} ({})", id, e),
+                            None => id.clone(),
+                        })
+                        .collect();
+                    j.error = Some(format!("Failed: {}", failures.join(", ")));
+                }
+                Err(OrchestratorError::Cancelled) => j.state = JobState::Cancelled,
+                Err(e) => {
+                    j.state = JobState::Failed;
+                    j.error = Some(e.to_string());
+                }
+            }
+        });
+    }
+
+    // 在阻塞线程池中执行一次进程操作，并记录进度
+    async fn step<F>(&self, job: &JobContext, task_id: &str, action: F) -> Result<(), OrchestratorError>
+    where
+        F: FnOnce(&ProcessManager) -> Result<(), ProcessError> + Send + 'static,
+    {
+        job.check_cancelled()?;
+        job.update(|j| j.active.push(task_id.to_string()));
+
+        let result = self.blocking(action).await;
+
+        job.update(|j| {
+            j.active.retain(|id| id != task_id);
+            j.completed += 1;
+            if let Err(e) = &result {
+                j.failed.push(task_id.to_string());
+                j.errors.insert(task_id.to_string(), e.to_string());
+            }
+        });
+        result
+    }
+
+    // 在阻塞线程中操作进程，不记录任务进度
+    async fn blocking<F>(&self, action: F) -> Result<(), OrchestratorError>
+    where
+        F: FnOnce(&ProcessManager) -> Result<(), ProcessError> + Send + 'static,
+    {
+        let pm = self.pm.clone();
+        match tokio::task::spawn_blocking(move || action(&pm)).await {
+            Ok(result) => result.map_err(OrchestratorError::from),
+            Err(e) => Err(OrchestratorError::Internal(e.to_string())),
+        }
+    }
+
+    // 生成启动计划并按批次执行
+    async fn start(&self, job: &JobContext, operation: &Operation) -> Result<(), OrchestratorError> {
+        let plan = self.plan_start(&job.config, operation)?;
+        let count: usize = plan.batches.iter().map(|b| b.services.len()).sum();
+        job.update(|j| j.total = j.completed + count);
+
+        job.update(|j| {
+            for skipped in &plan.skipped {
+                let outcome = match skipped.reason {
+                    SkipReason::AlreadyRunning => ServiceOutcome::AlreadyRunning,
+                    SkipReason::NotAutoStart => ServiceOutcome::Skipped { reason: "auto start disabled".to_string() },
+                };
+                j.outcomes.insert(skipped.service_id.clone(), outcome);
+            }
+        });
+
+        let limits = job.config.groups.iter()
+            .filter_map(|g| g.max_concurrency.filter(|n| *n > 0).map(|n| (g.id.clone(), Arc::new(Semaphore::new(n)))))
+            .collect();
+        let progress = Mutex::new(PlanProgress { limits, ..Default::default() });
+        for batch in &plan.batches {
+            job.check_cancelled()?;
+            let starts = batch.services.iter().map(|planned| self.start_planned(job, planned, &progress));
+            let results = future::join_all(starts).await;
+            if results.iter().any(|r| matches!(r, Err(OrchestratorError::Cancelled))) {
+                return Err(OrchestratorError::Cancelled);
+            }
+            self.rollback_failed_groups(job, &plan, &progress).await;
+            if batch.delay > 0 {
+                job.sleep(batch.delay).await?;
+            }
+        }
+
         Ok(())
     }
-    
-    pub fn start_task(&self, task_id: &str) -> Result<(), OrchestratorError> {
-        let config = self.config.lock().unwrap();
-        let config = config.as_ref().ok_or(OrchestratorError::TaskNotFound(task_id.to_string()))?;
-        
-        // 查找任务
-        let task = self.find_task(task_id, config)?;
-        
-        // 检查任务是否已经在运行
-        if self.pm.is_running(task_id) {
-            return Ok(());
+
+    // 启动计划中的一个服务：前置服务失败时跳过，等待依赖和健康检查，检查冲突后启动
+    async fn start_planned(&self, job: &JobContext, planned: &PlannedStart, progress: &Mutex<PlanProgress>) -> Result<(), OrchestratorError> {
+        let task_id = planned.service_id.as_str();
+        let result = self.prepare_planned(job, planned, progress).await;
+        let result = match result {
+            Ok(()) => {
+                // 按组限制同时启动的服务数
+                let limit = progress.lock().unwrap().limits.get(&planned.group_id).cloned();
+                let _permit = match &limit {
+                    Some(limit) => Some(tokio::select! {
+                        permit = limit.acquire() => permit.map_err(|e| OrchestratorError::Internal(e.to_string()))?,
+                        _ = job.cancel.cancelled() => return Err(OrchestratorError::Cancelled),
+                    }),
+                    None => None,
+                };
+                self.start_replacing(job, planned).await
+            }
+            Err(e) => {
+                if !matches!(e, OrchestratorError::Cancelled) {
+                    job.fail(task_id, &e);
+                }
+                Err(e)
+            }
+        };
+
+        {
+            let mut progress = progress.lock().unwrap();
+            progress.outcomes.insert(task_id.to_string(), result.is_ok());
+            if result.is_ok() {
+                progress.started_at.insert(task_id.to_string(), Instant::now());
+                progress.started.push(task_id.to_string());
+            }
         }
-        
-        // 处理依赖
-        if let Some(dep_configs) = &task.dependency_configs {
-            // 使用详细依赖配置
-            for dep_config in dep_configs {
-                match dep_config.r#type {
-                    DependencyType::Required => {
-                        // 必需依赖：必须启动成功
-                        self.start_task(&dep_config.service_id)?;
-                        
-                        // 等待依赖服务启动
-                        if let Some(timeout) = dep_config.timeout {
-                            thread::sleep(Duration::from_millis(timeout));
-                        }
-                    }
-                    DependencyType::Optional => {
-                        // 可选依赖：尝试启动，失败不影响
-                        let _ = self.start_task(&dep_config.service_id);
+        let oneshot = find_service(&job.config, task_id).is_ok_and(|s| s.is_oneshot());
+        let outcome = match &result {
+            Ok(()) if oneshot => Some(ServiceOutcome::Completed),
+            Ok(()) => Some(ServiceOutcome::Started),
+            Err(OrchestratorError::Cancelled) => None,
+            Err(e @ (OrchestratorError::DependencyFailed(..) | OrchestratorError::RolledBack(_))) => {
+                Some(ServiceOutcome::Skipped { reason: e.to_string() })
+            }
+            Err(e) => Some(ServiceOutcome::Failed { reason: e.to_string() }),
+        };
+        if let Some(outcome) = outcome {
+            job.update(|j| {
+                j.outcomes.insert(task_id.to_string(), outcome);
+            });
+        }
+        if result.is_ok() && planned.delay > 0 {
+            job.sleep(planned.delay).await?;
+        }
+        result
+    }
+
+    async fn prepare_planned(&self, job: &JobContext, planned: &PlannedStart, progress: &Mutex<PlanProgress>) -> Result<(), OrchestratorError> {
+        let task_id = planned.service_id.as_str();
+
+        if let Some(group_id) = &planned.atomic_group {
+            if progress.lock().unwrap().rolled_back.contains(group_id) {
+                return Err(OrchestratorError::RolledBack(group_id.clone()));
+            }
+        }
+
+        // 必需依赖和前置服务必须已经启动成功
+        let blockers = planned.dependencies.iter()
+            .filter(|d| matches!(d.r#type, DependencyType::Required))
+            .map(|d| &d.service_id)
+            .chain(planned.after.iter());
+        for blocker in blockers {
+            if progress.lock().unwrap().outcomes.get(blocker) == Some(&false) {
+                return Err(OrchestratorError::DependencyFailed(task_id.to_string(), blocker.clone()));
+            }
+        }
+
+        for dep in &planned.dependencies {
+            // 等待依赖服务启动
+            let started_at = progress.lock().unwrap().started_at.get(&dep.service_id).copied();
+            if let (Some(timeout), Some(started_at)) = (dep.timeout, started_at) {
+                let elapsed = started_at.elapsed().as_millis() as u64;
+                if elapsed < timeout {
+                    job.sleep(timeout - elapsed).await?;
+                }
+            }
+            if dep.health_gate {
+                match self.wait_healthy(job, &dep.service_id).await {
+                    Err(OrchestratorError::Cancelled) => return Err(OrchestratorError::Cancelled),
+                    Err(e) => return Err(OrchestratorError::DependencyFailed(task_id.to_string(), e.to_string())),
+                    Ok(()) => {}
+                }
+            }
+        }
+
+        // 冲突依赖：不能替换的冲突服务在运行则报错
+        for conflict in &planned.conflicts {
+            if conflict.resolution == ConflictResolution::Error && self.pm.is_running(&conflict.service_id) {
+                return Err(OrchestratorError::ConflictingService(task_id.to_string(), conflict.service_id.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    // 启动服务：先停止需要替换的冲突服务，新服务启动失败或没有通过健康检查时停止它并恢复被替换的服务
+    // 原子组中的服务同样需要通过健康检查
+    async fn start_replacing(&self, job: &JobContext, planned: &PlannedStart) -> Result<(), OrchestratorError> {
+        let task_id = planned.service_id.as_str();
+        let mut replaced: Vec<String> = Vec::new();
+        for conflict in &planned.conflicts {
+            if conflict.resolution != ConflictResolution::Replace || !self.pm.is_running(&conflict.service_id) {
+                continue;
+            }
+            if let Err(e) = self.stop_process(&job.config, &conflict.service_id).await {
+                self.restore(job, &replaced).await;
+                job.fail(task_id, &e);
+                return Err(e);
+            }
+            log::info!("Stopped {} to replace it with {}", conflict.service_id, task_id);
+            replaced.push(conflict.service_id.clone());
+        }
+
+        let mut result = self.start_process(job, task_id).await;
+        if result.is_ok() && (!replaced.is_empty() || planned.atomic_group.is_some()) {
+            if let Err(e) = self.wait_healthy(job, task_id).await {
+                let _ = self.stop_process(&job.config, task_id).await;
+                job.update(|j| {
+                    j.failed.push(task_id.to_string());
+                    j.errors.insert(task_id.to_string(), e.to_string());
+                });
+                result = Err(e);
+            }
+        }
+        if result.is_err() {
+            self.restore(job, &replaced).await;
+        }
+        result
+    }
+
+    // 回滚有服务失败的原子组：按启动顺序逆序停止本次启动的组内服务
+    async fn rollback_failed_groups(&self, job: &JobContext, plan: &StartPlan, progress: &Mutex<PlanProgress>) {
+        let atomic_groups: HashMap<&str, &str> = plan.batches.iter()
+            .flat_map(|b| b.services.iter())
+            .filter_map(|p| p.atomic_group.as_deref().map(|g| (p.service_id.as_str(), g)))
+            .collect();
+
+        let (failed_groups, started) = {
+            let progress = progress.lock().unwrap();
+            let mut failed_groups: Vec<&str> = Vec::new();
+            for (service_id, group_id) in &atomic_groups {
+                if progress.outcomes.get(*service_id) == Some(&false)
+                    && !progress.rolled_back.contains(*group_id)
+                    && !failed_groups.contains(group_id)
+                {
+                    failed_groups.push(group_id);
+                }
+            }
+            (failed_groups, progress.started.clone())
+        };
+
+        for group_id in failed_groups {
+            progress.lock().unwrap().rolled_back.insert(group_id.to_string());
+            let targets = started.iter()
+                .rev()
+                .filter(|id| atomic_groups.get(id.as_str()) == Some(&group_id));
+            for task_id in targets {
+                let result = match self.stop_process(&job.config, task_id).await {
+                    Err(OrchestratorError::ProcessError(ProcessError::NotFound)) => Ok(()),
+                    result => result,
+                };
+                progress.lock().unwrap().outcomes.insert(task_id.clone(), false);
+                job.update(|j| match &result {
+                    Ok(()) => {
+                        j.outcomes.insert(task_id.clone(), ServiceOutcome::RolledBack);
                     }
-                    DependencyType::Conflict => {
-                        // 冲突依赖：检查是否运行，如果运行则报错
-                        if self.pm.is_running(&dep_config.service_id) {
-                            return Err(OrchestratorError::ConflictingService(
-                                task_id.to_string(),
-                                dep_config.service_id.clone()
-                            ));
-                        }
+                    Err(e) => {
+                        let reason = format!("Rollback failed: {}", e);
+                        j.failed.push(task_id.clone());
+                        j.errors.insert(task_id.clone(), reason.clone());
+                        j.outcomes.insert(task_id.clone(), ServiceOutcome::RollbackFailed { reason });
                     }
+                });
+            }
+            log::warn!("Rolled back atomic group {}", group_id);
+        }
+    }
+
+    // 重新启动被替换的服务
+    async fn restore(&self, job: &JobContext, task_ids: &[String]) {
+        for task_id in task_ids {
+            let task = match self.prepare_ports(&job.config, task_id, false) {
+                Ok(task) => task,
+                Err(e) => {
+                    log::error!("Failed to restore replaced service {}: {}", task_id, e);
+                    continue;
                 }
+            };
+            let checked = task.clone();
+            let result = self.blocking(move |pm| start_with_hooks(pm, &task)).await;
+            match result {
+                Ok(()) => {
+                    self.start_health_check(&checked);
+                    log::info!("Restored replaced service {}", task_id);
+                }
+                Err(e) => log::error!("Failed to restore replaced service {}: {}", task_id, e),
             }
-        } else {
-            // 使用简单依赖列表（向后兼容）
-            for dep_id in &task.dependencies {
-                self.start_task(dep_id)?;
-            }
-        }
-        
-        // 启动当前任务
-        self.pm.start(
-            task_id,
-            &task.path,
-            task.work_dir.as_deref(),
-            task.args.as_ref(),
-            task.env.as_ref()
-        )?;
-        
-        Ok(())
+        }
+    }
+
+    // 停止服务，服务未在运行时视为成功
+    async fn stop_service(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        let task = find_service(&job.config, task_id).ok().map(|s| self.resolve_service(&job.config, s));
+        let task_id_owned = task_id.to_string();
+        self.stop_health_check(task_id);
+        self.step(job, task_id, move |pm| match stop_with_hooks(pm, task.as_ref(), &task_id_owned) {
+            Err(ProcessError::NotFound) => Ok(()),
+            result => result,
+        }).await
+    }
+
+    // 停止服务进程（不记录任务进度），服务在运行时前后运行 preStop/postStop 钩子
+    async fn stop_process(&self, config: &Config, task_id: &str) -> Result<(), OrchestratorError> {
+        let task = find_service(config, task_id).ok().map(|s| self.resolve_service(config, s));
+        let id = task_id.to_string();
+        self.stop_health_check(task_id);
+        self.blocking(move |pm| stop_with_hooks(pm, task.as_ref(), &id)).await
+    }
+
+    // 停止服务以及（传递地）以必需依赖方式依赖它的服务，依赖方先停止
+    async fn stop_service_and_dependents(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        let mut targets = required_dependents(&job.config, std::slice::from_ref(&task_id.to_string()));
+        targets.insert(task_id.to_string());
+        let running: Vec<String> = dependency_order(&job.config).into_iter()
+            .rev()
+            .filter(|id| targets.contains(id) && (id == task_id || self.pm.is_running(id)))
+            .collect();
+        self.stop_in_order(job, &running).await
     }
-    
-    pub fn stop_task(&self, task_id: &str) -> Result<(), OrchestratorError> {
-        // 停止任务
-        self.pm.stop(task_id)?;
+
+    async fn restart_service(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        if find_service(&job.config, task_id)?.is_oneshot() {
+            // 一次性服务重新运行：停止正在进行的运行后再运行一次
+            if self.pm.is_running(task_id) {
+                self.stop_process(&job.config, task_id).await?;
+            }
+            return self.run_oneshot(job, task_id).await;
+        }
+        // 服务自己仍占用着端口，不把它当作端口被占用
+        let task = self.prepare_ports(&job.config, task_id, self.pm.is_running(task_id))?;
+
+        // 重启任务（多实例服务重启所有实例）
+        let checked = task.clone();
+        self.stop_health_check(task_id);
+        self.step(job, task_id, move |pm| {
+            if pm.is_running(&task.id) {
+                stop_with_hooks(pm, Some(&task), &task.id)?;
+            }
+            start_with_hooks(pm, &task)
+        }).await?;
+        self.start_health_check(&checked);
         Ok(())
     }
-    
-    pub fn restart_task(&self, task_id: &str) -> Result<(), OrchestratorError> {
-        let config = self.config.lock().unwrap();
-        let config = config.as_ref().ok_or(OrchestratorError::TaskNotFound(task_id.to_string()))?;
-        
-        // 查找任务
-        let task = self.find_task(task_id, config)?;
-        
-        // 重启任务
-        self.pm.restart(
-            task_id,
-            &task.path,
-            task.work_dir.as_deref(),
-            task.args.as_ref(),
-            task.env.as_ref()
-        )?;
-        
+
+    // 重启多实例服务的单个实例，其余实例保持运行
+    async fn restart_instance(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        let instance = self.resolve_task(&job.config, task_id)?;
+        let checked = instance.clone();
+        self.stop_health_check(task_id);
+        self.step(job, task_id, move |pm| {
+            if pm.is_running(&instance.id) {
+                stop_with_hooks(pm, Some(&instance), &instance.id)?;
+            }
+            start_instance_with_hooks(pm, &instance)
+        }).await?;
+        self.start_health_check(&checked);
         Ok(())
     }
-    
-    pub fn start_group(&self, group_id: &str) -> Result<(), OrchestratorError> {
-        let config = self.config.lock().unwrap();
-        let config = config.as_ref().ok_or(OrchestratorError::GroupNotFound(group_id.to_string()))?;
-        
-        // 查找组
-        let group = config.groups.iter()
-            .find(|g| g.id == group_id)
-            .ok_or(OrchestratorError::GroupNotFound(group_id.to_string()))?;
-        
-        // 启动组内所有服务
-        for service in &group.services {
-            self.start_task(&service.id)?;
-        }
-        
+
+    // 正在以单实例运行的服务需要重启后才能按多实例运行，不能直接调整实例数
+    fn check_scalable(&self, service_id: &str) -> Result<(), OrchestratorError> {
+        if self.pm.is_running(service_id) && self.pm.instance_ids(service_id).is_empty() {
+            return Err(OrchestratorError::NotReplicated(service_id.to_string()));
+        }
         Ok(())
     }
-    
-    pub fn stop_group(&self, group_id: &str) -> Result<(), OrchestratorError> {
-        let config = self.config.lock().unwrap();
-        let config = config.as_ref().ok_or(OrchestratorError::GroupNotFound(group_id.to_string()))?;
-        
-        // 查找组
-        let group = config.groups.iter()
-            .find(|g| g.id == group_id)
-            .ok_or(OrchestratorError::GroupNotFound(group_id.to_string()))?;
-        
-        // 停止组内所有服务
-        for service in &group.services {
-            self.stop_task(&service.id)?;
-        }
-        
+
+    // 调整实例数：停止序号超出的实例，启动缺少的实例，其余实例保持运行；服务未运行时不启动，只使用新的实例数
+    async fn scale(&self, job: &JobContext, task_id: &str, replicas: u32) -> Result<(), OrchestratorError> {
+        if !self.pm.is_running(task_id) {
+            return Ok(());
+        }
+        self.check_scalable(task_id)?;
+        let task = self.prepare_ports(&job.config, task_id, true)?;
+        let running: Vec<u32> = self.pm.instance_ids(task_id).iter()
+            .filter(|id| self.pm.is_running(id))
+            .filter_map(|id| split_instance_id(id).map(|(_, index)| index))
+            .collect();
+        let excess: Vec<Service> = running.iter()
+            .rev()
+            .filter(|index| **index >= replicas)
+            .map(|index| task.instance(*index))
+            .collect();
+        let missing: Vec<Service> = (0..replicas)
+            .filter(|index| !running.contains(index))
+            .map(|index| task.instance(index))
+            .collect();
+        job.update(|j| j.total = excess.len() + missing.len());
+
+        // 实例的钩子与整个服务相同，环境变量中带有该实例的 INSTANCE 和 PORT
+        for instance in excess {
+            let id = instance.id.clone();
+            self.stop_health_check(&id);
+            self.step(job, &id, move |pm| match stop_with_hooks(pm, Some(&instance), &instance.id) {
+                Err(ProcessError::NotFound) => Ok(()),
+                result => result,
+            }).await?;
+        }
+        for instance in missing {
+            let id = instance.id.clone();
+            let checked = instance.clone();
+            self.step(job, &id, move |pm| start_instance_with_hooks(pm, &instance)).await?;
+            self.start_health_check(&checked);
+        }
         Ok(())
     }
-    
-    fn find_task<'a>(&self, task_id: &str, config: &'a Config) -> Result<&'a Service, OrchestratorError> {
-        for group in &config.groups {
-            for service in &group.services {
-                if service.id == task_id {
-                    return Ok(service);
-                }
+
+    // 滚动重启：按依赖顺序每次重启一批服务，全部健康后再重启下一批；有服务未通过健康检查时暂停
+    async fn rolling_restart(&self, job: &JobContext, group_id: &str, batch_size: usize) -> Result<(), OrchestratorError> {
+        let batches = self.rolling_batches(&job.config, group_id, batch_size)?;
+        let count: usize = batches.iter().map(|batch| batch.len()).sum();
+        job.update(|j| j.total = count);
+
+        for (index, batch) in batches.iter().enumerate() {
+            job.check_cancelled()?;
+            let restarts = batch.iter().map(|id| self.restart_gated(job, id));
+            let results = future::join_all(restarts).await;
+            if results.iter().any(|r| matches!(r, Err(OrchestratorError::Cancelled))) {
+                return Err(OrchestratorError::Cancelled);
+            }
+
+            let failed: Vec<&str> = batch.iter()
+                .zip(&results)
+                .filter(|(_, result)| result.is_err())
+                .map(|(id, _)| id.as_str())
+                .collect();
+            if !failed.is_empty() && index + 1 < batches.len() {
+                job.pause(format!("Paused: {} failed to become healthy", failed.join(", "))).await?;
             }
         }
-        Err(OrchestratorError::TaskNotFound(task_id.to_string()))
-    }
-    
-    // 混合模式启动组
-    fn start_group_mixed(&self, group_id: &str) -> Result<(), OrchestratorError> {
-        let config = self.config.lock().unwrap();
-        let config = config.as_ref().ok_or(OrchestratorError::GroupNotFound(group_id.to_string()))?;
-        
-        let group = config.groups.iter()
-            .find(|g| g.id == group_id)
-            .ok_or(OrchestratorError::GroupNotFound(group_id.to_string()))?;
-        
-        // 构建依赖图
-        let mut dep_graph: HashMap<String, Vec<String>> = HashMap::new();
-        for service in &group.services {
-            if !service.auto_start {
+
+        Ok(())
+    }
+
+    // 滚动重启的分批：只包括正在运行的服务（多实例服务为正在运行的实例），依赖的服务在前，同一批中的服务互不依赖
+    fn rolling_batches(&self, config: &Config, group_id: &str, batch_size: usize) -> Result<Vec<Vec<String>>, OrchestratorError> {
+        let group = find_group(config, group_id)?;
+        let mut batches: Vec<Vec<String>> = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+        for id in dependency_order(config) {
+            if !group.services.iter().any(|s| s.id == id) || !self.pm.is_running(&id) {
                 continue;
             }
-            
-            let deps = if let Some(dep_configs) = &service.dependency_configs {
-                dep_configs.iter()
-                    .filter(|d| matches!(d.r#type, DependencyType::Required))
-                    .map(|d| d.service_id.clone())
-                    .collect()
+            let service = find_service(config, &id)?;
+            // 多实例服务按实例分批，每次只重启一部分实例
+            let targets = if service.is_replicated() {
+                self.pm.instance_ids(&id).into_iter().filter(|i| self.pm.is_running(i)).collect()
             } else {
-                service.dependencies.clone()
+                vec![id.clone()]
             };
-            
-            dep_graph.insert(service.id.clone(), deps);
-        }
-        
-        // 拓扑排序启动
-        let mut started = HashSet::new();
-        let mut to_start: Vec<String> = group.services.iter()
-            .filter(|s| s.auto_start)
-            .map(|s| s.id.clone())
-            .collect();
-        
-        while !to_start.is_empty() {
-            let mut progress = false;
-            let mut next_batch = Vec::new();
-            
-            for service_id in &to_start {
-                let deps = dep_graph.get(service_id).cloned().unwrap_or_default();
-                let all_deps_started = deps.iter().all(|d| started.contains(d));
-                
-                if all_deps_started {
-                    self.start_task(service_id)?;
-                    started.insert(service_id.clone());
-                    progress = true;
-                } else {
-                    next_batch.push(service_id.clone());
+            let depends_on_current = service.start_dependencies().iter()
+                .any(|dep| current.iter().any(|c| c == dep || split_instance_id(c).map(|(s, _)| s) == Some(*dep)));
+            if depends_on_current && !current.is_empty() {
+                batches.push(std::mem::take(&mut current));
+            }
+            for target in targets {
+                if current.len() >= batch_size.max(1) {
+                    batches.push(std::mem::take(&mut current));
                 }
+                current.push(target);
             }
-            
-            if !progress && !next_batch.is_empty() {
-                return Err(OrchestratorError::CircularDependency);
+        }
+        if !current.is_empty() {
+            batches.push(current);
+        }
+        Ok(batches)
+    }
+
+    // 重启服务并等待健康
+    async fn restart_gated(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        if split_instance_id(task_id).is_some() {
+            self.restart_instance(job, task_id).await?;
+        } else {
+            self.restart_service(job, task_id).await?;
+        }
+        match self.wait_healthy(job, task_id).await {
+            Err(OrchestratorError::Cancelled) => Err(OrchestratorError::Cancelled),
+            Err(e) => {
+                job.update(|j| {
+                    j.failed.push(task_id.to_string());
+                    j.errors.insert(task_id.to_string(), e.to_string());
+                });
+                Err(e)
             }
-            
-            to_start = next_batch;
+            Ok(()) => Ok(()),
         }
-        
-        Ok(())
     }
-    
-    // 检查依赖关系是否存在循环
-    fn check_dependencies(&self, config: &Config) -> Result<(), OrchestratorError> {
-        let mut visited = HashSet::new();
-        let mut recursion_stack = HashSet::new();
-        
-        // 获取所有服务ID
-        let mut all_tasks = Vec::new();
-        for group in &config.groups {
-            for service in &group.services {
-                all_tasks.push(service.id.clone());
-            }
-        }
-        
-        // 对每个任务进行深度优先搜索
-        for task_id in all_tasks {
-            if !visited.contains(&task_id) {
-                if self.dfs(&task_id, config, &mut visited, &mut recursion_stack)? {
-                    return Err(OrchestratorError::CircularDependency);
+
+    // 级联重启：从上到下逐层停止，再从下到上逐层启动，每层健康后才启动下一层
+    async fn restart_cascade(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        let plan = self.restart_plan(&job.config, task_id)?;
+        let count: usize = plan.layers.iter().map(|layer| layer.len()).sum();
+        job.update(|j| j.total = count * 2);
+
+        for layer in plan.layers.iter().rev() {
+            let stops = layer.iter().map(|id| self.stop_service(job, id));
+            for result in future::join_all(stops).await {
+                result?;
+            }
+        }
+
+        for (index, layer) in plan.layers.iter().enumerate() {
+            let starts = layer.iter().map(|id| self.start_process(job, id));
+            for result in future::join_all(starts).await {
+                result?;
+            }
+
+            // 最后一层之后不需要等待
+            if index + 1 < plan.layers.len() {
+                let gates = layer.iter().map(|id| self.wait_healthy(job, id));
+                for result in future::join_all(gates).await {
+                    result?;
                 }
             }
         }
-        
+
         Ok(())
     }
-    
-    // 深度优先搜索，检查循环依赖
-    #[allow(dead_code)]
-    fn dfs(&self, task_id: &str, config: &Config, visited: &mut HashSet<String>, recursion_stack: &mut HashSet<String>) -> Result<bool, OrchestratorError> {
-        visited.insert(task_id.to_string());
-        recursion_stack.insert(task_id.to_string());
-        
-        // 获取任务
-        let task = self.find_task(task_id, config)?;
-        
-        // 检查依赖
-        for dep_id in &task.dependencies {
-            if !visited.contains(dep_id) {
-                if self.dfs(dep_id, config, visited, recursion_stack)? {
-                    return Ok(true);
-                }
-            } else if recursion_stack.contains(dep_id) {
-                return Ok(true);
+
+    // 直接启动服务进程（不处理依赖），一次性服务等待运行结束
+    async fn start_process(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        if find_service(&job.config, task_id)?.is_oneshot() {
+            return self.run_oneshot(job, task_id).await;
+        }
+        let task = self.prepare_ports(&job.config, task_id, self.pm.is_running(task_id))?;
+        let checked = task.clone();
+        self.step(job, task_id, move |pm| start_with_hooks(pm, &task)).await?;
+        self.start_health_check(&checked);
+        Ok(())
+    }
+
+    // 运行一次性服务并等待结束，退出码为 0 才算成功；任务取消时停止运行
+    async fn run_oneshot(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        job.check_cancelled()?;
+        job.update(|j| j.active.push(task_id.to_string()));
+
+        let killed = AtomicBool::new(false);
+        let run = self.scheduler.run_once(&job.config, task_id, "start", &killed);
+        tokio::pin!(run);
+        let (record, cancelled) = tokio::select! {
+            record = &mut run => (record, false),
+            _ = job.cancel.cancelled() => {
+                killed.store(true, Ordering::SeqCst);
+                let id = task_id.to_string();
+                let _ = self.blocking(move |pm| pm.stop(&id)).await;
+                (run.await, true)
+            }
+        };
+
+        let result = if cancelled {
+            Err(OrchestratorError::Cancelled)
+        } else if record.status == RunStatus::Succeeded.as_str() {
+            Ok(())
+        } else {
+            let reason = match record.exit_code {
+                Some(code) => format!("{} (exit code {})", record.status, code),
+                None => record.status.clone(),
+            };
+            Err(OrchestratorError::RunFailed(task_id.to_string(), reason))
+        };
+        job.update(|j| {
+            j.active.retain(|id| id != task_id);
+            j.completed += 1;
+            if let Err(e @ OrchestratorError::RunFailed(..)) = &result {
+                j.failed.push(task_id.to_string());
+                j.errors.insert(task_id.to_string(), e.to_string());
+            }
+        });
+        result
+    }
+
+    // 等待一次性服务正在进行的运行结束，退出码为 0 才算成功
+    async fn wait_completed(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        while self.pm.is_running(task_id) {
+            job.sleep(HEALTH_GATE_INTERVAL).await?;
+        }
+        match self.pm.exit_code(task_id) {
+            Some(0) => Ok(()),
+            Some(code) => Err(OrchestratorError::RunFailed(task_id.to_string(), format!("exit code {}", code))),
+            None => Err(OrchestratorError::RunFailed(task_id.to_string(), "process was stopped".to_string())),
+        }
+    }
+
+    // 服务或实例启动后开始健康检查，已有的检查重新开始
+    fn start_health_check(&self, task: &Service) {
+        if let Some(health_checker) = self.health_checker.get() {
+            health_checker.restart_checking(task);
+        }
+    }
+
+    // 服务或实例停止前结束健康检查，避免把正常停止当作不健康
+    fn stop_health_check(&self, task_id: &str) {
+        if let Some(health_checker) = self.health_checker.get() {
+            health_checker.stop_checking(task_id);
+        }
+    }
+
+    // 任务 ID 对应的服务或多实例服务的某个实例，端口引用按实际端口展开
+    fn resolve_task(&self, config: &Config, task_id: &str) -> Result<Service, OrchestratorError> {
+        match split_instance_id(task_id) {
+            Some((service_id, index)) => {
+                let service = find_service(config, service_id)?;
+                Ok(self.resolve_service(config, service).instance(index))
+            }
+            None => Ok(self.resolve_service(config, find_service(config, task_id)?)),
+        }
+    }
+
+    // 分配服务的自动端口并展开端口引用
+    fn prepare_ports(&self, config: &Config, task_id: &str, running: bool) -> Result<Service, OrchestratorError> {
+        let task = find_service(config, task_id)?;
+        self.ports.allocate(task, running)?;
+        Ok(self.ports.interpolate(config, task)?)
+    }
+
+    // 等待服务通过健康检查，未配置健康检查时只要求进程在运行；一次性服务等待运行成功结束
+    async fn wait_healthy(&self, job: &JobContext, task_id: &str) -> Result<(), OrchestratorError> {
+        // 健康检查的地址中可能引用了命名端口，按启动时的实际端口展开
+        let task = self.resolve_task(&job.config, task_id)?;
+        if task.is_oneshot() {
+            return self.wait_completed(job, task_id).await;
+        }
+        let timeout = task.timeout_config.as_ref()
+            .map_or(DEFAULT_HEALTH_GATE_TIMEOUT, |t| t.start_timeout);
+        let started = Instant::now();
+
+        loop {
+            if !self.pm.is_running(task_id) {
+                return Err(OrchestratorError::Unhealthy(task_id.to_string(), "process exited".to_string()));
+            }
+            let probe_task = task.clone();
+            let result = tokio::task::spawn_blocking(move || healthcheck::probe(&probe_task)).await
+                .map_err(|e| OrchestratorError::Internal(e.to_string()))?;
+            let message = match result {
+                None => return Ok(()),
+                Some(result) if result.status => return Ok(()),
+                Some(result) => result.message,
+            };
+            if started.elapsed() >= Duration::from_millis(timeout) {
+                return Err(OrchestratorError::Unhealthy(task_id.to_string(), message));
+            }
+            job.sleep(HEALTH_GATE_INTERVAL).await?;
+        }
+    }
+
+    // 停止组内正在运行的服务，按依赖关系逆序停止
+    async fn stop_group(&self, job: &JobContext, group_id: &str) -> Result<(), OrchestratorError> {
+        let group = find_group(&job.config, group_id)?;
+        let services: HashSet<&str> = group.services.iter().map(|s| s.id.as_str()).collect();
+        let running: Vec<String> = dependency_order(&job.config).into_iter()
+            .rev()
+            .filter(|task_id| services.contains(task_id.as_str()) && self.pm.is_running(task_id))
+            .collect();
+        self.stop_in_order(job, &running).await
+    }
+
+    // 停止应用内的服务，应用外的依赖服务可能被其他服务共用，保持运行
+    async fn stop_application(&self, job: &JobContext, application_id: &str) -> Result<(), OrchestratorError> {
+        let application = find_application(&job.config, application_id)?;
+        let services: HashSet<String> = application_services(&job.config, application).into_iter().collect();
+        let running: Vec<String> = dependency_order(&job.config).into_iter()
+            .rev()
+            .filter(|task_id| services.contains(task_id) && self.pm.is_running(task_id))
+            .collect();
+        self.stop_in_order(job, &running).await
+    }
+
+    /// 停止所有服务：按依赖关系逆序停止，依赖方先于被依赖方停止
+    async fn stop_all(&self, job: &JobContext) -> Result<(), OrchestratorError> {
+        let running: Vec<String> = dependency_order(&job.config).into_iter()
+            .rev()
+            .filter(|task_id| self.pm.is_running(task_id))
+            .collect();
+        self.stop_in_order(job, &running).await
+    }
+
+    // 依次停止服务，单个服务停止失败不会中断其余服务，失败的服务记录在任务中
+    async fn stop_in_order(&self, job: &JobContext, services: &[String]) -> Result<(), OrchestratorError> {
+        job.update(|j| j.total = j.completed + services.len());
+
+        for task_id in services {
+            match self.stop_service(job, task_id).await {
+                Err(OrchestratorError::Cancelled) => return Err(OrchestratorError::Cancelled),
+                Err(e) => log::error!("Failed to stop {}: {}", task_id, e),
+                Ok(()) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+}
+
+// 启动服务：先运行 preStart 钩子（失败时放弃启动），启动后运行 postStart 钩子
+fn start_with_hooks(pm: &ProcessManager, task: &Service) -> Result<(), ProcessError> {
+    hooks::run(pm, task, HookStage::PreStart).map_err(|e| ProcessError::StartError(e.to_string()))?;
+    spawn_instances(pm, task)?;
+    hooks::run_or_warn(pm, task, HookStage::PostStart);
+    Ok(())
+}
+
+// 启动多实例服务的单个实例，钩子的处理与启动整个服务相同
+fn start_instance_with_hooks(pm: &ProcessManager, instance: &Service) -> Result<(), ProcessError> {
+    hooks::run(pm, instance, HookStage::PreStart).map_err(|e| ProcessError::StartError(e.to_string()))?;
+    spawn(pm, instance)?;
+    hooks::run_or_warn(pm, instance, HookStage::PostStart);
+    Ok(())
+}
+
+// 停止服务，服务在运行时前后运行 preStop/postStop 钩子；实例 ID 等找不到配置时直接停止
+fn stop_with_hooks(pm: &ProcessManager, task: Option<&Service>, task_id: &str) -> Result<(), ProcessError> {
+    let Some(task) = task.filter(|_| pm.is_running(task_id)) else {
+        return pm.stop(task_id);
+    };
+    hooks::run_or_warn(pm, task, HookStage::PreStop);
+    pm.stop(task_id)?;
+    hooks::run_or_warn(pm, task, HookStage::PostStop);
+    Ok(())
+}
+
+// 启动服务的所有实例，多实例服务中已在运行的实例保持不变
+fn spawn_instances(pm: &ProcessManager, task: &Service) -> Result<(), ProcessError> {
+    for instance in task.instances() {
+        if task.is_replicated() && pm.is_running(&instance.id) {
+            continue;
+        }
+        spawn(pm, &instance)?;
+    }
+    Ok(())
+}
+
+fn spawn(pm: &ProcessManager, instance: &Service) -> Result<(), ProcessError> {
+    pm.start(
+        &instance.id,
+        &instance.path,
+        instance.work_dir.as_deref(),
+        instance.args.as_ref(),
+        instance.env.as_ref(),
+        instance.is_detached()
+    )
+}
+
+fn find_service<'a>(config: &'a Config, task_id: &str) -> Result<&'a Service, OrchestratorError> {
+    config.groups.iter()
+        .flat_map(|g| g.services.iter())
+        .find(|s| s.id == task_id)
+        .ok_or(OrchestratorError::TaskNotFound(task_id.to_string()))
+}
+
+fn find_application<'a>(config: &'a Config, application_id: &str) -> Result<&'a Application, OrchestratorError> {
+    config.applications.iter()
+        .find(|a| a.id == application_id)
+        .ok_or(OrchestratorError::ApplicationNotFound(application_id.to_string()))
+}
+
+// 应用内的所有服务，按组的启动顺序排列
+fn application_services(config: &Config, application: &Application) -> Vec<String> {
+    let groups = config.group_order().unwrap_or_else(|_| config.groups.iter().collect());
+    groups.into_iter()
+        .filter(|g| application.group_ids.contains(&g.id))
+        .flat_map(|g| g.services.iter().map(|s| s.id.clone()))
+        .collect()
+}
+
+fn find_group<'a>(config: &'a Config, group_id: &str) -> Result<&'a ServiceGroup, OrchestratorError> {
+    config.groups.iter()
+        .find(|g| g.id == group_id)
+        .ok_or(OrchestratorError::GroupNotFound(group_id.to_string()))
+}
+
+// 服务的必需依赖（简单依赖列表视为必需依赖）
+fn required_dependencies_of(service: &Service) -> Vec<String> {
+    let mut deps = service.dependencies.clone();
+    for dep_config in service.dependency_configs.iter().flatten() {
+        if matches!(dep_config.r#type, DependencyType::Required) && !deps.contains(&dep_config.service_id) {
+            deps.push(dep_config.service_id.clone());
+        }
+    }
+    deps
+}
+
+// 启动计划中服务的依赖：简单依赖列表视为必需依赖，只对本次启动的依赖做健康检查等待
+fn planned_dependencies(config: &Config, service: &Service, planned: &HashSet<&str>) -> Vec<PlannedDependency> {
+    let mut deps: Vec<PlannedDependency> = Vec::new();
+    let legacy = service.dependencies.iter().map(|id| (id, DependencyType::Required, None));
+    let configured = service.dependency_configs.iter()
+        .flatten()
+        .filter(|d| !matches!(d.r#type, DependencyType::Conflict))
+        .map(|d| (&d.service_id, d.r#type.clone(), d.timeout));
+
+    for (id, r#type, timeout) in legacy.chain(configured) {
+        if deps.iter().any(|d| &d.service_id == id) {
+            continue;
+        }
+        // 一次性依赖在本次启动中运行时，运行结束后才启动依赖方；正在运行时需要等待它结束
+        let health_gate = matches!(r#type, DependencyType::Required) && match find_service(config, id) {
+            Ok(dep) if dep.is_oneshot() => !planned.contains(id.as_str()),
+            Ok(dep) => planned.contains(id.as_str()) && healthcheck::can_probe(dep),
+            Err(_) => false,
+        };
+        deps.push(PlannedDependency {
+            service_id: id.clone(),
+            r#type,
+            timeout,
+            health_gate,
+        });
+    }
+    deps
+}
+
+// 以必需依赖方式（传递地）依赖指定服务的所有服务，不包括指定服务本身
+fn required_dependents(config: &Config, roots: &[String]) -> HashSet<String> {
+    let mut dependents = HashSet::new();
+    let mut pending: Vec<String> = roots.to_vec();
+    while let Some(task_id) = pending.pop() {
+        for service in config.groups.iter().flat_map(|g| g.services.iter()) {
+            if roots.contains(&service.id) || !required_dependencies_of(service).contains(&task_id) {
+                continue;
+            }
+            if dependents.insert(service.id.clone()) {
+                pending.push(service.id.clone());
             }
         }
-        
-        recursion_stack.remove(task_id);
-        Ok(false)
     }
-}
\ No newline at end of file
+    dependents
+}
+
+// 启动指定服务时会涉及的所有服务（包括传递依赖）
+fn start_closure(config: &Config, roots: &[String]) -> HashSet<String> {
+    let mut closure = HashSet::new();
+    let mut pending: Vec<String> = roots.to_vec();
+    while let Some(task_id) = pending.pop() {
+        let Ok(service) = find_service(config, &task_id) else { continue };
+        if closure.insert(task_id) {
+            pending.extend(service.start_dependencies().into_iter().map(String::from));
+        }
+    }
+    closure
+}
+
+// 所有服务的启动顺序（被依赖的服务在前），存在循环时剩余服务按配置顺序追加
+fn dependency_order(config: &Config) -> Vec<String> {
+    let services: Vec<&Service> = config.groups.iter()
+        .flat_map(|g| g.services.iter())
+        .collect();
+    let known: HashSet<&str> = services.iter().map(|s| s.id.as_str()).collect();
+
+    let deps_of = |service: &Service| -> Vec<String> {
+        service.start_dependencies().into_iter()
+            .filter(|d| known.contains(d))
+            .map(String::from)
+            .collect()
+    };
+
+    let mut order = Vec::new();
+    let mut placed = HashSet::new();
+    loop {
+        let mut progress = false;
+        for service in &services {
+            if placed.contains(&service.id) {
+                continue;
+            }
+            if deps_of(service).iter().all(|d| placed.contains(d)) {
+                placed.insert(service.id.clone());
+                order.push(service.id.clone());
+                progress = true;
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+
+    for service in &services {
+        if !placed.contains(&service.id) {
+            order.push(service.id.clone());
+        }
+    }
+
+    order
+}
+
+// 检查从指定服务出发能到达的依赖：引用的服务必须存在，且不能有循环
+fn check_dependencies(config: &Config, roots: &[String]) -> Result<(), OrchestratorError> {
+    let mut closure: Vec<String> = start_closure(config, roots).into_iter().collect();
+    closure.sort();
+    for task_id in closure {
+        let service = find_service(config, &task_id)?;
+        let conflict_ids = service.dependency_configs.iter()
+            .flatten()
+            .filter(|d| matches!(d.r#type, DependencyType::Conflict))
+            .map(|d| d.service_id.as_str());
+        for dep in service.start_dependencies().into_iter().chain(conflict_ids) {
+            if find_service(config, dep).is_err() {
+                return Err(OrchestratorError::MissingDependency(task_id.clone(), dep.to_string()));
+            }
+        }
+    }
+
+    let roots: Vec<&str> = roots.iter().map(|id| id.as_str()).collect();
+    match config.find_dependency_cycle(&roots) {
+        Some(cycle) => Err(OrchestratorError::CircularDependency(cycle.join(" → "))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn orchestrator() -> (tokio::runtime::Runtime, Orchestrator) {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let pm = Arc::new(ProcessManager::new(Arc::new(|_: &str, _: &str| {})));
+        let orchestrator = Orchestrator::new(pm, runtime.handle().clone(), Arc::new(|_: &Job| {}));
+        (runtime, orchestrator)
+    }
+
+    fn service(id: &str, dependencies: &[&str]) -> Value {
+        json!({ "id": id, "name": id, "path": "/bin/true", "autoStart": true, "dependencies": dependencies })
+    }
+
+    fn group(id: &str, strategy: &str, dependencies: &[&str], services: Vec<Value>) -> Value {
+        json!({
+            "id": id,
+            "name": id,
+            "startupDelay": 0,
+            "startupStrategy": strategy,
+            "dependencies": dependencies,
+            "services": services,
+        })
+    }
+
+    fn config(groups: Vec<Value>) -> Config {
+        Config {
+            groups: groups.into_iter().map(|g| serde_json::from_value(g).unwrap()).collect(),
+            ..Config::default()
+        }
+    }
+
+    fn batch_ids(plan: &StartPlan) -> Vec<Vec<&str>> {
+        plan.batches.iter()
+            .map(|b| b.services.iter().map(|s| s.service_id.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn independent_services_share_a_batch() {
+        let (_runtime, orchestrator) = orchestrator();
+        let config = config(vec![group("g", "parallel", &[], vec![
+            service("web", &["api"]),
+            service("api", &["db", "cache"]),
+            service("cache", &[]),
+            service("db", &[]),
+        ])]);
+
+        let plan = orchestrator.plan_start(&config, &Operation::StartAll).unwrap();
+        let mut batches = batch_ids(&plan);
+        batches[0].sort();
+        assert_eq!(batches, vec![vec!["cache", "db"], vec!["api"], vec!["web"]]);
+        assert!(plan.skipped.is_empty());
+    }
+
+    #[test]
+    fn batches_follow_the_longest_dependency_path() {
+        let (_runtime, orchestrator) = orchestrator();
+        // worker 直接依赖 db，但也依赖位于第二批的 api，因此排在第三批
+        let config = config(vec![group("g", "parallel", &[], vec![
+            service("db", &[]),
+            service("api", &["db"]),
+            service("worker", &["db", "api"]),
+        ])]);
+
+        let plan = orchestrator.plan_start(&config, &Operation::StartAll).unwrap();
+        assert_eq!(batch_ids(&plan), vec![vec!["db"], vec!["api"], vec!["worker"]]);
+        let worker = &plan.batches[2].services[0];
+        let deps: Vec<&str> = worker.dependencies.iter().map(|d| d.service_id.as_str()).collect();
+        assert_eq!(deps, ["db", "api"]);
+    }
+
+    #[test]
+    fn sequential_group_starts_one_service_per_batch() {
+        let (_runtime, orchestrator) = orchestrator();
+        let mut group = group("g", "sequential", &[], vec![service("a", &[]), service("b", &[]), service("c", &[])]);
+        group["startupDelay"] = json!(500);
+        group["services"][0]["startupDelay"] = json!(200);
+        let config = config(vec![group]);
+
+        let plan = orchestrator.plan_start(&config, &Operation::StartGroup("g".to_string())).unwrap();
+        assert_eq!(batch_ids(&plan), vec![vec!["a"], vec!["b"], vec!["c"]]);
+        assert_eq!(plan.batches[1].services[0].after, ["a"]);
+        assert_eq!(plan.batches[0].services[0].delay, 200);
+        // 组的启动延迟放在最后一批之后
+        assert_eq!(plan.batches.iter().map(|b| b.delay).collect::<Vec<_>>(), [0, 0, 500]);
+    }
+
+    #[test]
+    fn dependent_group_starts_after_its_dependency() {
+        let (_runtime, orchestrator) = orchestrator();
+        let config = config(vec![
+            group("app", "parallel", &["infra"], vec![service("web", &[])]),
+            group("infra", "parallel", &[], vec![service("db", &[]), service("mq", &[])]),
+        ]);
+
+        let plan = orchestrator.plan_start(&config, &Operation::StartAll).unwrap();
+        let mut batches = batch_ids(&plan);
+        batches[0].sort();
+        assert_eq!(batches, vec![vec!["db", "mq"], vec!["web"]]);
+        let mut after = plan.batches[1].services[0].after.clone();
+        after.sort();
+        assert_eq!(after, ["db", "mq"]);
+    }
+
+    #[test]
+    fn start_service_includes_dependencies_without_auto_start() {
+        let (_runtime, orchestrator) = orchestrator();
+        let mut db = service("db", &[]);
+        db["autoStart"] = json!(false);
+        let mut manual = service("manual", &[]);
+        manual["autoStart"] = json!(false);
+        let config = config(vec![group("g", "parallel", &[], vec![db, service("api", &["db"]), manual])]);
+
+        let plan = orchestrator.plan_start(&config, &Operation::StartService("api".to_string())).unwrap();
+        assert_eq!(batch_ids(&plan), vec![vec!["db"], vec!["api"]]);
+        assert!(plan.skipped.is_empty());
+
+        // 全部启动时只跳过不被任何自动启动服务依赖的服务
+        let plan = orchestrator.plan_start(&config, &Operation::StartAll).unwrap();
+        assert_eq!(batch_ids(&plan), vec![vec!["db"], vec!["api"]]);
+        assert_eq!(plan.skipped.len(), 1);
+        assert_eq!(plan.skipped[0].service_id, "manual");
+        assert_eq!(plan.skipped[0].reason, SkipReason::NotAutoStart);
+    }
+
+    #[test]
+    fn only_start_operations_can_be_planned() {
+        let (_runtime, orchestrator) = orchestrator();
+        let config = config(vec![group("g", "parallel", &[], vec![service("a", &[])])]);
+        assert!(matches!(
+            orchestrator.plan_start(&config, &Operation::StopAll),
+            Err(OrchestratorError::NotStartOperation(_))
+        ));
+    }
+
+    #[test]
+    fn plan_rejects_conflicts_declared_by_either_side() {
+        let (_runtime, orchestrator) = orchestrator();
+        let mut nginx = service("nginx", &[]);
+        nginx["dependencyConfigs"] = json!([{ "serviceId": "apache", "type": "conflict" }]);
+        let config = config(vec![group("g", "parallel", &[], vec![nginx, service("apache", &[]), service("other", &[])])]);
+
+        // 冲突只由 nginx 声明，同时启动时不论从哪一方检查都会报错
+        assert!(matches!(
+            orchestrator.plan_start(&config, &Operation::StartAll),
+            Err(OrchestratorError::ConflictingService(..))
+        ));
+        // 单独启动任意一方时，计划中列出未运行的冲突服务
+        let plan = orchestrator.plan_start(&config, &Operation::StartService("apache".to_string())).unwrap();
+        let conflicts = &plan.batches[0].services[0].conflicts;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].service_id, "nginx");
+        assert!(!conflicts[0].running);
+    }
+}
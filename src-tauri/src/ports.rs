@@ -0,0 +1,305 @@
+// 服务的命名端口：固定端口或启动时自动分配的空闲端口
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::TcpListener;
+use std::sync::Mutex;
+use thiserror::Error;
+
+use crate::config::{Config, HealthCheckUnion, PortSpec, Service};
+use crate::database;
+
+// 自动分配端口时的最大尝试次数
+const ALLOCATE_ATTEMPTS: usize = 20;
+
+#[derive(Error, Debug)]
+pub enum PortError {
+    #[error("Unknown port reference: ${{{0}}}")]
+    UnknownPort(String),
+
+    #[error("No free port available for {0}")]
+    NoFreePort(String),
+}
+
+/// 自动端口的分配记录，保存在数据库中，重启后尽量继续使用同一个端口
+pub struct PortAllocator {
+    // (服务 ID, 端口名) -> 端口
+    allocations: Mutex<HashMap<(String, String), u16>>,
+}
+
+impl PortAllocator {
+    pub fn new() -> Self {
+        Self {
+            allocations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 从数据库加载上次分配的端口
+    pub async fn load(&self) {
+        let Some(db) = database::get_db() else {
+            return;
+        };
+        match db.get_port_allocations().await {
+            Ok(rows) => {
+                let mut allocations = self.allocations.lock().unwrap();
+                for (service_id, name, port) in rows {
+                    allocations.insert((service_id, name), port);
+                }
+            }
+            Err(e) => log::error!("Failed to load port allocations: {}", e),
+        }
+    }
+
+    /// 启动服务前分配自动端口
+    ///
+    /// 上次分配的端口仍然空闲（或仍被正在运行的服务自己占用）时继续使用，否则重新分配。
+    pub fn allocate(&self, service: &Service, running: bool) -> Result<(), PortError> {
+        for (name, spec) in service.ports.iter().flatten() {
+            if !matches!(spec, PortSpec::Auto(_)) {
+                continue;
+            }
+            let key = (service.id.clone(), name.clone());
+            let current = self.allocations.lock().unwrap().get(&key).copied();
+            match current {
+                Some(port) if running || is_free(port) => {}
+                _ => {
+                    let port = self.pick(&format!("{}.{}", service.id, name))?;
+                    self.remember(key, port);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 服务的端口：固定端口和已分配的自动端口
+    pub fn ports_of(&self, service: &Service) -> BTreeMap<String, u16> {
+        let allocations = self.allocations.lock().unwrap();
+        service.ports.iter()
+            .flatten()
+            .filter_map(|(name, spec)| {
+                let port = match spec {
+                    PortSpec::Fixed(port) => Some(*port),
+                    PortSpec::Auto(_) => allocations.get(&(service.id.clone(), name.clone())).copied(),
+                };
+                port.map(|port| (name.clone(), port))
+            })
+            .collect()
+    }
+
+    /// 展开参数、环境变量、工作目录、钩子和健康检查中的 `${svc.<服务ID>.port.<名称>}` 和 `${port.<名称>}`
+    ///
+    /// 引用了尚未分配的自动端口时会先预留一个空闲端口，被引用的服务启动时继续使用它。
+    pub fn interpolate(&self, config: &Config, service: &Service) -> Result<Service, PortError> {
+        let expand = |value: &str| self.expand(config, service, value);
+
+        let mut resolved = service.clone();
+        if let Some(args) = &service.args {
+            resolved.args = Some(args.iter().map(|a| expand(a)).collect::<Result<_, _>>()?);
+        }
+        if let Some(env) = &service.env {
+            let mut expanded = HashMap::new();
+            for (key, value) in env {
+                expanded.insert(key.clone(), expand(value)?);
+            }
+            resolved.env = Some(expanded);
+        }
+        if let Some(work_dir) = &service.work_dir {
+            resolved.work_dir = Some(expand(work_dir)?);
+        }
+        if let Some(hooks) = &mut resolved.hooks {
+            let stages = [&mut hooks.pre_start, &mut hooks.post_start, &mut hooks.pre_stop, &mut hooks.post_stop];
+            for hook in stages.into_iter().flatten() {
+                hook.command = expand(&hook.command)?;
+                if let Some(args) = &hook.args {
+                    hook.args = Some(args.iter().map(|a| expand(a)).collect::<Result<_, _>>()?);
+                }
+            }
+        }
+        match &mut resolved.health_check {
+            Some(HealthCheckUnion::Old(health_check)) => {
+                if let Some(url) = &health_check.url {
+                    health_check.url = Some(expand(url)?);
+                }
+                if let Some(host) = &health_check.host {
+                    health_check.host = Some(expand(host)?);
+                }
+            }
+            Some(HealthCheckUnion::New(health_check)) => {
+                if let Some(check) = health_check.config.as_object_mut() {
+                    for key in ["url", "host"] {
+                        if let Some(serde_json::Value::String(value)) = check.get_mut(key) {
+                            *value = expand(value)?;
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+        Ok(resolved)
+    }
+
+    fn expand(&self, config: &Config, service: &Service, value: &str) -> Result<String, PortError> {
+        let mut result = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find("${") {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            let key = &rest[start + 2..start + end];
+            result.push_str(&rest[..start]);
+            match self.lookup(config, service, key)? {
+                Some(port) => result.push_str(&port.to_string()),
+                // 其他变量（例如 ${PORT}）保持原样
+                None => result.push_str(&rest[start..start + end + 1]),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    // 查找端口引用，不是端口引用时返回 None
+    fn lookup(&self, config: &Config, service: &Service, key: &str) -> Result<Option<u16>, PortError> {
+        let (target, name) = if let Some(name) = key.strip_prefix("port.") {
+            (service, name)
+        } else if let Some(reference) = key.strip_prefix("svc.") {
+            let Some((service_id, name)) = reference.rsplit_once(".port.") else {
+                return Ok(None);
+            };
+            let target = config.groups.iter()
+                .flat_map(|g| g.services.iter())
+                .find(|s| s.id == service_id)
+                .ok_or_else(|| PortError::UnknownPort(key.to_string()))?;
+            (target, name)
+        } else {
+            return Ok(None);
+        };
+
+        match target.ports.as_ref().and_then(|ports| ports.get(name)) {
+            Some(PortSpec::Fixed(port)) => Ok(Some(*port)),
+            Some(PortSpec::Auto(_)) => {
+                let key = (target.id.clone(), name.to_string());
+                if let Some(port) = self.allocations.lock().unwrap().get(&key) {
+                    return Ok(Some(*port));
+                }
+                let port = self.pick(&format!("{}.{}", target.id, name))?;
+                self.remember(key, port);
+                Ok(Some(port))
+            }
+            None => Err(PortError::UnknownPort(key.to_string())),
+        }
+    }
+
+    // 由系统分配一个空闲端口，避开已分配给其他服务的端口
+    fn pick(&self, label: &str) -> Result<u16, PortError> {
+        for _ in 0..ALLOCATE_ATTEMPTS {
+            let port = TcpListener::bind(("127.0.0.1", 0))
+                .and_then(|listener| listener.local_addr())
+                .map(|addr| addr.port())
+                .map_err(|_| PortError::NoFreePort(label.to_string()))?;
+            let taken = self.allocations.lock().unwrap().values().any(|p| *p == port);
+            if !taken {
+                return Ok(port);
+            }
+        }
+        Err(PortError::NoFreePort(label.to_string()))
+    }
+
+    fn remember(&self, key: (String, String), port: u16) {
+        log::info!("Allocated port {} for {}.{}", port, key.0, key.1);
+        self.allocations.lock().unwrap().insert(key.clone(), port);
+        database::spawn(move |db| async move { db.save_port_allocation(&key.0, &key.1, port).await });
+    }
+}
+
+// 端口在本机上是否可以监听
+fn is_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok() && TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServiceGroup;
+    use serde_json::json;
+
+    fn service(id: &str, ports: serde_json::Value) -> Service {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": id,
+            "path": "/bin/true",
+            "autoStart": false,
+            "ports": ports,
+        })).unwrap()
+    }
+
+    fn config(services: Vec<Service>) -> Config {
+        let group: ServiceGroup = serde_json::from_value(json!({
+            "id": "g",
+            "name": "g",
+            "startupDelay": 0,
+            "services": services,
+        })).unwrap();
+        Config { groups: vec![group], ..Config::default() }
+    }
+
+    #[test]
+    fn expands_own_and_other_service_ports() {
+        let api = service("api", json!({ "http": 8080 }));
+        let db = service("db", json!({ "main": 5432 }));
+        let config = config(vec![api.clone(), db]);
+        let ports = PortAllocator::new();
+
+        let value = ports.expand(&config, &api, "http://localhost:${port.http}/?db=${svc.db.port.main}").unwrap();
+        assert_eq!(value, "http://localhost:8080/?db=5432");
+    }
+
+    #[test]
+    fn unknown_reference_is_an_error() {
+        let api = service("api", json!({ "http": 8080 }));
+        let config = config(vec![api.clone()]);
+        let ports = PortAllocator::new();
+
+        for key in ["port.grpc", "svc.db.port.main", "svc.api.port.grpc"] {
+            match ports.lookup(&config, &api, key) {
+                Err(PortError::UnknownPort(reference)) => assert_eq!(reference, key),
+                other => panic!("expected UnknownPort for {}, got {:?}", key, other),
+            }
+        }
+        assert!(matches!(ports.expand(&config, &api, "--port=${port.grpc}"), Err(PortError::UnknownPort(_))));
+    }
+
+    #[test]
+    fn other_variables_are_left_intact() {
+        let api = service("api", json!({ "http": 8080 }));
+        let config = config(vec![api.clone()]);
+        let ports = PortAllocator::new();
+
+        assert_eq!(ports.lookup(&config, &api, "PORT").unwrap(), None);
+        assert_eq!(ports.lookup(&config, &api, "svc.api.host").unwrap(), None);
+        let value = ports.expand(&config, &api, "${PORT}-${INSTANCE}-${port.http}").unwrap();
+        assert_eq!(value, "${PORT}-${INSTANCE}-8080");
+    }
+
+    #[test]
+    fn unterminated_reference_is_kept() {
+        let api = service("api", json!({ "http": 8080 }));
+        let config = config(vec![api.clone()]);
+        let ports = PortAllocator::new();
+
+        assert_eq!(ports.expand(&config, &api, "--port=${port.http").unwrap(), "--port=${port.http");
+        assert_eq!(ports.expand(&config, &api, "${port.http}:${").unwrap(), "8080:${");
+    }
+
+    #[test]
+    fn referenced_auto_port_is_reserved_once() {
+        let api = service("api", json!({}));
+        let db = service("db", json!({ "main": "auto" }));
+        let config = config(vec![api.clone(), db.clone()]);
+        let ports = PortAllocator::new();
+
+        let first = ports.lookup(&config, &api, "svc.db.port.main").unwrap().unwrap();
+        let second = ports.lookup(&config, &api, "svc.db.port.main").unwrap().unwrap();
+        assert_eq!(first, second);
+        assert_eq!(ports.ports_of(&db).get("main"), Some(&first));
+    }
+}
@@ -1,13 +1,19 @@
 use std::process::{Command, Child, Stdio};
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
 use std::thread;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::time::{Duration, Instant};
 use thiserror::Error;
-use sysinfo::{Pid, System, SystemExt, ProcessExt};
+use sysinfo::{Pid, System, SystemExt, ProcessExt, Signal};
 use serde::{Serialize, Deserialize};
 
+use crate::config::{split_instance_id, INSTANCE_SEPARATOR};
+use crate::database::{self, DetachedProcessRecord};
+
 #[derive(Error, Debug)]
 pub enum ProcessError {
     #[error("Failed to start process: {0}")]
@@ -28,6 +34,29 @@ impl From<std::io::Error> for ProcessError {
 
 pub type LogCallback = Arc<dyn Fn(&str, &str) + Send + Sync + 'static>;
 
+// 每个服务在内存中保留的最近输出行数
+const RECENT_LOG_LINES: usize = 500;
+
+// 服务最近的输出以及累计输出的行数
+#[derive(Default)]
+struct LogBuffer {
+    lines: VecDeque<String>,
+    total: u64,
+}
+
+type RecentLogs = Arc<Mutex<HashMap<String, LogBuffer>>>;
+
+// 分离模式进程的 PID 复用判断允许的启动时间误差（秒）
+const START_TIME_TOLERANCE: u64 = 2;
+
+// 分离模式运行的进程：可能由本次启动创建，也可能是重新接管的
+struct DetachedProcess {
+    pid: u32,
+    start_time: u64,
+    // 通知日志文件跟踪线程退出
+    tail_stop: Arc<AtomicBool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct ProcessStats {
@@ -41,7 +70,12 @@ pub struct ProcessStats {
 
 pub struct ProcessManager {
     processes: Arc<Mutex<HashMap<String, Child>>>,
+    detached: Arc<Mutex<HashMap<String, DetachedProcess>>>,
+    recent_logs: RecentLogs,
     log_callback: LogCallback,
+    log_dir: Mutex<PathBuf>,
+    // 已退出进程的退出码，重新启动时清除
+    exit_codes: Mutex<HashMap<String, Option<i32>>>,
     #[allow(dead_code)]
     system: Arc<Mutex<System>>,
 }
@@ -50,14 +84,23 @@ impl ProcessManager {
     pub fn new(log_callback: LogCallback) -> Self {
         Self {
             processes: Arc::new(Mutex::new(HashMap::new())),
+            detached: Arc::new(Mutex::new(HashMap::new())),
+            recent_logs: Arc::new(Mutex::new(HashMap::new())),
             log_callback,
+            log_dir: Mutex::new(std::env::temp_dir().join("service-flow").join("logs")),
+            exit_codes: Mutex::new(HashMap::new()),
             system: Arc::new(Mutex::new(System::new_all())),
         }
     }
     
-    pub fn start(&self, task_id: &str, path: &str, work_dir: Option<&str>, args: Option<&Vec<String>>, env: Option<&HashMap<String, String>>) -> Result<(), ProcessError> {
-        log::info!("Starting process: task_id={}, path={}, args={:?}, work_dir={:?}", 
-                   task_id, path, args, work_dir);
+    /// 设置分离模式服务的日志文件目录
+    pub fn set_log_dir(&self, dir: PathBuf) {
+        *self.log_dir.lock().unwrap() = dir;
+    }
+    
+    pub fn start(&self, task_id: &str, path: &str, work_dir: Option<&str>, args: Option<&Vec<String>>, env: Option<&HashMap<String, String>>, detached: bool) -> Result<(), ProcessError> {
+        log::info!("Starting process: task_id={}, path={}, args={:?}, work_dir={:?}, detached={}", 
+                   task_id, path, args, work_dir, detached);
         
         // 检查可执行文件是否存在（如果是绝对路径）
         let exe_path = std::path::Path::new(path);
@@ -66,6 +109,7 @@ impl ProcessManager {
             return Err(ProcessError::StartError(format!("Executable file does not exist: {}", path)));
         }
         
+        self.exit_codes.lock().unwrap().remove(task_id);
         let mut cmd = Command::new(path);
         
         // 设置工作目录
@@ -92,6 +136,10 @@ impl ProcessManager {
             cmd.envs(e);
         }
         
+        if detached {
+            return self.start_detached(task_id, cmd);
+        }
+        
         // 捕获标准输出和错误
         let child = cmd
             .stdout(Stdio::piped())
@@ -113,40 +161,248 @@ impl ProcessManager {
         let child = processes.get_mut(task_id).unwrap();
         
         // 处理标准输出
-        let task_id_clone = task_id.to_string();
-        let log_callback_clone = self.log_callback.clone();
         if let Some(stdout) = child.stdout.take() {
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        log_callback_clone(&task_id_clone, &line);
-                    }
-                }
-            });
+            self.spawn_log_reader(task_id, stdout);
         }
         
         // 处理标准错误
-        let task_id_clone = task_id.to_string();
-        let log_callback_clone = self.log_callback.clone();
         if let Some(stderr) = child.stderr.take() {
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        log_callback_clone(&task_id_clone, &line);
-                    }
-                }
-            });
+            self.spawn_log_reader(task_id, stderr);
+        }
+        
+        Ok(())
+    }
+    
+    // 以分离模式启动：输出写入日志文件，进程放入独立的进程组，ServiceFlow 退出时不终止
+    fn start_detached(&self, task_id: &str, mut cmd: Command) -> Result<(), ProcessError> {
+        let log_dir = self.log_dir.lock().unwrap().clone();
+        fs::create_dir_all(&log_dir)?;
+        let log_path = log_dir.join(format!("{}.log", task_id));
+        let log_file = OpenOptions::new().create(true).append(true).open(&log_path)?;
+        let offset = log_file.metadata()?.len();
+        
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::from(log_file.try_clone()?))
+            .stderr(Stdio::from(log_file));
+        
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
         }
         
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const DETACHED_PROCESS: u32 = 0x00000008;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+            cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+        }
+        
+        let child = cmd.spawn().map_err(|e| {
+            log::error!("Failed to spawn process: {}", e);
+            ProcessError::StartError(e.to_string())
+        })?;
+        
+        let pid = child.id();
+        let start_time = self.process_start_time(pid).unwrap_or(0);
+        self.processes.lock().unwrap().insert(task_id.to_string(), child);
+        
+        let tail_stop = self.spawn_log_tailer(task_id, log_path.clone(), offset);
+        self.detached.lock().unwrap().insert(task_id.to_string(), DetachedProcess {
+            pid,
+            start_time,
+            tail_stop,
+        });
+        
+        let record = DetachedProcessRecord {
+            task_id: task_id.to_string(),
+            pid,
+            start_time,
+            log_path: log_path.to_string_lossy().to_string(),
+        };
+        database::spawn(move |db| async move { db.save_detached_process(&record).await });
+        
         Ok(())
     }
     
+    /// 重新接管上次以分离模式启动且仍在运行的进程，返回接管的服务 ID
+    pub async fn reattach(&self) -> Vec<String> {
+        let Some(db) = database::get_db() else {
+            return Vec::new();
+        };
+        let records = match db.get_detached_processes().await {
+            Ok(records) => records,
+            Err(e) => {
+                log::error!("Failed to load detached processes: {}", e);
+                return Vec::new();
+            }
+        };
+        
+        let mut adopted = Vec::new();
+        for record in records {
+            if self.adopt(&record) {
+                log::info!("Reattached to {} (PID {})", record.task_id, record.pid);
+                adopted.push(record.task_id);
+            } else if let Err(e) = db.remove_detached_process(&record.task_id).await {
+                log::error!("Failed to remove detached process {}: {}", record.task_id, e);
+            }
+        }
+        adopted
+    }
+    
+    // 进程仍在运行且启动时间一致时接管，日志从文件末尾开始跟踪
+    fn adopt(&self, record: &DetachedProcessRecord) -> bool {
+        if self.processes.lock().unwrap().contains_key(&record.task_id)
+            || self.detached.lock().unwrap().contains_key(&record.task_id) {
+            return false;
+        }
+        if !self.is_same_process(record.pid, record.start_time) {
+            return false;
+        }
+        
+        let log_path = PathBuf::from(&record.log_path);
+        let offset = fs::metadata(&log_path).map(|m| m.len()).unwrap_or(0);
+        let tail_stop = self.spawn_log_tailer(&record.task_id, log_path, offset);
+        self.detached.lock().unwrap().insert(record.task_id.clone(), DetachedProcess {
+            pid: record.pid,
+            start_time: record.start_time,
+            tail_stop,
+        });
+        true
+    }
+    
+    fn process_start_time(&self, pid: u32) -> Option<u64> {
+        let mut system = self.system.lock().unwrap();
+        let pid = Pid::from(pid as usize);
+        if !system.refresh_process(pid) {
+            return None;
+        }
+        system.process(pid).map(|p| p.start_time())
+    }
+    
+    // 通过启动时间判断 PID 是否已被其他进程复用
+    fn is_same_process(&self, pid: u32, start_time: u64) -> bool {
+        self.process_start_time(pid)
+            .map(|t| t.abs_diff(start_time) <= START_TIME_TOLERANCE)
+            .unwrap_or(false)
+    }
+    
+    // 不再管理分离模式进程：停止日志跟踪并删除数据库记录
+    fn forget_detached(&self, task_id: &str) {
+        if let Some(process) = self.detached.lock().unwrap().remove(task_id) {
+            process.tail_stop.store(true, Ordering::Relaxed);
+            let task_id = task_id.to_string();
+            database::spawn(move |db| async move { db.remove_detached_process(&task_id).await });
+        }
+    }
+    
+    // 逐行读取进程输出，保存到最近日志并转发给回调
+    fn spawn_log_reader<R: std::io::Read + Send + 'static>(&self, task_id: &str, output: R) {
+        let task_id = task_id.to_string();
+        let recent_logs = self.recent_logs.clone();
+        let log_callback = self.log_callback.clone();
+        thread::spawn(move || {
+            let reader = BufReader::new(output);
+            for line in reader.lines().map_while(Result::ok) {
+                record_log(&recent_logs, &log_callback, &task_id, &line);
+            }
+        });
+    }
+    
+    // 从指定位置开始跟踪日志文件（类似 tail -f），直到收到停止通知
+    fn spawn_log_tailer(&self, task_id: &str, path: PathBuf, offset: u64) -> Arc<AtomicBool> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        let task_id = task_id.to_string();
+        let recent_logs = self.recent_logs.clone();
+        let log_callback = self.log_callback.clone();
+        thread::spawn(move || {
+            let Ok(mut file) = File::open(&path) else {
+                log::warn!("Failed to open log file: {}", path.display());
+                return;
+            };
+            if file.seek(SeekFrom::Start(offset)).is_err() {
+                return;
+            }
+            
+            let mut reader = BufReader::new(file);
+            let mut line = Vec::new();
+            while !stop_flag.load(Ordering::Relaxed) {
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) => {
+                        thread::sleep(Duration::from_millis(200));
+                        // 日志文件被截断时从头读取
+                        if is_truncated(&path, &mut reader) {
+                            let _ = reader.seek(SeekFrom::Start(0));
+                            line.clear();
+                        }
+                    }
+                    // 行尚未写完，等待后续内容
+                    Ok(_) if !line.ends_with(b"\n") => {}
+                    Ok(_) => {
+                        let text = String::from_utf8_lossy(&line);
+                        record_log(&recent_logs, &log_callback, &task_id, text.trim_end_matches(['\r', '\n']));
+                        line.clear();
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+        stop
+    }
+    
+    /// 获取服务最近的输出（最多 `limit` 行）
+    pub fn recent_logs(&self, task_id: &str, limit: usize) -> Vec<String> {
+        let recent_logs = self.recent_logs.lock().unwrap();
+        recent_logs.get(task_id)
+            .map(|logs| logs.lines.iter().skip(logs.lines.len().saturating_sub(limit)).cloned().collect())
+            .unwrap_or_default()
+    }
+    
+    /// 向服务日志追加一行（例如钩子的输出）
+    pub fn log_line(&self, task_id: &str, line: &str) {
+        record_log(&self.recent_logs, &self.log_callback, task_id, line);
+    }
+    
+    /// 服务累计输出的行数，配合 `logs_since` 获取之后的输出
+    pub fn log_position(&self, task_id: &str) -> u64 {
+        self.recent_logs.lock().unwrap().get(task_id).map_or(0, |logs| logs.total)
+    }
+    
+    /// 获取 `position` 之后的输出（最多最后 `limit` 行）
+    pub fn logs_since(&self, task_id: &str, position: u64, limit: usize) -> Vec<String> {
+        let recent_logs = self.recent_logs.lock().unwrap();
+        let Some(logs) = recent_logs.get(task_id) else {
+            return Vec::new();
+        };
+        let new_lines = (logs.total.saturating_sub(position) as usize).min(logs.lines.len());
+        logs.lines.iter().skip(logs.lines.len() - new_lines.min(limit)).cloned().collect()
+    }
+    
+    /// 进程退出后的退出码；仍在运行、未启动过或被信号终止时为 None
+    pub fn exit_code(&self, task_id: &str) -> Option<i32> {
+        self.exit_codes.lock().unwrap().get(task_id).copied().flatten()
+    }
+    
+    /// 停止进程；多实例服务传入服务 ID 时停止所有实例
     pub fn stop(&self, task_id: &str) -> Result<(), ProcessError> {
-        let mut processes = self.processes.lock().unwrap();
+        if !self.has_process(task_id) {
+            let instances = self.instance_ids(task_id);
+            if !instances.is_empty() {
+                let mut result = Ok(());
+                for instance in instances {
+                    if let Err(e) = self.stop(&instance) {
+                        result = result.and(Err(e));
+                    }
+                }
+                return result;
+            }
+        }
+
+        let child = self.processes.lock().unwrap().remove(task_id);
         
-        if let Some(mut child) = processes.remove(task_id) {
+        if let Some(mut child) = child {
             // 尝试优雅终止
             if let Err(e) = child.kill() {
                 return Err(ProcessError::KillError(e.to_string()));
@@ -154,56 +410,164 @@ impl ProcessManager {
             
             // 等待进程退出
             let _ = child.wait();
+            self.forget_detached(task_id);
             
+            Ok(())
+        } else if let Some(pid) = self.detached_pid(task_id) {
+            // 重新接管的进程没有 Child 句柄，直接通过 PID 终止
+            self.kill_pid(pid)?;
+            self.forget_detached(task_id);
             Ok(())
         } else {
             Err(ProcessError::NotFound)
         }
     }
     
+    // 先发送 SIGTERM，超时后强制结束
+    fn kill_pid(&self, pid: u32) -> Result<(), ProcessError> {
+        let pid = Pid::from(pid as usize);
+        let mut system = self.system.lock().unwrap();
+        if !system.refresh_process(pid) {
+            return Ok(());
+        }
+        let process = system.process(pid).ok_or(ProcessError::NotFound)?;
+        if process.kill_with(Signal::Term) != Some(true) && !process.kill() {
+            return Err(ProcessError::KillError(format!("Failed to signal process {}", pid)));
+        }
+        
+        let start = Instant::now();
+        while system.refresh_process(pid) {
+            if start.elapsed() > Duration::from_secs(5) {
+                if let Some(process) = system.process(pid) {
+                    process.kill();
+                }
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        Ok(())
+    }
+    
+    /// 向进程发送信号；多实例服务传入服务 ID 时发送给所有实例
+    pub fn signal(&self, task_id: &str, signal: Signal) -> Result<(), ProcessError> {
+        let ids = if self.has_process(task_id) {
+            vec![task_id.to_string()]
+        } else {
+            self.instance_ids(task_id)
+        };
+        let pids: Vec<u32> = ids.iter()
+            .filter(|id| self.is_process_running(id))
+            .filter_map(|id| self.get_pid(id))
+            .collect();
+        if pids.is_empty() {
+            return Err(ProcessError::NotFound);
+        }
+        
+        let mut system = self.system.lock().unwrap();
+        for pid in pids {
+            let pid = Pid::from(pid as usize);
+            system.refresh_process(pid);
+            let process = system.process(pid).ok_or(ProcessError::NotFound)?;
+            if process.kill_with(signal) != Some(true) {
+                return Err(ProcessError::KillError(format!("Failed to send {:?} to process {}", signal, pid)));
+            }
+        }
+        Ok(())
+    }
+    
+    fn detached_pid(&self, task_id: &str) -> Option<u32> {
+        self.detached.lock().unwrap().get(task_id).map(|p| p.pid)
+    }
+    
+    fn has_process(&self, task_id: &str) -> bool {
+        self.processes.lock().unwrap().contains_key(task_id)
+            || self.detached.lock().unwrap().contains_key(task_id)
+    }
+    
+    /// 多实例服务中由本进程管理的实例 ID，按序号排列
+    pub fn instance_ids(&self, service_id: &str) -> Vec<String> {
+        let prefix = format!("{}{}", service_id, INSTANCE_SEPARATOR);
+        let mut instances: Vec<String> = self.processes.lock().unwrap().keys().cloned().collect();
+        instances.extend(self.detached.lock().unwrap().keys().cloned());
+        instances.retain(|id| id.starts_with(&prefix) && split_instance_id(id).map(|(s, _)| s) == Some(service_id));
+        instances.sort_by_key(|id| split_instance_id(id).map(|(_, index)| index));
+        instances.dedup();
+        instances
+    }
+    
+    /// 进程是否在运行；多实例服务传入服务 ID 时任一实例在运行即为运行
     pub fn is_running(&self, task_id: &str) -> bool {
+        if !self.has_process(task_id) {
+            return self.instance_ids(task_id).iter().any(|id| self.is_process_running(id));
+        }
+        self.is_process_running(task_id)
+    }
+    
+    fn is_process_running(&self, task_id: &str) -> bool {
         let mut processes = self.processes.lock().unwrap();
         
         if let Some(child) = processes.get_mut(task_id) {
             // 检查进程是否仍在运行
-            match child.try_wait() {
-                Ok(None) => true,  // 进程仍在运行
-                Ok(Some(_)) => {
-                    // 进程已退出，从映射中移除
-                    processes.remove(task_id);
-                    false
-                }
-                Err(_) => {
-                    // 发生错误，假设进程已退出
-                    processes.remove(task_id);
-                    false
-                }
+            let exit_code = match child.try_wait() {
+                Ok(None) => return true,  // 进程仍在运行
+                Ok(Some(status)) => status.code(),  // 进程已退出
+                Err(_) => None,  // 发生错误，假设进程已退出
+            };
+            self.exit_codes.lock().unwrap().insert(task_id.to_string(), exit_code);
+            
+            // 从映射中移除
+            processes.remove(task_id);
+            drop(processes);
+            self.forget_detached(task_id);
+            return false;
+        }
+        drop(processes);
+        
+        // 重新接管的进程通过 PID 和启动时间判断
+        let detached = self.detached.lock().unwrap()
+            .get(task_id)
+            .map(|p| (p.pid, p.start_time));
+        match detached {
+            Some((pid, start_time)) if self.is_same_process(pid, start_time) => true,
+            Some(_) => {
+                self.forget_detached(task_id);
+                false
             }
-        } else {
-            false
+            None => false,
         }
     }
     
+    /// 进程 PID；多实例服务传入服务 ID 时返回第一个运行中实例的 PID
     pub fn get_pid(&self, task_id: &str) -> Option<u32> {
-        let processes = self.processes.lock().unwrap();
-        
-        processes.get(task_id).map(|child| child.id())
+        let pid = self.processes.lock().unwrap().get(task_id).map(|child| child.id());
+        pid.or_else(|| self.detached_pid(task_id))
+            .or_else(|| {
+                self.instance_ids(task_id).iter()
+                    .filter(|id| self.is_process_running(id))
+                    .find_map(|id| self.get_pid(id))
+            })
     }
     
-    pub fn restart(&self, task_id: &str, path: &str, work_dir: Option<&str>, args: Option<&Vec<String>>, env: Option<&HashMap<String, String>>) -> Result<(), ProcessError> {
+    #[allow(dead_code)]
+    pub fn restart(&self, task_id: &str, path: &str, work_dir: Option<&str>, args: Option<&Vec<String>>, env: Option<&HashMap<String, String>>, detached: bool) -> Result<(), ProcessError> {
         // 先停止进程
         if self.is_running(task_id) {
             self.stop(task_id)?;
         }
         
         // 再启动进程
-        self.start(task_id, path, work_dir, args, env)
+        self.start(task_id, path, work_dir, args, env, detached)
     }
     
     #[allow(dead_code)]
     pub fn get_all_running_tasks(&self) -> Vec<String> {
-        let processes = self.processes.lock().unwrap();
-        processes.keys().cloned().collect()
+        let mut tasks: Vec<String> = self.processes.lock().unwrap().keys().cloned().collect();
+        for task_id in self.detached.lock().unwrap().keys() {
+            if !tasks.contains(task_id) {
+                tasks.push(task_id.clone());
+            }
+        }
+        tasks
     }
     
     #[allow(dead_code)]
@@ -278,11 +642,61 @@ impl ProcessManager {
     }
 }
 
+/// 解析信号名，例如 HUP、SIGUSR1
+pub fn parse_signal(name: &str) -> Option<Signal> {
+    let name = name.trim().to_uppercase();
+    match name.strip_prefix("SIG").unwrap_or(&name) {
+        "HUP" => Some(Signal::Hangup),
+        "INT" => Some(Signal::Interrupt),
+        "QUIT" => Some(Signal::Quit),
+        "TERM" => Some(Signal::Term),
+        "KILL" => Some(Signal::Kill),
+        "USR1" => Some(Signal::User1),
+        "USR2" => Some(Signal::User2),
+        _ => None,
+    }
+}
+
+// 保存一行输出到最近日志并转发给回调，实例的输出同时带上序号记到所属服务下
+fn record_log(recent_logs: &RecentLogs, log_callback: &LogCallback, task_id: &str, line: &str) {
+    push_log(recent_logs, log_callback, task_id, line);
+    if let Some((service_id, index)) = split_instance_id(task_id) {
+        push_log(recent_logs, log_callback, service_id, &format!("[{}] {}", index, line));
+    }
+}
+
+fn push_log(recent_logs: &RecentLogs, log_callback: &LogCallback, task_id: &str, line: &str) {
+    {
+        let mut recent_logs = recent_logs.lock().unwrap();
+        let logs = recent_logs.entry(task_id.to_string()).or_default();
+        if logs.lines.len() >= RECENT_LOG_LINES {
+            logs.lines.pop_front();
+        }
+        logs.lines.push_back(line.to_string());
+        logs.total += 1;
+    }
+    log_callback(task_id, line);
+}
+
+fn is_truncated(path: &Path, reader: &mut BufReader<File>) -> bool {
+    match (fs::metadata(path), reader.stream_position()) {
+        (Ok(metadata), Ok(position)) => metadata.len() < position,
+        _ => false,
+    }
+}
+
 impl Drop for ProcessManager {
     fn drop(&mut self) {
-        // 停止所有进程
+        // 停止所有进程，分离模式的进程继续运行
+        let detached = self.detached.lock().unwrap();
+        for process in detached.values() {
+            process.tail_stop.store(true, Ordering::Relaxed);
+        }
         let mut processes = self.processes.lock().unwrap();
-        for (_, mut child) in processes.drain() {
+        for (task_id, mut child) in processes.drain() {
+            if detached.contains_key(&task_id) {
+                continue;
+            }
             let _ = child.kill();
             let _ = child.wait();
         }
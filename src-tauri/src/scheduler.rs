@@ -0,0 +1,394 @@
+// 定时运行：按 cron 表达式启动服务的一次性运行，并在数据库中记录每次运行的结果
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+use crate::config::{Config, OverlapPolicy, ScheduleConfig, Service};
+use crate::database::{self, RunRecord};
+use crate::hooks::{self, HookStage};
+use crate::ports::PortAllocator;
+use crate::process::{ProcessError, ProcessManager};
+
+// 检查运行是否结束的间隔
+const RUN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// 运行记录中保留的输出行数
+const LOG_EXCERPT_LINES: usize = 50;
+
+// 默认返回的运行记录数量
+pub const DEFAULT_RUN_HISTORY: i64 = 20;
+
+/// 运行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    TimedOut,
+    Killed,
+    Skipped,
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Succeeded => "succeeded",
+            RunStatus::Failed => "failed",
+            RunStatus::TimedOut => "timedOut",
+            RunStatus::Killed => "killed",
+            RunStatus::Skipped => "skipped",
+        }
+    }
+}
+
+// 正在进行的运行（包括排队等待的运行）
+struct ActiveRun {
+    handle: JoinHandle<()>,
+    killed: Arc<AtomicBool>,
+}
+
+// 执行一次运行需要的上下文
+#[derive(Clone)]
+struct RunContext {
+    pm: Arc<ProcessManager>,
+    ports: Arc<PortAllocator>,
+    config: Arc<Config>,
+}
+
+pub struct Scheduler {
+    pm: Arc<ProcessManager>,
+    ports: Arc<PortAllocator>,
+    runtime: Handle,
+    schedules: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl Scheduler {
+    pub fn new(pm: Arc<ProcessManager>, ports: Arc<PortAllocator>, runtime: Handle) -> Self {
+        Self {
+            pm,
+            ports,
+            runtime,
+            schedules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 按配置重新安排所有定时服务，启动时和配置修改后调用；正在进行的运行不受影响
+    pub fn sync(&self, config: &Config) {
+        self.clear();
+
+        let context = RunContext {
+            pm: self.pm.clone(),
+            ports: self.ports.clone(),
+            config: Arc::new(config.clone()),
+        };
+        let mut schedules = self.schedules.lock().unwrap();
+        for service in config.groups.iter().flat_map(|g| g.services.iter()) {
+            let Some(settings) = &service.schedule else {
+                continue;
+            };
+            let (schedule, timezone) = match settings.parse() {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    log::error!("Failed to schedule {}: {}", service.id, e);
+                    continue;
+                }
+            };
+            log::info!("Scheduled {} with '{}'", service.id, settings.cron);
+            let handle = self.runtime.spawn(run_schedule(
+                context.clone(),
+                service.id.clone(),
+                settings.clone(),
+                schedule,
+                timezone,
+            ));
+            schedules.insert(service.id.clone(), handle);
+        }
+    }
+
+    /// 停止所有定时触发
+    pub fn clear(&self) {
+        for (_, handle) in self.schedules.lock().unwrap().drain() {
+            handle.abort();
+        }
+    }
+
+    /// 立即运行一次并等待结束（一次性服务），运行记录写入数据库；`killed` 置位后被停止的运行记录为 killed
+    pub async fn run_once(&self, config: &Config, service_id: &str, trigger: &str, killed: &AtomicBool) -> RunRecord {
+        let context = RunContext {
+            pm: self.pm.clone(),
+            ports: self.ports.clone(),
+            config: Arc::new(config.clone()),
+        };
+        let record = execute_run(&context, service_id, trigger, None, killed).await;
+        save_run(record.clone());
+        record
+    }
+
+    /// 服务下一次定时运行的时间
+    pub fn next_run(&self, service: &Service) -> Option<String> {
+        let (schedule, timezone) = service.schedule.as_ref()?.parse().ok()?;
+        next_fire(&schedule, timezone).map(|time| time.to_rfc3339())
+    }
+}
+
+/// 服务最近的运行记录，最新的在前
+pub async fn run_history(service_id: &str, limit: i64) -> Result<Vec<RunRecord>, String> {
+    let db = database::get_db().ok_or("Database not initialized".to_string())?;
+    db.get_runs(service_id, limit).await.map_err(|e| e.to_string())
+}
+
+// 按 cron 表达式循环触发运行
+async fn run_schedule(
+    context: RunContext,
+    service_id: String,
+    settings: ScheduleConfig,
+    schedule: cron::Schedule,
+    timezone: Option<Tz>,
+) {
+    let mut active: Option<ActiveRun> = None;
+    let queued = Arc::new(AtomicBool::new(false));
+
+    while let Some(next) = next_fire(&schedule, timezone) {
+        let wait = (next - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        let busy = context.pm.is_running(&service_id)
+            || active.as_ref().is_some_and(|run| !run.handle.is_finished());
+        if !busy {
+            active = Some(spawn_run(&context, &service_id, settings.timeout, None));
+            continue;
+        }
+
+        match settings.overlap {
+            OverlapPolicy::Skip => {
+                log::info!("Skipping scheduled run of {}: previous run still in progress", service_id);
+                save_run(skipped_run(&service_id, "Previous run still in progress"));
+            }
+            OverlapPolicy::Queue => {
+                if queued.swap(true, Ordering::SeqCst) {
+                    log::info!("Skipping scheduled run of {}: a run is already queued", service_id);
+                    save_run(skipped_run(&service_id, "A run is already queued"));
+                    continue;
+                }
+                let previous = active.take();
+                let waiting = queued.clone();
+                let run = spawn_run(&context, &service_id, settings.timeout, Some(Box::pin(async move {
+                    if let Some(previous) = previous {
+                        let _ = previous.handle.await;
+                    }
+                    waiting.store(false, Ordering::SeqCst);
+                })));
+                active = Some(run);
+            }
+            OverlapPolicy::Kill => {
+                log::info!("Killing previous run of {} before the scheduled run", service_id);
+                if let Some(previous) = active.take() {
+                    previous.killed.store(true, Ordering::SeqCst);
+                    stop_run(&context.pm, &service_id).await;
+                    let _ = previous.handle.await;
+                } else {
+                    // 手动启动的进程没有运行记录，直接停止
+                    stop_run(&context.pm, &service_id).await;
+                }
+                active = Some(spawn_run(&context, &service_id, settings.timeout, None));
+            }
+        }
+    }
+    log::warn!("Schedule of {} has no upcoming runs", service_id);
+}
+
+type BeforeRun = std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>;
+
+// 在后台执行一次运行，`before` 完成后才开始（用于排队）
+fn spawn_run(context: &RunContext, service_id: &str, timeout: Option<u64>, before: Option<BeforeRun>) -> ActiveRun {
+    let killed = Arc::new(AtomicBool::new(false));
+    let context = context.clone();
+    let service_id = service_id.to_string();
+    let run_killed = killed.clone();
+    let handle = tokio::spawn(async move {
+        if let Some(before) = before {
+            before.await;
+            // 等待手动启动的进程结束
+            while context.pm.is_running(&service_id) {
+                tokio::time::sleep(RUN_POLL_INTERVAL).await;
+            }
+        }
+        let record = execute_run(&context, &service_id, "schedule", timeout, &run_killed).await;
+        save_run(record);
+    });
+    ActiveRun { handle, killed }
+}
+
+// 启动服务并等待其退出，超时后终止
+async fn execute_run(context: &RunContext, service_id: &str, trigger: &str, timeout: Option<u64>, killed: &AtomicBool) -> RunRecord {
+    let started_at = Utc::now();
+    let started = Instant::now();
+    let record = |status: RunStatus, exit_code: Option<i32>, log_excerpt: String| RunRecord {
+        id: 0,
+        service_id: service_id.to_string(),
+        trigger: trigger.to_string(),
+        status: status.as_str().to_string(),
+        exit_code,
+        started_at: started_at.to_rfc3339(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        log_excerpt,
+    };
+
+    let task = match prepare(context, service_id) {
+        Ok(task) => task,
+        Err(e) => return record(RunStatus::Failed, None, e),
+    };
+    let position = context.pm.log_position(service_id);
+    let pm = context.pm.clone();
+    let hook_task = task.clone();
+    let spawned = tokio::task::spawn_blocking(move || {
+        hooks::run(&pm, &task, HookStage::PreStart).map_err(|e| e.to_string())?;
+        pm.start(&task.id, &task.path, task.work_dir.as_deref(), task.args.as_ref(), task.env.as_ref(), task.is_detached())
+            .map_err(|e| e.to_string())?;
+        hooks::run_or_warn(&pm, &task, HookStage::PostStart);
+        Ok::<_, String>(pm.get_pid(&task.id))
+    }).await;
+    let pid = match spawned {
+        Ok(Ok(pid)) => pid,
+        Ok(Err(e)) => return record(RunStatus::Failed, None, e),
+        Err(e) => return record(RunStatus::Failed, None, e.to_string()),
+    };
+    log::info!("Started run of {} ({})", service_id, trigger);
+
+    let mut timed_out = false;
+    loop {
+        tokio::time::sleep(RUN_POLL_INTERVAL).await;
+        // 进程被替换（例如被手动重启）也视为本次运行结束
+        if !context.pm.is_running(service_id) || context.pm.get_pid(service_id) != pid {
+            break;
+        }
+        if timeout.is_some_and(|timeout| started.elapsed() >= Duration::from_secs(timeout)) {
+            log::warn!("Run of {} timed out, stopping it", service_id);
+            timed_out = true;
+            stop_run(&context.pm, service_id).await;
+            break;
+        }
+    }
+
+    let exit_code = context.pm.exit_code(service_id);
+    let pm = context.pm.clone();
+    let _ = tokio::task::spawn_blocking(move || hooks::run_or_warn(&pm, &hook_task, HookStage::PostStop)).await;
+    let status = if timed_out {
+        RunStatus::TimedOut
+    } else if killed.load(Ordering::SeqCst) {
+        RunStatus::Killed
+    } else if exit_code == Some(0) {
+        RunStatus::Succeeded
+    } else {
+        RunStatus::Failed
+    };
+    let log_excerpt = context.pm.logs_since(service_id, position, LOG_EXCERPT_LINES).join("\n");
+    log::info!("Run of {} finished: {} (exit code {:?})", service_id, status.as_str(), exit_code);
+    record(status, exit_code, log_excerpt)
+}
+
+// 分配端口并展开端口引用
+fn prepare(context: &RunContext, service_id: &str) -> Result<Service, String> {
+    let service = context.config.groups.iter()
+        .flat_map(|g| g.services.iter())
+        .find(|s| s.id == service_id)
+        .ok_or(format!("Service not found: {}", service_id))?;
+    context.ports.allocate(service, false).map_err(|e| e.to_string())?;
+    context.ports.interpolate(&context.config, service).map_err(|e| e.to_string())
+}
+
+async fn stop_run(pm: &Arc<ProcessManager>, service_id: &str) {
+    let pm = pm.clone();
+    let service_id = service_id.to_string();
+    let result = tokio::task::spawn_blocking(move || match pm.stop(&service_id) {
+        Err(ProcessError::NotFound) => Ok(()),
+        result => result,
+    }).await;
+    if let Ok(Err(e)) = result {
+        log::error!("Failed to stop run: {}", e);
+    }
+}
+
+fn skipped_run(service_id: &str, reason: &str) -> RunRecord {
+    RunRecord {
+        id: 0,
+        service_id: service_id.to_string(),
+        trigger: "schedule".to_string(),
+        status: RunStatus::Skipped.as_str().to_string(),
+        exit_code: None,
+        started_at: Utc::now().to_rfc3339(),
+        duration_ms: 0,
+        log_excerpt: reason.to_string(),
+    }
+}
+
+fn save_run(record: RunRecord) {
+    database::spawn(move |db| async move { db.add_run(&record).await });
+}
+
+// 下一次触发时间，未指定时区时使用本地时区
+fn next_fire(schedule: &cron::Schedule, timezone: Option<Tz>) -> Option<DateTime<Utc>> {
+    match timezone {
+        Some(timezone) => schedule.upcoming(timezone).next().map(|time| time.with_timezone(&Utc)),
+        None => schedule.upcoming(Local).next().map(|time| time.with_timezone(&Utc)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    fn schedule(cron: &str, timezone: Option<&str>) -> ScheduleConfig {
+        ScheduleConfig {
+            cron: cron.to_string(),
+            timezone: timezone.map(String::from),
+            overlap: OverlapPolicy::default(),
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn five_field_expressions_fire_at_second_zero() {
+        let (parsed, timezone) = schedule("*/5 * * * *", None).parse().unwrap();
+        assert!(timezone.is_none());
+        let fire = next_fire(&parsed, None).unwrap();
+        assert_eq!(fire.second(), 0);
+        assert_eq!(fire.minute() % 5, 0);
+    }
+
+    #[test]
+    fn six_field_expressions_keep_seconds() {
+        let (parsed, _) = schedule("30 0 12 * * *", None).parse().unwrap();
+        let fire = next_fire(&parsed, Some(Tz::UTC)).unwrap();
+        assert_eq!((fire.hour(), fire.minute(), fire.second()), (12, 0, 30));
+    }
+
+    #[test]
+    fn next_fire_uses_the_configured_timezone() {
+        let (parsed, timezone) = schedule("0 9 * * *", Some("Asia/Shanghai")).parse().unwrap();
+        assert_eq!(timezone, Some(chrono_tz::Asia::Shanghai));
+
+        let fire = next_fire(&parsed, timezone).unwrap();
+        let now = Utc::now();
+        assert!(fire > now && fire - now <= chrono::Duration::days(1));
+        // 上海 9 点是 UTC 1 点
+        assert_eq!(fire.with_timezone(&chrono_tz::Asia::Shanghai).hour(), 9);
+        assert_eq!((fire.hour(), fire.minute()), (1, 0));
+    }
+
+    #[test]
+    fn invalid_cron_and_timezone_are_rejected() {
+        let error = schedule("61 * * * *", None).parse().unwrap_err();
+        assert!(error.starts_with("Invalid cron expression '61 * * * *'"), "{}", error);
+        assert!(schedule("not a cron", None).parse().is_err());
+
+        let error = schedule("0 9 * * *", Some("Mars/Olympus")).parse().unwrap_err();
+        assert_eq!(error, "Unknown timezone: Mars/Olympus");
+    }
+}
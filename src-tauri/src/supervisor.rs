@@ -0,0 +1,199 @@
+// 健康检查失败的处理：连续失败达到阈值后按 onUnhealthy 重启或停止服务，并记录到事件历史
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::Utc;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+use crate::config::{split_instance_id, Config, UnhealthyAction};
+use crate::database::{self, EventRecord};
+use crate::healthcheck::CheckResult;
+use crate::orchestrator::{JobState, Operation, Orchestrator};
+use crate::process::ProcessManager;
+
+// 默认返回的事件数量
+pub const DEFAULT_EVENT_HISTORY: i64 = 20;
+
+#[derive(Default)]
+struct SupervisorState {
+    // 服务或实例连续失败的次数
+    failures: HashMap<String, u32>,
+    // 服务或实例连续成功的次数
+    successes: HashMap<String, u32>,
+    // 服务连续自动重启的次数，连续成功 successThreshold 次后清零
+    restarts: HashMap<String, u32>,
+    // 正在等待或执行操作的服务
+    pending: HashMap<String, JoinHandle<()>>,
+}
+
+pub struct Supervisor {
+    pm: Arc<ProcessManager>,
+    runtime: Handle,
+    config: Mutex<Option<Arc<Config>>>,
+    state: Arc<Mutex<SupervisorState>>,
+}
+
+impl Supervisor {
+    pub fn new(pm: Arc<ProcessManager>, runtime: Handle) -> Self {
+        Self {
+            pm,
+            runtime,
+            config: Mutex::new(None),
+            state: Arc::new(Mutex::new(SupervisorState::default())),
+        }
+    }
+
+    /// 使用新的配置，之后的检查结果按新的 onUnhealthy 处理
+    pub fn sync(&self, config: &Config) {
+        *self.config.lock().unwrap() = Some(Arc::new(config.clone()));
+    }
+
+    /// 停止处理检查结果，取消等待中的重启
+    pub fn clear(&self) {
+        *self.config.lock().unwrap() = None;
+        let mut state = self.state.lock().unwrap();
+        for (_, handle) in state.pending.drain() {
+            handle.abort();
+        }
+        state.failures.clear();
+        state.successes.clear();
+        state.restarts.clear();
+    }
+
+    /// 处理一次健康检查结果
+    ///
+    /// 多实例服务统计每个实例的结果，任一实例连续失败达到阈值时处理整个服务。
+    pub fn report(&self, orchestrator: &Orchestrator, result: &CheckResult) {
+        let Some(config) = self.config.lock().unwrap().clone() else {
+            return;
+        };
+        let service_id = split_instance_id(&result.task_id)
+            .map(|(service_id, _)| service_id)
+            .unwrap_or(&result.task_id);
+        let Some(service) = config.groups.iter().flat_map(|g| g.services.iter()).find(|s| s.id == service_id) else {
+            return;
+        };
+        // 多实例服务的汇总结果不计数
+        if service.is_replicated() && result.task_id == service.id {
+            return;
+        }
+        let Some(policy) = service.unhealthy_policy() else {
+            return;
+        };
+        let threshold = policy.failure_threshold;
+
+        let mut state = self.state.lock().unwrap();
+        if result.status {
+            // 只通过一次检查不算恢复，避免反复重启后短暂健康的服务绕过重启次数限制
+            state.failures.remove(&result.task_id);
+            let successes = state.successes.entry(result.task_id.clone()).or_insert(0);
+            *successes += 1;
+            let recovered = *successes >= policy.success_threshold;
+            if recovered && !state.failures.keys().any(|id| belongs_to(id, service_id)) {
+                state.restarts.remove(service_id);
+            }
+            return;
+        }
+        state.successes.remove(&result.task_id);
+        let failures = state.failures.entry(result.task_id.clone()).or_insert(0);
+        *failures += 1;
+        if *failures != threshold || state.pending.contains_key(service_id) {
+            return;
+        }
+        // 已经停止的服务不处理，避免重新启动被手动停止的服务
+        if !self.pm.is_running(&result.task_id) {
+            return;
+        }
+
+        let reason = format!("{} consecutive failed health checks of {} ({})", threshold, result.task_id, result.message);
+        let (operation, delay, attempt) = match policy.action {
+            UnhealthyAction::None => return,
+            UnhealthyAction::Stop => (Operation::StopService(service_id.to_string()), Duration::ZERO, None),
+            UnhealthyAction::Restart => {
+                let retry = service.retry_config.clone().unwrap_or_default();
+                let restarts = state.restarts.get(service_id).copied().unwrap_or(0);
+                if restarts >= retry.max_retries {
+                    // 只在第一次达到上限时记录
+                    if restarts == retry.max_retries {
+                        state.restarts.insert(service_id.to_string(), restarts + 1);
+                        log::warn!("Not restarting {}: restart limit ({}) reached", service_id, retry.max_retries);
+                        save_event(service_id, "restartLimitReached", false, format!(
+                            "Restart limit ({}) reached after {}", retry.max_retries, reason));
+                    }
+                    return;
+                }
+                state.restarts.insert(service_id.to_string(), restarts + 1);
+                let attempt = format!("attempt {}/{}", restarts + 1, retry.max_retries);
+                (Operation::RestartService(service_id.to_string()), retry.delay(restarts), Some(attempt))
+            }
+        };
+
+        log::warn!("{} is unhealthy after {}, running {:?} in {:?}", service_id, reason, operation, delay);
+        let orchestrator = orchestrator.clone();
+        let pm = self.pm.clone();
+        let shared = self.state.clone();
+        let id = service_id.to_string();
+        let handle = self.runtime.spawn(async move {
+            tokio::time::sleep(delay).await;
+            let action = match &operation {
+                Operation::StopService(_) => "stop",
+                _ => "restart",
+            };
+            // 等待期间服务被手动停止时放弃重启
+            let outcome = if !pm.is_running(&id) {
+                log::info!("{} is no longer running, skipping {}", id, action);
+                None
+            } else {
+                Some(match orchestrator.run((*config).clone(), operation).await {
+                    Ok(job) if job.state == JobState::Succeeded => Ok(()),
+                    Ok(job) => Err(job.error.or_else(|| job.errors.values().next().cloned()).unwrap_or_else(|| format!("{:?}", job.state))),
+                    Err(e) => Err(e.to_string()),
+                })
+            };
+            let detail = match &attempt {
+                Some(attempt) => format!("{}, {}", reason, attempt),
+                None => reason,
+            };
+            match outcome {
+                None => {}
+                Some(Ok(())) => save_event(&id, action, true, format!("Ran {} after {}", action, detail)),
+                Some(Err(e)) => {
+                    log::error!("Failed to {} unhealthy service {}: {}", action, id, e);
+                    save_event(&id, action, false, format!("Failed to {} after {}: {}", action, detail, e));
+                }
+            }
+
+            // 处理完成后重新开始统计
+            let mut state = shared.lock().unwrap();
+            state.failures.retain(|task_id, _| !belongs_to(task_id, &id));
+            state.pending.remove(&id);
+        });
+        state.failures.retain(|task_id, _| !belongs_to(task_id, service_id));
+        state.pending.insert(service_id.to_string(), handle);
+    }
+}
+
+/// 服务最近的事件，最新的在前
+pub async fn event_history(service_id: &str, limit: i64) -> Result<Vec<EventRecord>, String> {
+    let db = database::get_db().ok_or("Database not initialized".to_string())?;
+    db.get_events(service_id, limit).await.map_err(|e| e.to_string())
+}
+
+// 任务 ID 是否为该服务或它的实例
+fn belongs_to(task_id: &str, service_id: &str) -> bool {
+    task_id == service_id || split_instance_id(task_id).map(|(s, _)| s) == Some(service_id)
+}
+
+fn save_event(service_id: &str, action: &str, success: bool, message: String) {
+    let record = EventRecord {
+        id: 0,
+        service_id: service_id.to_string(),
+        action: action.to_string(),
+        success,
+        message,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    database::spawn(move |db| async move { db.add_event(&record).await });
+}
@@ -0,0 +1,245 @@
+use std::sync::Mutex;
+use std::time::Instant;
+use chrono::Utc;
+use serde::Serialize;
+use sysinfo::{System, SystemExt, CpuExt, DiskExt, NetworksExt, NetworkExt};
+use lazy_static::lazy_static;
+
+#[derive(Serialize)]
+pub struct NetworkUsage {
+    #[serde(rename = "in")]
+    pub in_kb_s: f64,
+    pub out: f64,
+}
+
+#[derive(Serialize)]
+pub struct ServerHealthResponse {
+    pub cpu: f64,
+    pub memory: f64,
+    pub disk: f64,
+    pub network: NetworkUsage,
+    pub uptime: u64,
+    #[serde(rename = "lastUpdated")]
+    pub last_updated: String,
+    #[serde(rename = "os")]
+    pub os_name: String,
+    #[serde(rename = "kernel")]
+    pub kernel_version: String,
+    #[serde(rename = "cpuModel")]
+    pub cpu_model: String,
+    #[serde(rename = "totalMemory")]
+    pub total_memory_human: String,
+}
+
+struct PrevNetworkSample {
+    in_bytes: u64,
+    out_bytes: u64,
+    timestamp: Instant,
+}
+
+lazy_static! {
+    static ref PREV_NETWORK: Mutex<Option<PrevNetworkSample>> = Mutex::new(None);
+}
+
+/// 采集主机 CPU、内存、磁盘、网络等运行指标
+pub fn server_health() -> Result<ServerHealthResponse, String> {
+    let mut system = System::new_all();
+    system.refresh_all();
+
+    // CPU usage percentage
+    let cpu = system.global_cpu_info().cpu_usage() as f64;
+
+    // Memory usage percentage
+    let total_memory = system.total_memory() as f64;
+    let used_memory = system.used_memory() as f64;
+    let memory = if total_memory > 0.0 {
+        (used_memory / total_memory) * 100.0
+    } else {
+        0.0
+    };
+
+    // Disk usage percentage (use first disk as representative)
+    let mut disk_percent = 0.0;
+    if let Some(disk) = system.disks().first() {
+        let total_space = disk.total_space() as f64;
+        let available_space = disk.available_space() as f64;
+        if total_space > 0.0 {
+            let used_space = total_space - available_space;
+            disk_percent = (used_space / total_space) * 100.0;
+        }
+    }
+
+    // Uptime in seconds
+    let uptime = system.uptime();
+
+    // Network throughput: compute based on delta of cumulative bytes
+    let networks = system.networks();
+    let mut total_in = 0u64;
+    let mut total_out = 0u64;
+    for (_name, data) in networks.iter() {
+        total_in += data.received();
+        total_out += data.transmitted();
+    }
+
+    let now = Instant::now();
+    let mut prev = PREV_NETWORK.lock().map_err(|e| e.to_string())?;
+    let (in_kb_s, out_kb_s) = if let Some(prev_sample) = &*prev {
+        let elapsed = now.duration_since(prev_sample.timestamp).as_secs_f64();
+        if elapsed > 0.0 {
+            let in_delta = total_in.saturating_sub(prev_sample.in_bytes) as f64;
+            let out_delta = total_out.saturating_sub(prev_sample.out_bytes) as f64;
+            ((in_delta / 1024.0) / elapsed, (out_delta / 1024.0) / elapsed)
+        } else {
+            (0.0, 0.0)
+        }
+    } else {
+        (0.0, 0.0)
+    };
+
+    *prev = Some(PrevNetworkSample {
+        in_bytes: total_in,
+        out_bytes: total_out,
+        timestamp: now,
+    });
+
+    let network = NetworkUsage {
+        in_kb_s,
+        out: out_kb_s,
+    };
+
+    // System info
+    let os_name = system
+        .name()
+        .unwrap_or_else(|| "Unknown OS".to_string());
+    let kernel_version = system
+        .kernel_version()
+        .unwrap_or_else(|| "Unknown".to_string());
+    let cpu_model = system
+        .cpus()
+        .first()
+        .map(|c| c.brand().to_string())
+        .unwrap_or_else(|| "Unknown CPU".to_string());
+    // sysinfo 的 total_memory 在不同平台单位略有差异，这里按 MB 处理，只除以 1024 得到 GB
+    let total_mem_gb = (total_memory / 1024.0).max(0.0);
+    let total_memory_human = format!("{:.0} GB", total_mem_gb.round());
+
+    let last_updated = Utc::now().to_rfc3339();
+
+    Ok(ServerHealthResponse {
+        cpu,
+        memory,
+        disk: disk_percent,
+        network,
+        uptime,
+        last_updated,
+        os_name,
+        kernel_version,
+        cpu_model,
+        total_memory_human,
+    })
+}
+
+/// 查找进程（及其子进程）正在监听的 TCP 端口
+pub fn pid_port(pid: u32) -> Result<Option<u16>, String> {
+    #[cfg(target_os = "windows")]
+    use std::process::Command;
+    #[cfg(not(target_os = "windows"))]
+    let _ = pid;
+    
+    // Windows implementation using netstat
+    #[cfg(target_os = "windows")]
+    {
+        // 1. 获取 netstat 输出（一次获取，多次查询）
+        let output = Command::new("netstat")
+            .args(&["-ano"])
+            .output()
+            .map_err(|e| e.to_string())?;
+            
+        let netstat_str = String::from_utf8_lossy(&output.stdout);
+        
+        // 定义内部闭包：查找指定 PID 的端口
+        let find_port_for_pid = |target_pid: u32| -> Option<u16> {
+            for line in netstat_str.lines() {
+                // 确保包含 LISTENING 和 TCP
+                if !line.contains("LISTENING") || !line.contains("TCP") {
+                    continue;
+                }
+                
+                // 分割并检查最后一个字段是否精确匹配 PID
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() < 5 {
+                    continue;
+                }
+                
+                // PID 通常在最后
+                if let Ok(line_pid) = parts[parts.len() - 1].parse::<u32>() {
+                    if line_pid == target_pid {
+                        // 提取端口：本地地址通常是第二个字段 (parts[1])
+                        let local_address = parts[1]; // 0.0.0.0:80
+                        if let Some(colon_idx) = local_address.rfind(':') {
+                            if let Ok(port) = local_address[colon_idx + 1..].parse::<u16>() {
+                                return Some(port);
+                            }
+                        }
+                    }
+                }
+            }
+            None
+        };
+
+        // 2. 尝试查找主进程的端口
+        if let Some(port) = find_port_for_pid(pid) {
+            return Ok(Some(port));
+        }
+
+        // 3. 如果主进程没找到，递归查找所有后代进程
+        // 使用 wmic 查找所有进程的 ParentProcessId，构建进程树
+        let wmic_output = Command::new("wmic")
+            .args(&["process", "get", "ProcessId,ParentProcessId"])
+            .output();
+            
+        if let Ok(output) = wmic_output {
+            let output_str = String::from_utf8_lossy(&output.stdout);
+            let mut parent_map: std::collections::HashMap<u32, Vec<u32>> = std::collections::HashMap::new();
+            
+            // 解析 wmic 输出，构建父子关系映射
+            // Format: ParentProcessId  ProcessId
+            for line in output_str.lines().skip(1) { // Skip header
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 2 {
+                    if let (Ok(parent), Ok(child)) = (parts[0].parse::<u32>(), parts[1].parse::<u32>()) {
+                        parent_map.entry(parent).or_insert_with(Vec::new).push(child);
+                    }
+                }
+            }
+            
+            // 广度优先搜索 (BFS) 查找所有后代
+            let mut queue = std::collections::VecDeque::new();
+            queue.push_back(pid);
+            
+            // 防止死循环（虽然进程树不应该有环）
+            let mut visited = std::collections::HashSet::new();
+            visited.insert(pid);
+
+            while let Some(current_pid) = queue.pop_front() {
+                // 检查当前进程的端口 (跳过主进程，因为已经查过了，但也无妨)
+                if current_pid != pid {
+                    if let Some(port) = find_port_for_pid(current_pid) {
+                        return Ok(Some(port));
+                    }
+                }
+
+                // 将子进程加入队列
+                if let Some(children) = parent_map.get(&current_pid) {
+                    for &child in children {
+                        if visited.insert(child) {
+                            queue.push_back(child);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    Ok(None)
+}
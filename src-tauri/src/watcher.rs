@@ -0,0 +1,198 @@
+// 文件监视：开发时服务的源文件变化后自动重启服务或发送信号
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use sysinfo::Signal;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::config::{Config, Service, WatchAction, WatchConfig};
+use crate::orchestrator::{Operation, Orchestrator};
+use crate::process::{self, ProcessManager};
+
+// 一个服务的监视：notify 的监视器和处理变化的任务
+struct ServiceWatch {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl Drop for ServiceWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+pub struct FileWatcher {
+    pm: Arc<ProcessManager>,
+    runtime: Handle,
+    watches: Mutex<HashMap<String, ServiceWatch>>,
+}
+
+impl FileWatcher {
+    pub fn new(pm: Arc<ProcessManager>, runtime: Handle) -> Self {
+        Self {
+            pm,
+            runtime,
+            watches: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 按配置重新监视所有设置了 watch 的服务
+    pub fn sync(&self, orchestrator: &Orchestrator, config: &Config) {
+        self.clear();
+
+        let config = Arc::new(config.clone());
+        let mut watches = self.watches.lock().unwrap();
+        for service in config.groups.iter().flat_map(|g| g.services.iter()) {
+            let Some(settings) = &service.watch else {
+                continue;
+            };
+            match self.watch(orchestrator, &config, service, settings) {
+                Ok(watch) => {
+                    watches.insert(service.id.clone(), watch);
+                }
+                Err(e) => log::error!("Failed to watch files of {}: {}", service.id, e),
+            }
+        }
+    }
+
+    /// 停止所有监视
+    pub fn clear(&self) {
+        self.watches.lock().unwrap().clear();
+    }
+
+    fn watch(&self, orchestrator: &Orchestrator, config: &Arc<Config>, service: &Service, settings: &WatchConfig) -> Result<ServiceWatch, String> {
+        let roots = watch_roots(service, settings);
+        if roots.is_empty() {
+            return Err("no watch paths".to_string());
+        }
+        let include = build_globs(&settings.include)?;
+        let exclude = build_globs(&settings.exclude)?;
+
+        // notify 在自己的线程中回调，只把匹配的变化转发给处理任务
+        let (sender, changes) = mpsc::unbounded_channel();
+        let filter_roots = roots.clone();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Access(_) | EventKind::Other) {
+                return;
+            }
+            for path in event.paths {
+                let Some(relative) = filter_roots.iter().find_map(|root| path.strip_prefix(root).ok()) else {
+                    continue;
+                };
+                let included = match &include {
+                    Some(globs) => globs.is_match(relative),
+                    None => true,
+                };
+                let excluded = exclude.as_ref().is_some_and(|globs| globs.is_match(relative));
+                if included && !excluded {
+                    let _ = sender.send(path.clone());
+                }
+            }
+        }).map_err(|e| e.to_string())?;
+        for root in &roots {
+            watcher.watch(root, RecursiveMode::Recursive)
+                .map_err(|e| format!("{}: {}", root.display(), e))?;
+        }
+
+        log::info!("Watching {} for {}", roots.iter().map(|r| r.display().to_string()).collect::<Vec<_>>().join(", "), service.id);
+        let task = self.runtime.spawn(handle_changes(
+            orchestrator.clone(),
+            self.pm.clone(),
+            config.clone(),
+            service.id.clone(),
+            settings.clone(),
+            changes,
+        ));
+        Ok(ServiceWatch { _watcher: watcher, task })
+    }
+}
+
+// 合并一段时间内的变化，然后重启服务或发送信号；重启期间的变化合并到下一次重启
+async fn handle_changes(
+    orchestrator: Orchestrator,
+    pm: Arc<ProcessManager>,
+    config: Arc<Config>,
+    service_id: String,
+    settings: WatchConfig,
+    mut changes: mpsc::UnboundedReceiver<PathBuf>,
+) {
+    let debounce = Duration::from_millis(settings.debounce);
+    while let Some(first) = changes.recv().await {
+        let mut count = 1;
+        loop {
+            match tokio::time::timeout(debounce, changes.recv()).await {
+                Ok(Some(_)) => count += 1,
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+        // 服务没有运行时不做处理，避免修改文件时意外启动服务
+        if !pm.is_running(&service_id) {
+            continue;
+        }
+        log::info!("{} change(s) detected for {} (first: {})", count, service_id, first.display());
+
+        match settings.action {
+            WatchAction::Restart => {
+                let operation = if settings.cascade {
+                    Operation::RestartServiceCascade(service_id.clone())
+                } else {
+                    Operation::RestartService(service_id.clone())
+                };
+                match orchestrator.run((*config).clone(), operation).await {
+                    Ok(job) => log::info!("Restarted {} after file changes: {:?}", service_id, job.state),
+                    Err(e) => log::error!("Failed to restart {} after file changes: {}", service_id, e),
+                }
+            }
+            WatchAction::Signal => {
+                let signal = settings.signal.as_deref()
+                    .and_then(process::parse_signal)
+                    .unwrap_or(Signal::Hangup);
+                let pm = pm.clone();
+                let id = service_id.clone();
+                match tokio::task::spawn_blocking(move || pm.signal(&id, signal)).await {
+                    Ok(Ok(())) => log::info!("Sent {:?} to {} after file changes", signal, service_id),
+                    Ok(Err(e)) => log::error!("Failed to signal {}: {}", service_id, e),
+                    Err(e) => log::error!("Failed to signal {}: {}", service_id, e),
+                }
+            }
+        }
+    }
+}
+
+// 监视的目录：相对路径相对于服务的工作目录，未设置时监视工作目录
+fn watch_roots(service: &Service, settings: &WatchConfig) -> Vec<PathBuf> {
+    let work_dir = service.work_dir.as_deref()
+        .map(str::trim)
+        .filter(|wd| !wd.is_empty())
+        .map(PathBuf::from);
+    if settings.paths.is_empty() {
+        return work_dir.into_iter().collect();
+    }
+    settings.paths.iter()
+        .map(|path| match &work_dir {
+            Some(work_dir) if Path::new(path).is_relative() => work_dir.join(path),
+            _ => PathBuf::from(path),
+        })
+        .collect()
+}
+
+fn build_globs(patterns: &[String]) -> Result<Option<GlobSet>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).map_err(|e| e.to_string())?);
+    }
+    builder.build().map(Some).map_err(|e| e.to_string())
+}
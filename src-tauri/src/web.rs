@@ -1,64 +1,1005 @@
 use axum::{
     Router,
-    routing::get,
-    response::Html,
+    Json,
+    routing::{delete, get, post, put},
+    response::{IntoResponse, Response},
+    extract::{Path, Query, State},
     extract::ws::{WebSocket, WebSocketUpgrade, Message},
+    http::{header, HeaderMap, Request, StatusCode, Uri},
+    middleware::{self, Next},
+    Extension,
 };
-use std::net::SocketAddr;
+use axum_server::tls_rustls::{self, RustlsConfig};
+use axum_server::Handle;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+use crate::assets;
+use crate::auth::{self, Principal};
+use crate::config::{AuthSettings, BasicAuthConfig, Config, ConfigManager, Service, Settings, TlsSettings, TokenScope};
+use crate::healthcheck::{CheckResult, HealthChecker};
+use crate::orchestrator::{ApplicationStatus, InstanceStatus, Job, JobState, Operation, Orchestrator, OrchestratorError};
+use crate::process::ProcessManager;
+use crate::scheduler;
+use crate::supervisor;
+use crate::system;
+
+/// 推送给 WebSocket 客户端的事件
+#[derive(Debug, Clone, Serialize)]
+pub struct WebEvent {
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+/// 通过 Web 接口修改监听配置后的回调，由持有 WebServer 的一方重启监听器
+pub type ListenCallback = Arc<dyn Fn(ListenConfig) + Send + Sync + 'static>;
+
+/// Web 服务器与桌面端共享的运行时状态
+#[derive(Clone)]
+pub struct WebState {
+    pub config: Arc<Mutex<Option<ConfigManager>>>,
+    pub process_manager: Arc<ProcessManager>,
+    pub orchestrator: Arc<Orchestrator>,
+    pub health_checker: Arc<HealthChecker>,
+    pub events: broadcast::Sender<WebEvent>,
+    pub tickets: Arc<auth::Tickets>,
+    pub on_listen_change: ListenCallback,
+}
+
+/// 广播事件，没有客户端连接时直接丢弃
+pub fn publish<T: Serialize + ?Sized>(events: &broadcast::Sender<WebEvent>, event: impl Into<String>, payload: &T) {
+    if let Ok(payload) = serde_json::to_value(payload) {
+        let _ = events.send(WebEvent { event: event.into(), payload });
+    }
+}
+
+impl WebState {
+    fn config_snapshot(&self) -> Option<Config> {
+        let config = self.config.lock().unwrap();
+        config.as_ref().map(|manager| manager.get().clone())
+    }
+}
+
+// 只读配置中替换环境变量值的占位符
+const REDACTED: &str = "******";
+
+// 路由级别的权限要求
+#[derive(Clone)]
+struct ScopeGuard {
+    state: WebState,
+    scope: TokenScope,
+}
+
+// Web 服务器监听配置，来自 Settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListenConfig {
+    pub bind_address: String,
+    pub port: u16,
+    pub tls: Option<TlsSettings>,
+}
+
+impl From<&Settings> for ListenConfig {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            bind_address: settings.bind_address.clone(),
+            port: settings.server_port,
+            tls: settings.tls.clone(),
+        }
+    }
+}
+
+impl ListenConfig {
+    /// 检查监听地址和 TLS 证书文件，保存配置前调用，避免把无法监听的设置写入配置文件
+    pub fn validate(&self) -> Result<(), WebServerError> {
+        self.bind_address.trim().parse::<IpAddr>()
+            .map_err(|_| WebServerError::InvalidAddress(self.bind_address.clone()))?;
+        if let Some(tls) = &self.tls {
+            for path in [&tls.cert_path, &tls.key_path] {
+                std::fs::File::open(path).map_err(|e| WebServerError::TlsFile(path.clone(), e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum WebServerError {
+    #[error("Invalid bind address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Failed to bind {0}: {1}")]
+    BindError(SocketAddr, std::io::Error),
+
+    #[error("Failed to load TLS certificate: {0}")]
+    TlsError(std::io::Error),
+
+    #[error("Cannot read TLS file {0}: {1}")]
+    TlsFile(String, std::io::Error),
+}
+
+/// Web 服务器运行状态，供前端展示
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebServerStatus {
+    pub running: bool,
+    pub address: Option<String>,
+    pub tls: bool,
+    pub error: Option<String>,
+}
+
+// 正在运行的监听器
+struct RunningServer {
+    handle: Handle,
+    task: tokio::task::JoinHandle<()>,
+}
 
 pub struct WebServer {
-    port: u16,
+    state: WebState,
+    running: tokio::sync::Mutex<Option<RunningServer>>,
+    status: Arc<Mutex<WebServerStatus>>,
 }
 
 impl WebServer {
-    pub fn new(port: u16) -> Self {
-        Self { port }
-    }
-    
-    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let addr = SocketAddr::from(([0, 0, 0, 0], self.port));
-        
-        // 创建HTTP路由
-        let app = Router::new()
-            .route("/", get(index_handler))
-            .route("/ws", get(ws_handler));
-        
-        // 启动服务器
-        println!("Web server running on http://localhost:{}", self.port);
-        axum::Server::bind(&addr)
-            .serve(app.into_make_service())
-            .await?;
-        
+    pub fn new(state: WebState) -> Self {
+        Self {
+            state,
+            running: tokio::sync::Mutex::new(None),
+            status: Arc::new(Mutex::new(WebServerStatus::default())),
+        }
+    }
+
+    pub fn status(&self) -> WebServerStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// 按配置启动监听，已有监听器会先停止
+    pub async fn start(&self, listen: &ListenConfig) -> Result<SocketAddr, WebServerError> {
+        let mut running = self.running.lock().await;
+        if let Some(server) = running.take() {
+            // 等待旧监听器释放端口后再重新绑定
+            server.handle.graceful_shutdown(Some(Duration::from_secs(5)));
+            let _ = server.task.await;
+        }
+
+        match self.bind(listen).await {
+            Ok((addr, server)) => {
+                *running = Some(server);
+                *self.status.lock().unwrap() = WebServerStatus {
+                    running: true,
+                    address: Some(addr.to_string()),
+                    tls: listen.tls.is_some(),
+                    error: None,
+                };
+                let scheme = if listen.tls.is_some() { "https" } else { "http" };
+                log::info!("Web server running on {}://{}", scheme, addr);
+                Ok(addr)
+            }
+            Err(e) => {
+                *self.status.lock().unwrap() = WebServerStatus {
+                    running: false,
+                    address: None,
+                    tls: listen.tls.is_some(),
+                    error: Some(e.to_string()),
+                };
+                Err(e)
+            }
+        }
+    }
+
+    /// 停止监听
+    pub async fn stop(&self) {
+        if let Some(server) = self.running.lock().await.take() {
+            server.handle.graceful_shutdown(Some(Duration::from_secs(5)));
+            let _ = server.task.await;
+        }
+        self.status.lock().unwrap().running = false;
+    }
+
+    async fn bind(&self, listen: &ListenConfig) -> Result<(SocketAddr, RunningServer), WebServerError> {
+        let ip: IpAddr = listen.bind_address.trim().parse()
+            .map_err(|_| WebServerError::InvalidAddress(listen.bind_address.clone()))?;
+        let addr = SocketAddr::new(ip, listen.port);
+
+        // 先同步绑定端口，以便端口被占用时立即返回错误
+        let listener = std::net::TcpListener::bind(addr)
+            .map_err(|e| WebServerError::BindError(addr, e))?;
+        listener.set_nonblocking(true)
+            .map_err(|e| WebServerError::BindError(addr, e))?;
+
+        let handle = Handle::new();
+        let app = self.router().into_make_service();
+        let task = match &listen.tls {
+            Some(tls) => {
+                let rustls = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+                    .await
+                    .map_err(WebServerError::TlsError)?;
+                let server = tls_rustls::from_tcp_rustls(listener, rustls).handle(handle.clone());
+                self.spawn_server(server.serve(app))
+            }
+            None => {
+                let server = axum_server::from_tcp(listener).handle(handle.clone());
+                self.spawn_server(server.serve(app))
+            }
+        };
+
+        Ok((addr, RunningServer { handle, task }))
+    }
+
+    fn spawn_server<F>(&self, serve: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Future<Output = std::io::Result<()>> + Send + 'static,
+    {
+        let status = self.status.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve.await {
+                log::error!("Web server stopped with error: {}", e);
+                let mut status = status.lock().unwrap();
+                status.running = false;
+                status.error = Some(e.to_string());
+            }
+        })
+    }
+
+    fn router(&self) -> Router {
+        let guard = |scope: TokenScope| {
+            middleware::from_fn_with_state(
+                ScopeGuard { state: self.state.clone(), scope },
+                require_scope,
+            )
+        };
+
+        let read_routes = Router::new()
+            .route("/ws", get(ws_handler))
+            .route("/api/auth/whoami", get(whoami_handler))
+            .route("/api/auth/ws-ticket", post(ws_ticket_handler))
+            .route("/api/config/redacted", get(get_redacted_config_handler))
+            .route("/api/config/validate", post(validate_config_handler))
+            .route("/api/services", get(list_services_handler))
+            .route("/api/services/:id", get(get_service_handler))
+            .route("/api/services/:id/stats", get(service_stats_handler))
+            .route("/api/services/:id/logs", get(service_logs_handler))
+            .route("/api/services/:id/health", get(service_health_handler))
+            .route("/api/services/:id/runs", get(service_runs_handler))
+            .route("/api/services/:id/events", get(service_events_handler))
+            .route("/api/services/:id/restart-plan", get(restart_plan_handler))
+            .route("/api/plan/start", get(start_plan_handler))
+            .route("/api/processes/:pid/port", get(pid_port_handler))
+            .route("/api/server/health", get(server_health_handler))
+            .route("/api/auto-start", get(auto_start_handler))
+            .route("/api/jobs", get(list_jobs_handler))
+            .route("/api/jobs/:id", get(get_job_handler))
+            .route("/api/applications", get(list_application_status_handler))
+            .route("/api/applications/:id/status", get(application_status_handler))
+            .route_layer(guard(TokenScope::Read));
+
+        let operator_routes = Router::new()
+            .route("/api/start-all", post(start_all_handler))
+            .route("/api/stop-all", post(stop_all_handler))
+            .route("/api/auto-start/cancel", post(cancel_auto_start_handler))
+            .route("/api/jobs/:id/cancel", post(cancel_job_handler))
+            .route("/api/jobs/:id/resume", post(resume_job_handler))
+            .route("/api/services/:id/start", post(start_service_handler))
+            .route("/api/services/:id/stop", post(stop_service_handler))
+            .route("/api/services/:id/restart", post(restart_service_handler))
+            .route("/api/services/:id/scale", post(scale_service_handler))
+            .route("/api/services/:id/health/restart", post(restart_health_check_handler))
+            .route("/api/groups/:id/start", post(start_group_handler))
+            .route("/api/groups/:id/stop", post(stop_group_handler))
+            .route("/api/groups/:id/rolling-restart", post(rolling_restart_group_handler))
+            .route("/api/applications/:id/start", post(start_application_handler))
+            .route("/api/applications/:id/stop", post(stop_application_handler))
+            .route("/api/applications/:id/restart", post(restart_application_handler))
+            .route_layer(guard(TokenScope::Operator));
+
+        let admin_routes = Router::new()
+            .route("/api/config", get(get_config_handler).put(put_config_handler))
+            .route("/api/auth/settings", get(get_auth_settings_handler))
+            .route("/api/auth/tokens", post(create_token_handler))
+            .route("/api/auth/tokens/:id", delete(revoke_token_handler))
+            .route("/api/auth/basic", put(set_basic_auth_handler))
+            .route_layer(guard(TokenScope::Admin));
+
+        // 前端静态资源不含任何运行数据，无需认证即可加载登录页
+        Router::new()
+            .route("/healthz", get(healthz_handler))
+            .merge(read_routes)
+            .merge(operator_routes)
+            .merge(admin_routes)
+            .fallback(static_handler)
+            .with_state(self.state.clone())
+    }
+}
+
+// 认证中间件：校验令牌并检查权限范围
+async fn require_scope<B>(
+    State(guard): State<ScopeGuard>,
+    mut req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let auth = guard.state.config_snapshot()
+        .and_then(|config| config.settings.auth)
+        .unwrap_or_default();
+
+    let query = Query::<HashMap<String, String>>::try_from_uri(req.uri())
+        .map(|Query(query)| query)
+        .unwrap_or_default();
+
+    match auth::authenticate(&auth, &guard.state.tickets, req.headers(), query.get("token").map(String::as_str)) {
+        Some(principal) if principal.scope >= guard.scope => {
+            req.extensions_mut().insert(principal);
+            next.run(req).await
+        }
+        Some(principal) => error_response(
+            StatusCode::FORBIDDEN,
+            format!("Token '{}' does not have {:?} scope", principal.name, guard.scope),
+        ),
+        None => {
+            let mut response = error_response(StatusCode::UNAUTHORIZED, "Authentication required");
+            // 配置了 Basic 认证时让浏览器弹出登录框，前端页面发起的请求由登录页处理
+            let from_web_ui = req.headers().contains_key("x-requested-with");
+            if auth.basic_auth.is_some() && !from_web_ui {
+                response.headers_mut().insert(
+                    header::WWW_AUTHENTICATE,
+                    header::HeaderValue::from_static("Basic realm=\"ServiceFlow\""),
+                );
+            }
+            response
+        }
+    }
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({ "error": message.into() }))).into_response()
+}
+
+// 提交编排操作，立即返回任务 ID，进度通过 job:progress 事件推送
+fn submit_job(state: &WebState, operation: Operation) -> Response {
+    let Some(config) = state.config_snapshot() else {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "Config not loaded");
+    };
+    match state.orchestrator.submit(config, operation) {
+        Ok(job_id) => (StatusCode::ACCEPTED, Json(serde_json::json!({ "jobId": job_id }))).into_response(),
+        Err(e @ (OrchestratorError::TaskNotFound(_) | OrchestratorError::GroupNotFound(_) | OrchestratorError::ApplicationNotFound(_))) => {
+            error_response(StatusCode::NOT_FOUND, e.to_string())
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServiceStatus {
+    id: String,
+    name: String,
+    group_id: String,
+    running: bool,
+    pid: Option<u32>,
+    health: Option<CheckResult>,
+    // 多实例服务的期望实例数和各实例状态
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replicas: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    instances: Vec<InstanceStatus>,
+    // 命名端口（包括自动分配的端口）
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    ports: BTreeMap<String, u16>,
+    // 定时服务的下一次运行时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_run: Option<String>,
+    // 进程退出后的退出码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+}
+
+impl ServiceStatus {
+    fn new(state: &WebState, config: &Config, service: &Service) -> Self {
+        Self {
+            id: service.id.clone(),
+            name: service.name.clone(),
+            group_id: service.group_id.clone(),
+            running: state.process_manager.is_running(&service.id),
+            pid: state.process_manager.get_pid(&service.id),
+            health: state.health_checker.latest_result(&service.id),
+            replicas: service.replicas,
+            instances: state.orchestrator.instance_status(config, &service.id, &state.health_checker)
+                .unwrap_or_default(),
+            ports: state.orchestrator.ports().ports_of(service),
+            next_run: state.orchestrator.scheduler().next_run(service),
+            exit_code: state.process_manager.exit_code(&service.id),
+        }
+    }
+}
+
+fn find_service<'a>(config: &'a Config, id: &str) -> Option<&'a Service> {
+    config.groups.iter()
+        .flat_map(|group| group.services.iter())
+        .find(|service| service.id == id)
+}
+
+async fn healthz_handler(State(state): State<WebState>) -> Response {
+    let public = state.config_snapshot()
+        .and_then(|config| config.settings.auth)
+        .map(|auth| auth.public_healthz)
+        .unwrap_or(false);
+
+    if public {
+        (StatusCode::OK, "ok").into_response()
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
+}
+
+// 前端静态资源，未匹配的页面路径回退到 index.html 交给前端路由
+async fn static_handler(uri: Uri, headers: HeaderMap) -> Response {
+    let path = uri.path().trim_start_matches('/');
+    let path = if path.is_empty() { "index.html" } else { path };
+
+    if let Some(asset) = assets::get(path) {
+        return asset_response(asset, &headers);
+    }
+
+    // API 路径和带扩展名的资源不回退，避免把 HTML 当作脚本返回
+    let is_file = path.rsplit('/').next().is_some_and(|name| name.contains('.'));
+    if path.starts_with("api/") || is_file {
+        return error_response(StatusCode::NOT_FOUND, format!("Not found: /{}", path));
+    }
+
+    match assets::get("index.html") {
+        Some(asset) => asset_response(asset, &headers),
+        None => error_response(StatusCode::NOT_FOUND, "Web UI is not bundled in this build"),
+    }
+}
+
+fn asset_response(asset: &assets::Asset, headers: &HeaderMap) -> Response {
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value == asset.etag);
+
+    let response_headers = [
+        (header::CONTENT_TYPE, assets::content_type(asset.path)),
+        (header::CACHE_CONTROL, assets::cache_control(asset.path)),
+        (header::ETAG, asset.etag),
+    ];
+
+    if not_modified {
+        (StatusCode::NOT_MODIFIED, response_headers).into_response()
+    } else {
+        (response_headers, asset.data).into_response()
+    }
+}
+
+async fn whoami_handler(Extension(principal): Extension<Principal>) -> Json<Principal> {
+    Json(principal)
+}
+
+// 签发 WebSocket 连接票据，供使用账号密码登录的网页连接 /ws
+async fn ws_ticket_handler(State(state): State<WebState>, Extension(principal): Extension<Principal>) -> Response {
+    Json(serde_json::json!({ "ticket": state.tickets.issue(&principal) })).into_response()
+}
+
+async fn get_config_handler(State(state): State<WebState>) -> Response {
+    match state.config_snapshot() {
+        Some(mut config) => {
+            // 不向客户端暴露令牌哈希
+            config.settings.auth = None;
+            Json(config).into_response()
+        }
+        None => error_response(StatusCode::SERVICE_UNAVAILABLE, "Config not loaded"),
+    }
+}
+
+// 只读权限可见的配置：隐藏环境变量的值、启动参数和钩子命令，这些内容常包含密钥
+async fn get_redacted_config_handler(State(state): State<WebState>) -> Response {
+    let Some(mut config) = state.config_snapshot() else {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "Config not loaded");
+    };
+    config.settings.auth = None;
+    for service in config.groups.iter_mut().flat_map(|g| g.services.iter_mut()) {
+        if let Some(env) = &mut service.env {
+            for value in env.values_mut() {
+                *value = REDACTED.to_string();
+            }
+        }
+        service.args = None;
+        service.hooks = None;
+    }
+    Json(config).into_response()
+}
+
+#[derive(Serialize)]
+struct ValidationResult {
+    valid: bool,
+    errors: Vec<String>,
+}
+
+// 校验请求体中的配置，不会保存
+async fn validate_config_handler(Json(value): Json<serde_json::Value>) -> Json<ValidationResult> {
+    let errors = match serde_json::from_value::<Config>(value) {
+        Ok(config) => config.validate(),
+        Err(e) => vec![format!("Failed to parse config: {}", e)],
+    };
+    Json(ValidationResult { valid: errors.is_empty(), errors })
+}
+
+async fn put_config_handler(State(state): State<WebState>, Json(mut cfg): Json<Config>) -> Response {
+    let mut config = state.config.lock().unwrap();
+    let Some(manager) = config.as_mut() else {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "Config not loaded");
+    };
+    // GET 返回的配置中不含认证设置，令牌和 Basic 认证只通过专门的命令修改，保存时保留原有设置
+    cfg.settings.auth = manager.get().settings.auth.clone();
+    let errors = cfg.validate();
+    if !errors.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, format!("Invalid config: {}", errors.join("; ")));
+    }
+    let old_listen = ListenConfig::from(&manager.get().settings);
+    let new_listen = ListenConfig::from(&cfg.settings);
+    if let Err(e) = new_listen.validate() {
+        return error_response(StatusCode::BAD_REQUEST, e.to_string());
+    }
+    manager.set(cfg);
+    if let Err(e) = manager.save() {
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    }
+    state.orchestrator.reload(manager.get());
+    drop(config);
+
+    // 监听地址、端口或 TLS 变化时重启监听器，当前请求的响应在旧监听器关闭前发出
+    if old_listen != new_listen {
+        (state.on_listen_change)(new_listen);
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+// 修改认证设置并保存；认证设置不影响编排，无需重新加载
+fn update_auth<R>(
+    state: &WebState,
+    update: impl FnOnce(&mut AuthSettings) -> Result<R, (StatusCode, String)>,
+) -> Result<R, (StatusCode, String)> {
+    let mut config = state.config.lock().unwrap();
+    let Some(manager) = config.as_mut() else {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "Config not loaded".to_string()));
+    };
+    let auth = manager.get_mut().settings.auth.get_or_insert_with(AuthSettings::default);
+    let result = update(auth)?;
+    manager.save().map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(result)
+}
+
+async fn get_auth_settings_handler(State(state): State<WebState>) -> Response {
+    match state.config_snapshot() {
+        Some(config) => Json(config.settings.auth.as_ref().map(auth::redacted).unwrap_or_default()).into_response(),
+        None => error_response(StatusCode::SERVICE_UNAVAILABLE, "Config not loaded"),
+    }
+}
+
+#[derive(Deserialize)]
+struct CreateTokenRequest {
+    name: String,
+    scope: TokenScope,
+}
+
+// 创建 API 令牌，明文只在响应中出现这一次
+async fn create_token_handler(State(state): State<WebState>, Json(request): Json<CreateTokenRequest>) -> Response {
+    let (token, record) = auth::issue_token(request.name, request.scope);
+    match update_auth(&state, |auth| {
+        auth.tokens.push(record);
         Ok(())
+    }) {
+        Ok(()) => (StatusCode::CREATED, Json(serde_json::json!({ "token": token }))).into_response(),
+        Err((status, message)) => error_response(status, message),
     }
 }
 
-async fn index_handler() -> Html<&'static str> {
-    Html("<html><body><h1>ServiceFlow Web Interface</h1></body></html>")
+async fn revoke_token_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    let result = update_auth(&state, |auth| {
+        let before = auth.tokens.len();
+        auth.tokens.retain(|t| t.id != id);
+        if auth.tokens.len() == before {
+            return Err((StatusCode::NOT_FOUND, format!("Token not found: {}", id)));
+        }
+        Ok(())
+    });
+    match result {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err((status, message)) => error_response(status, message),
+    }
+}
+
+#[derive(Deserialize)]
+struct BasicAuthRequest {
+    username: String,
+    password: Option<String>,
+    scope: Option<TokenScope>,
 }
 
-async fn ws_handler(ws: WebSocketUpgrade) -> axum::response::Response {
-    ws.on_upgrade(handle_websocket)
+// 设置 Web 界面的 Basic 认证，密码为空时关闭
+async fn set_basic_auth_handler(State(state): State<WebState>, Json(request): Json<BasicAuthRequest>) -> Response {
+    // Argon2 哈希较慢，在持有配置锁之前计算
+    let password_hash = match request.password.filter(|p| !p.is_empty()) {
+        Some(password) => match auth::hash_password(&password) {
+            Ok(hash) => Some(hash),
+            Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        },
+        None => None,
+    };
+    let result = update_auth(&state, |auth| {
+        auth.basic_auth = password_hash.map(|password_hash| BasicAuthConfig {
+            username: request.username,
+            password_hash,
+            scope: request.scope.unwrap_or(TokenScope::Operator),
+        });
+        Ok(())
+    });
+    match result {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err((status, message)) => error_response(status, message),
+    }
 }
 
-async fn handle_websocket(mut socket: WebSocket) {
-    while let Some(msg) = socket.recv().await {
-        if let Ok(msg) = msg {
-            match msg {
-                Message::Text(text) => {
-                    println!("Received: {}", text);
-                    // Echo back
+async fn list_services_handler(State(state): State<WebState>) -> Response {
+    let Some(config) = state.config_snapshot() else {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "Config not loaded");
+    };
+
+    let services: Vec<ServiceStatus> = config.groups.iter()
+        .flat_map(|group| group.services.iter())
+        .map(|service| ServiceStatus::new(&state, &config, service))
+        .collect();
+
+    Json(services).into_response()
+}
+
+async fn get_service_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    let config = state.config_snapshot().unwrap_or_default();
+    match find_service(&config, &id) {
+        Some(service) => Json(ServiceStatus::new(&state, &config, service)).into_response(),
+        None => error_response(StatusCode::NOT_FOUND, format!("Service not found: {}", id)),
+    }
+}
+
+async fn service_stats_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    Json(state.process_manager.get_process_stats(&id)).into_response()
+}
+
+async fn service_health_handler(State(state): State<WebState>, Path(id): Path<String>) -> Json<Option<CheckResult>> {
+    Json(state.health_checker.latest_result(&id))
+}
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    lines: Option<usize>,
+}
+
+async fn service_logs_handler(
+    State(state): State<WebState>,
+    Path(id): Path<String>,
+    Query(query): Query<LogsQuery>,
+) -> Json<Vec<String>> {
+    Json(state.process_manager.recent_logs(&id, query.lines.unwrap_or(100)))
+}
+
+#[derive(Deserialize)]
+struct RunsQuery {
+    limit: Option<i64>,
+}
+
+async fn service_runs_handler(Path(id): Path<String>, Query(query): Query<RunsQuery>) -> Response {
+    match scheduler::run_history(&id, query.limit.unwrap_or(scheduler::DEFAULT_RUN_HISTORY)).await {
+        Ok(runs) => Json(runs).into_response(),
+        Err(e) => error_response(StatusCode::SERVICE_UNAVAILABLE, e),
+    }
+}
+
+async fn service_events_handler(Path(id): Path<String>, Query(query): Query<RunsQuery>) -> Response {
+    match supervisor::event_history(&id, query.limit.unwrap_or(supervisor::DEFAULT_EVENT_HISTORY)).await {
+        Ok(events) => Json(events).into_response(),
+        Err(e) => error_response(StatusCode::SERVICE_UNAVAILABLE, e),
+    }
+}
+
+async fn pid_port_handler(Path(pid): Path<u32>) -> Response {
+    match tokio::task::spawn_blocking(move || system::pid_port(pid)).await {
+        Ok(Ok(port)) => Json(port).into_response(),
+        Ok(Err(e)) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn server_health_handler() -> Response {
+    match tokio::task::spawn_blocking(system::server_health).await {
+        Ok(Ok(health)) => Json(health).into_response(),
+        Ok(Err(e)) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn start_all_handler(State(state): State<WebState>) -> Response {
+    submit_job(&state, Operation::StartAll)
+}
+
+async fn stop_all_handler(State(state): State<WebState>) -> Response {
+    submit_job(&state, Operation::StopAll)
+}
+
+async fn auto_start_handler(State(state): State<WebState>) -> Json<Option<Job>> {
+    Json(state.orchestrator.latest_job(&Operation::AutoStart))
+}
+
+async fn cancel_auto_start_handler(State(state): State<WebState>) -> Response {
+    let cancelled = state.orchestrator.latest_job(&Operation::AutoStart)
+        .map(|job| state.orchestrator.cancel(&job.id))
+        .unwrap_or(false);
+    if cancelled {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        error_response(StatusCode::CONFLICT, "Auto-start is not running")
+    }
+}
+
+async fn list_jobs_handler(State(state): State<WebState>) -> Json<Vec<Job>> {
+    Json(state.orchestrator.jobs())
+}
+
+async fn get_job_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    match state.orchestrator.job(&id) {
+        Some(job) => Json(job).into_response(),
+        None => error_response(StatusCode::NOT_FOUND, format!("Job not found: {}", id)),
+    }
+}
+
+async fn cancel_job_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    if state.orchestrator.cancel(&id) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        error_response(StatusCode::CONFLICT, format!("Job is not running: {}", id))
+    }
+}
+
+async fn resume_job_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    if state.orchestrator.resume(&id) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        error_response(StatusCode::CONFLICT, format!("Job is not paused: {}", id))
+    }
+}
+
+async fn start_service_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    submit_job(&state, Operation::StartService(id))
+}
+
+#[derive(Deserialize)]
+struct StopQuery {
+    // 同时停止依赖该服务的服务
+    #[serde(default)]
+    dependents: bool,
+}
+
+async fn stop_service_handler(
+    State(state): State<WebState>,
+    Path(id): Path<String>,
+    Query(query): Query<StopQuery>,
+) -> Response {
+    if query.dependents {
+        submit_job(&state, Operation::StopServiceAndDependents(id))
+    } else {
+        submit_job(&state, Operation::StopService(id))
+    }
+}
+
+#[derive(Deserialize)]
+struct RestartQuery {
+    // 同时重启依赖该服务的服务
+    #[serde(default)]
+    cascade: bool,
+}
+
+async fn restart_service_handler(
+    State(state): State<WebState>,
+    Path(id): Path<String>,
+    Query(query): Query<RestartQuery>,
+) -> Response {
+    if query.cascade {
+        submit_job(&state, Operation::RestartServiceCascade(id))
+    } else {
+        submit_job(&state, Operation::RestartService(id))
+    }
+}
+
+async fn restart_plan_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    let config = state.config_snapshot().unwrap_or_default();
+    match state.orchestrator.restart_plan(&config, &id) {
+        Ok(plan) => Json(plan).into_response(),
+        Err(e) => error_response(StatusCode::NOT_FOUND, e.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct PlanQuery {
+    service: Option<String>,
+    group: Option<String>,
+    application: Option<String>,
+}
+
+async fn start_plan_handler(State(state): State<WebState>, Query(query): Query<PlanQuery>) -> Response {
+    let Some(config) = state.config_snapshot() else {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "Config not loaded");
+    };
+    let operation = Operation::start_of(query.service, query.group, query.application);
+    match state.orchestrator.plan_start(&config, &operation) {
+        Ok(plan) => Json(plan).into_response(),
+        Err(e @ (OrchestratorError::TaskNotFound(_) | OrchestratorError::GroupNotFound(_) | OrchestratorError::ApplicationNotFound(_))) => {
+            error_response(StatusCode::NOT_FOUND, e.to_string())
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct ScaleRequest {
+    replicas: u32,
+}
+
+// 调整正在运行的实例，任务成功后才保存新的实例数；服务未运行时只保存实例数
+async fn scale_service_handler(
+    State(state): State<WebState>,
+    Path(id): Path<String>,
+    Json(request): Json<ScaleRequest>,
+) -> Response {
+    if request.replicas == 0 {
+        return error_response(StatusCode::BAD_REQUEST, "Replicas must be at least 1");
+    }
+    let Some(mut scaled) = state.config_snapshot() else {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "Config not loaded");
+    };
+    let Some(service) = scaled.service_mut(&id) else {
+        return error_response(StatusCode::NOT_FOUND, format!("Service not found: {}", id));
+    };
+    service.replicas = Some(request.replicas);
+    let errors = scaled.validate();
+    if !errors.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, errors.join("; "));
+    }
+
+    let operation = Operation::ScaleService { service_id: id.clone(), replicas: request.replicas };
+    match state.orchestrator.submit(scaled, operation) {
+        Ok(job_id) => {
+            tokio::spawn(save_replicas_on_success(
+                state.config.clone(), state.orchestrator.clone(), job_id.clone(), id, request.replicas));
+            (StatusCode::ACCEPTED, Json(serde_json::json!({ "jobId": job_id }))).into_response()
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+/// 调整实例数的任务成功后保存新的实例数并重新应用配置，任务失败或被取消时不修改配置
+pub async fn save_replicas_on_success(
+    config: Arc<Mutex<Option<ConfigManager>>>,
+    orchestrator: Arc<Orchestrator>,
+    job_id: String,
+    service_id: String,
+    replicas: u32,
+) {
+    let succeeded = orchestrator.wait(&job_id).await.is_some_and(|job| job.state == JobState::Succeeded);
+    if !succeeded {
+        log::warn!("Scaling {} to {} did not succeed, keeping the saved replicas", service_id, replicas);
+        return;
+    }
+    let mut config = config.lock().unwrap();
+    let Some(manager) = config.as_mut() else {
+        return;
+    };
+    let Some(service) = manager.get_mut().service_mut(&service_id) else {
+        return;
+    };
+    service.replicas = Some(replicas);
+    match manager.save() {
+        Ok(()) => orchestrator.reload(manager.get()),
+        Err(e) => log::error!("Failed to save replicas of {}: {}", service_id, e),
+    }
+}
+
+async fn restart_health_check_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    let config = state.config_snapshot().unwrap_or_default();
+    match find_service(&config, &id) {
+        Some(service) => {
+            state.health_checker.restart_checking(&state.orchestrator.resolve_service(&config, service));
+            StatusCode::NO_CONTENT.into_response()
+        }
+        None => error_response(StatusCode::NOT_FOUND, format!("Service not found: {}", id)),
+    }
+}
+
+async fn start_group_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    submit_job(&state, Operation::StartGroup(id))
+}
+
+async fn stop_group_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    submit_job(&state, Operation::StopGroup(id))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RollingRestartQuery {
+    // 每次重启的服务数
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+}
+
+fn default_batch_size() -> usize {
+    1
+}
+
+async fn rolling_restart_group_handler(
+    State(state): State<WebState>,
+    Path(id): Path<String>,
+    Query(query): Query<RollingRestartQuery>,
+) -> Response {
+    submit_job(&state, Operation::RollingRestartGroup { group_id: id, batch_size: query.batch_size })
+}
+
+async fn start_application_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    submit_job(&state, Operation::StartApplication(id))
+}
+
+async fn stop_application_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    submit_job(&state, Operation::StopApplication(id))
+}
+
+async fn restart_application_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    submit_job(&state, Operation::RestartApplication(id))
+}
+
+async fn list_application_status_handler(State(state): State<WebState>) -> Response {
+    let Some(config) = state.config_snapshot() else {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "Config not loaded");
+    };
+
+    let statuses: Vec<ApplicationStatus> = config.applications.iter()
+        .filter_map(|a| state.orchestrator.application_status(&config, &a.id, &state.health_checker).ok())
+        .collect();
+    Json(statuses).into_response()
+}
+
+async fn application_status_handler(State(state): State<WebState>, Path(id): Path<String>) -> Response {
+    let config = state.config_snapshot().unwrap_or_default();
+    match state.orchestrator.application_status(&config, &id, &state.health_checker) {
+        Ok(status) => Json(status).into_response(),
+        Err(e) => error_response(StatusCode::NOT_FOUND, e.to_string()),
+    }
+}
+
+async fn ws_handler(State(state): State<WebState>, ws: WebSocketUpgrade) -> Response {
+    let events = state.events.subscribe();
+    ws.on_upgrade(move |socket| handle_websocket(socket, events))
+}
+
+// 把后端事件（日志、健康检查等）推送给浏览器，格式与 Tauri 事件一致
+async fn handle_websocket(mut socket: WebSocket, mut events: broadcast::Receiver<WebEvent>) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else { continue };
                     if socket.send(Message::Text(text)).await.is_err() {
                         break;
                     }
                 }
-                Message::Close(_) => {
-                    break;
-                }
-                _ => {}
-            }
-        } else {
-            break;
+                // 客户端处理太慢时丢弃积压的事件
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
         }
     }
-}
\ No newline at end of file
+}
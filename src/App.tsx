@@ -1,5 +1,6 @@
 import { BrowserRouter, Routes, Route } from 'react-router-dom'
 import { NotificationToast } from "@/components/notification-toast"
+import { AutoStartProgress } from "@/components/auto-start-progress"
 import { KeyboardShortcutsProvider } from "@/components/keyboard-shortcuts-provider"
 import { SidebarProvider } from "@/components/sidebar-context"
 import DashboardPage from "./app/page"
@@ -29,6 +30,7 @@ export default function App() {
               <Route path="/settings" element={<SettingsPage />} />
               <Route path="/login" element={<LoginPage />} />
             </Routes>
+            <AutoStartProgress />
             <NotificationToast />
           </div>
         </SidebarProvider>
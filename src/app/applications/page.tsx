@@ -5,15 +5,16 @@ import { PageHeader } from "@/components/page-header"
 import { Card } from "@/components/ui/card"
 import { Button } from "@/components/ui/button"
 import { Badge } from "@/components/ui/badge"
-import { Plus, Edit, Trash2, ShoppingCart, BarChart, MoreVertical, Play, Square, Layers } from "lucide-react"
+import { Plus, Edit, Trash2, ShoppingCart, BarChart, MoreVertical, Play, Square, Layers, RotateCw } from "lucide-react"
 import { DropdownMenu, DropdownMenuContent, DropdownMenuItem, DropdownMenuTrigger } from "@/components/ui/dropdown-menu"
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip"
 import { ApplicationDialog } from "@/components/create-application-dialog"
-import { useState } from "react"
+import { useCallback, useEffect, useState } from "react"
 import { Dialog, DialogContent, DialogDescription, DialogHeader, DialogTitle } from "@/components/ui/dialog"
 import { useConfig } from "@/hooks/use-config"
 import { Loader2 } from "lucide-react"
-import type { Application } from "@/types/service"
+import type { Application, ApplicationStatus } from "@/types/service"
+import { api } from "@/lib/api"
 import {
   AlertDialog,
   AlertDialogAction,
@@ -27,6 +28,14 @@ import {
 import { Input } from "@/components/ui/input"
 import { toast } from "sonner"
 
+const stateLabels: Record<ApplicationStatus["state"], { label: string; className: string }> = {
+  up: { label: "正常", className: "text-success" },
+  degraded: { label: "降级", className: "text-warning" },
+  down: { label: "已停止", className: "text-muted-foreground" },
+}
+
+const actionLabels = { start: "启动", stop: "停止", restart: "重启" } as const
+
 const iconMap: Record<string, React.ComponentType<{ className?: string }>> = {
   ShoppingCart,
   BarChart,
@@ -46,6 +55,8 @@ export default function ApplicationsPage() {
   const [editingApp, setEditingApp] = useState<Application | null>(null)
   const [deletingApp, setDeletingApp] = useState<Application | null>(null)
   const [deleteConfirmName, setDeleteConfirmName] = useState("")
+  const [statuses, setStatuses] = useState<Record<string, ApplicationStatus>>({})
+  const [busyApp, setBusyApp] = useState<string | null>(null)
 
   const applications = config?.applications || []
   const groups = config?.groups || []
@@ -53,6 +64,39 @@ export default function ApplicationsPage() {
   const selectedAppData = applications.find((app) => app.id === selectedApp)
   const selectedAppGroups = selectedAppData ? groups.filter((g) => selectedAppData.groupIds.includes(g.id)) : []
 
+  // 应用列表变化时才重新创建刷新函数
+  const applicationIds = applications.map((app) => app.id).join(",")
+  const refreshStatuses = useCallback(async () => {
+    const ids = applicationIds ? applicationIds.split(",") : []
+    const results = await Promise.allSettled(ids.map((id) => api.getApplicationStatus(id)))
+    const next: Record<string, ApplicationStatus> = {}
+    results.forEach((result) => {
+      if (result.status === "fulfilled") next[result.value.id] = result.value
+    })
+    setStatuses(next)
+  }, [applicationIds])
+
+  useEffect(() => {
+    refreshStatuses()
+    const timer = setInterval(refreshStatuses, 5000)
+    return () => clearInterval(timer)
+  }, [refreshStatuses])
+
+  const handleAction = async (app: Application, action: keyof typeof actionLabels) => {
+    setBusyApp(app.id)
+    try {
+      if (action === "start") await api.startApplication(app.id)
+      else if (action === "stop") await api.stopApplication(app.id)
+      else await api.restartApplication(app.id)
+      toast.success(`应用「${app.name}」已${actionLabels[action]}`)
+    } catch (error) {
+      toast.error(`${actionLabels[action]}应用失败：${error instanceof Error ? error.message : String(error)}`)
+    } finally {
+      setBusyApp(null)
+      refreshStatuses()
+    }
+  }
+
   const handleSafeDelete = async (app: Application) => {
     // 只有在没有关联分组和服务时，才允许直接删除应用
     const appGroups = config?.groups.filter((g) => app.groupIds.includes(g.id)) ?? []
@@ -139,10 +183,11 @@ export default function ApplicationsPage() {
               const Icon = app.icon ? iconMap[app.icon] : null
               const appGroups = groups.filter((g) => app.groupIds.includes(g.id))
               const totalServices = appGroups.reduce((acc, g) => acc + g.services.length, 0)
-              const runningServices = appGroups.reduce(
-                (acc, g) => acc + g.services.filter((s) => s.status === "running").length,
-                0,
-              )
+              const status = statuses[app.id]
+              const runningServices =
+                status?.running.length ??
+                appGroups.reduce((acc, g) => acc + g.services.filter((s) => s.status === "running").length, 0)
+              const busy = busyApp === app.id
 
               return (
                 <Card key={app.id} className="flex flex-col">
@@ -177,6 +222,13 @@ export default function ApplicationsPage() {
                             <TooltipContent>更多操作</TooltipContent>
                           </Tooltip>
                           <DropdownMenuContent align="end">
+                            <DropdownMenuItem
+                              disabled={totalServices === 0 || busy}
+                              onClick={() => handleAction(app, "restart")}
+                            >
+                              <RotateCw className="mr-2 h-4 w-4" />
+                              重启应用
+                            </DropdownMenuItem>
                             <DropdownMenuItem onClick={() => setEditingApp(app)}>
                               <Edit className="mr-2 h-4 w-4" />
                               编辑应用
@@ -213,7 +265,8 @@ export default function ApplicationsPage() {
                       </div>
                       <div className="flex items-center justify-between text-sm">
                         <span className="text-muted-foreground">运行状态</span>
-                        <span className="font-medium text-success">
+                        <span className={`font-medium ${status ? stateLabels[status.state].className : "text-success"}`}>
+                          {status && `${stateLabels[status.state].label} · `}
                           {runningServices}/{totalServices}
                         </span>
                       </div>
@@ -227,13 +280,14 @@ export default function ApplicationsPage() {
                               size="sm" 
                               variant="outline" 
                               className="flex-1 bg-transparent"
-                              disabled={totalServices === 0}
+                              disabled={totalServices === 0 || busy}
+                              onClick={() => handleAction(app, "start")}
                             >
                               <Play className="mr-2 h-4 w-4" />
                               启动
                             </Button>
                           </TooltipTrigger>
-                          <TooltipContent>启动所有服务</TooltipContent>
+                          <TooltipContent>按依赖顺序启动所有服务（包括应用外的依赖）</TooltipContent>
                         </Tooltip>
                       </TooltipProvider>
 
@@ -244,7 +298,8 @@ export default function ApplicationsPage() {
                               size="sm" 
                               variant="outline" 
                               className="flex-1 bg-transparent"
-                              disabled={totalServices === 0}
+                              disabled={totalServices === 0 || busy}
+                              onClick={() => handleAction(app, "stop")}
                             >
                               <Square className="mr-2 h-4 w-4" />
                               停止
@@ -8,18 +8,33 @@ import { Label } from "@/components/ui/label"
 import { Button } from "@/components/ui/button"
 import { Server } from "lucide-react"
 import { useNavigate } from "react-router-dom"
+import { api } from "@/lib/api"
 
 export default function LoginPage() {
   const navigate = useNavigate()
   const [formData, setFormData] = useState({
     username: "",
     password: "",
+    token: "",
   })
+  // 无界面部署时可以先用命令行生成的 API 令牌登录，再在设置中配置账号
+  const [useToken, setUseToken] = useState(false)
 
-  const handleSubmit = (e: React.FormEvent) => {
+  const [error, setError] = useState<string | null>(null)
+
+  const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault()
-    // In a real app, this would make an API call
-    navigate("/")
+    setError(null)
+    try {
+      if (useToken) {
+        await api.loginWithToken(formData.token.trim())
+      } else {
+        await api.login(formData.username, formData.password)
+      }
+      navigate("/")
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err))
+    }
   }
 
   return (
@@ -36,38 +51,61 @@ export default function LoginPage() {
         </div>
 
         <form onSubmit={handleSubmit} className="space-y-4">
-          <div className="space-y-2">
-            <Label htmlFor="username">用户名</Label>
-            <Input
-              id="username"
-              type="text"
-              placeholder="请输入用户名"
-              value={formData.username}
-              onChange={(e) => setFormData({ ...formData, username: e.target.value })}
-              required
-            />
-          </div>
+          {useToken ? (
+            <div className="space-y-2">
+              <Label htmlFor="token">API 令牌</Label>
+              <Input
+                id="token"
+                type="password"
+                placeholder="sf_..."
+                value={formData.token}
+                onChange={(e) => setFormData({ ...formData, token: e.target.value })}
+                required
+              />
+            </div>
+          ) : (
+            <>
+              <div className="space-y-2">
+                <Label htmlFor="username">用户名</Label>
+                <Input
+                  id="username"
+                  type="text"
+                  placeholder="请输入用户名"
+                  value={formData.username}
+                  onChange={(e) => setFormData({ ...formData, username: e.target.value })}
+                  required
+                />
+              </div>
 
-          <div className="space-y-2">
-            <Label htmlFor="password">密码</Label>
-            <Input
-              id="password"
-              type="password"
-              placeholder="请输入密码"
-              value={formData.password}
-              onChange={(e) => setFormData({ ...formData, password: e.target.value })}
-              required
-            />
-          </div>
+              <div className="space-y-2">
+                <Label htmlFor="password">密码</Label>
+                <Input
+                  id="password"
+                  type="password"
+                  placeholder="请输入密码"
+                  value={formData.password}
+                  onChange={(e) => setFormData({ ...formData, password: e.target.value })}
+                  required
+                />
+              </div>
+            </>
+          )}
+
+          {error && <p className="text-sm text-destructive">{error}</p>}
 
           <Button type="submit" className="w-full">
             登录
           </Button>
+          <Button type="button" variant="link" className="w-full" onClick={() => setUseToken(!useToken)}>
+            {useToken ? "使用账号密码登录" : "使用 API 令牌登录"}
+          </Button>
         </form>
 
-        <div className="mt-6 text-center text-xs text-muted-foreground">
-          <p>默认用户名：admin</p>
-          <p>默认密码：admin</p>
+        <div className="mt-6 space-y-1 text-center text-xs text-muted-foreground">
+          <p>账号和令牌在“配置管理 → 认证安全”中设置</p>
+          <p>
+            首次使用无界面模式时，可在服务器上运行 <span className="font-mono">service-flow --headless --create-token admin</span> 生成管理员令牌
+          </p>
         </div>
       </Card>
     </div>
@@ -9,10 +9,11 @@ import { Label } from "@/components/ui/label"
 import { Switch } from "@/components/ui/switch"
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs"
 import { Download, Upload, Save } from "lucide-react"
-import { Textarea } from "@/components/ui/textarea"
 import { Badge } from "@/components/ui/badge"
 import { useState, useEffect } from "react"
 import { ExportConfigDialog } from "@/components/export-config-dialog"
+import { WebServerSettings } from "@/components/web-server-settings"
+import { WebAuthSettings } from "@/components/auth-settings"
 import { useTheme } from "next-themes"
 
 export default function SettingsPage() {
@@ -31,12 +32,6 @@ export default function SettingsPage() {
   }, [resolvedTheme])
 
   const [config, setConfig] = useState({
-    webPort: 8899,
-    webEnabled: true,
-    authEnabled: true,
-    username: "admin",
-    sessionTimeout: 3600,
-    ipWhitelist: "",
     autoBackup: true,
     backupInterval: 24,
     logLevel: "INFO",
@@ -63,133 +58,13 @@ export default function SettingsPage() {
 
             <TabsContent value="web">
               <Card className="p-6">
-                <div className="space-y-6">
-                  <div>
-                    <h3 className="mb-4 text-lg font-semibold text-foreground">Web 服务配置</h3>
-
-                    <div className="space-y-4">
-                      <div className="flex items-center justify-between">
-                        <div className="space-y-0.5">
-                          <Label htmlFor="webEnabled">启用 Web 服务</Label>
-                          <p className="text-sm text-muted-foreground">允许通过浏览器访问管理界面</p>
-                        </div>
-                        <Switch
-                          id="webEnabled"
-                          checked={config.webEnabled}
-                          onCheckedChange={(checked: boolean) =>
-                            setConfig({ ...config, webEnabled: checked })
-                          }
-                        />
-                      </div>
-
-                      <div className="grid gap-2">
-                        <Label htmlFor="port">Web 服务端口</Label>
-                        <Input
-                          id="port"
-                          type="number"
-                          value={config.webPort}
-                          onChange={(e) => setConfig({ ...config, webPort: Number.parseInt(e.target.value) })}
-                        />
-                        <p className="text-xs text-muted-foreground">修改后需要重启 Web 服务才能生效</p>
-                      </div>
-
-                      <div className="rounded-lg border border-border bg-muted/50 p-4">
-                        <p className="text-sm font-medium text-foreground">访问地址</p>
-                        <p className="mt-2 font-mono text-sm text-primary">http://localhost:{config.webPort}</p>
-                      </div>
-                    </div>
-                  </div>
-
-                  <div className="flex justify-end gap-2">
-                    <Button variant="outline">重置</Button>
-                    <Button>
-                      <Save className="mr-2 h-4 w-4" />
-                      保存配置
-                    </Button>
-                  </div>
-                </div>
+                <WebServerSettings />
               </Card>
             </TabsContent>
 
             <TabsContent value="auth">
               <Card className="p-6">
-                <div className="space-y-6">
-                  <div>
-                    <h3 className="mb-4 text-lg font-semibold text-foreground">认证与安全</h3>
-
-                    <div className="space-y-4">
-                      <div className="flex items-center justify-between">
-                        <div className="space-y-0.5">
-                          <Label htmlFor="authEnabled">启用登录认证</Label>
-                          <p className="text-sm text-muted-foreground">要求用户输入用户名和密码才能访问</p>
-                        </div>
-                        <Switch
-                          id="authEnabled"
-                          checked={config.authEnabled}
-                          onCheckedChange={(checked: boolean) =>
-                            setConfig({ ...config, authEnabled: checked })
-                          }
-                        />
-                      </div>
-
-                      {config.authEnabled && (
-                        <>
-                          <div className="grid gap-2">
-                            <Label htmlFor="username">用户名</Label>
-                            <Input
-                              id="username"
-                              value={config.username}
-                              onChange={(e) => setConfig({ ...config, username: e.target.value })}
-                            />
-                          </div>
-
-                          <div className="grid gap-2">
-                            <Label htmlFor="password">新密码</Label>
-                            <Input id="password" type="password" placeholder="留空表示不修改密码" />
-                          </div>
-
-                          <div className="grid gap-2">
-                            <Label htmlFor="confirmPassword">确认密码</Label>
-                            <Input id="confirmPassword" type="password" placeholder="再次输入新密码" />
-                          </div>
-
-                          <div className="grid gap-2">
-                            <Label htmlFor="sessionTimeout">会话超时（秒）</Label>
-                            <Input
-                              id="sessionTimeout"
-                              type="number"
-                              value={config.sessionTimeout}
-                              onChange={(e) =>
-                                setConfig({ ...config, sessionTimeout: Number.parseInt(e.target.value) })
-                              }
-                            />
-                            <p className="text-xs text-muted-foreground">超过指定时间无操作将自动退出登录</p>
-                          </div>
-                        </>
-                      )}
-
-                      <div className="grid gap-2">
-                        <Label htmlFor="ipWhitelist">IP 白名单</Label>
-                        <Textarea
-                          id="ipWhitelist"
-                          placeholder="192.168.1.100&#10;192.168.1.0/24&#10;留空表示允许所有 IP"
-                          value={config.ipWhitelist}
-                          onChange={(e) => setConfig({ ...config, ipWhitelist: e.target.value })}
-                          rows={4}
-                        />
-                        <p className="text-xs text-muted-foreground">每行一个 IP 地址或 CIDR 范围</p>
-                      </div>
-                    </div>
-                  </div>
-
-                  <div className="flex justify-end gap-2">
-                    <Button variant="outline">重置</Button>
-                    <Button>
-                      <Save className="mr-2 h-4 w-4" />
-                      保存配置
-                    </Button>
-                  </div>
-                </div>
+                <WebAuthSettings />
               </Card>
             </TabsContent>
 
@@ -0,0 +1,265 @@
+"use client"
+
+import { useCallback, useEffect, useState } from "react"
+import { Copy, KeyRound, Plus, Trash2 } from "lucide-react"
+import { Badge } from "@/components/ui/badge"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { Label } from "@/components/ui/label"
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select"
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "@/components/ui/table"
+import { toast } from "@/hooks/use-toast"
+import { api, getAuthHeader, isTauri } from "@/lib/api"
+import type { AuthSettings, TokenScope } from "@/types/service"
+
+const scopeLabels: Record<TokenScope, string> = {
+  read: "只读",
+  operator: "操作员",
+  admin: "管理员",
+}
+
+const errorMessage = (err: unknown) => (err instanceof Error ? err.message : String(err))
+
+function ScopeSelect({ value, onChange }: { value: TokenScope; onChange: (scope: TokenScope) => void }) {
+  return (
+    <Select value={value} onValueChange={(scope) => onChange(scope as TokenScope)}>
+      <SelectTrigger className="w-32">
+        <SelectValue />
+      </SelectTrigger>
+      <SelectContent>
+        {(Object.keys(scopeLabels) as TokenScope[]).map((scope) => (
+          <SelectItem key={scope} value={scope}>
+            {scopeLabels[scope]}
+          </SelectItem>
+        ))}
+      </SelectContent>
+    </Select>
+  )
+}
+
+// Web 访问认证：Basic 登录账号和 API 令牌，修改后立即写入配置文件，浏览器中需要 admin 权限
+export function WebAuthSettings() {
+  const [auth, setAuth] = useState<AuthSettings | null>(null)
+  const [loadError, setLoadError] = useState<string | null>(null)
+
+  const [username, setUsername] = useState("")
+  const [password, setPassword] = useState("")
+  const [confirmPassword, setConfirmPassword] = useState("")
+  const [basicScope, setBasicScope] = useState<TokenScope>("operator")
+
+  const [tokenName, setTokenName] = useState("")
+  const [tokenScope, setTokenScope] = useState<TokenScope>("read")
+  const [createdToken, setCreatedToken] = useState<string | null>(null)
+
+  const refresh = useCallback(async () => {
+    try {
+      const settings = await api.getAuthSettings()
+      setAuth(settings ?? { tokens: [], publicHealthz: false })
+      setUsername(settings?.basicAuth?.username ?? "")
+      setBasicScope(settings?.basicAuth?.scope ?? "operator")
+      setLoadError(null)
+    } catch (err) {
+      setLoadError(errorMessage(err))
+    }
+  }, [])
+
+  useEffect(() => {
+    refresh()
+  }, [refresh])
+
+  if (loadError) {
+    return <p className="text-sm text-destructive">无法读取认证设置：{loadError}（需要管理员权限）</p>
+  }
+  if (!auth) return null
+
+  const handleSaveBasicAuth = async () => {
+    if (!username.trim() || !password) {
+      toast({ title: "请输入用户名和密码", variant: "destructive" })
+      return
+    }
+    if (password !== confirmPassword) {
+      toast({ title: "两次输入的密码不一致", variant: "destructive" })
+      return
+    }
+    try {
+      await api.setBasicAuth(username.trim(), password, basicScope)
+      // 当前网页使用账号密码登录时，用新密码重新登录，避免后续请求被拒绝
+      if (!isTauri() && getAuthHeader()?.startsWith("Basic ")) {
+        await api.login(username.trim(), password)
+      }
+      setPassword("")
+      setConfirmPassword("")
+      toast({ title: "登录账号已保存" })
+      await refresh()
+    } catch (err) {
+      toast({ title: "保存登录账号失败", description: errorMessage(err), variant: "destructive" })
+    }
+  }
+
+  const handleDisableBasicAuth = async () => {
+    try {
+      await api.setBasicAuth(auth.basicAuth?.username ?? "", null)
+      toast({ title: "已关闭账号密码登录" })
+      await refresh()
+    } catch (err) {
+      toast({ title: "关闭账号密码登录失败", description: errorMessage(err), variant: "destructive" })
+    }
+  }
+
+  const handleCreateToken = async () => {
+    if (!tokenName.trim()) {
+      toast({ title: "请输入令牌名称", variant: "destructive" })
+      return
+    }
+    try {
+      const token = await api.createApiToken(tokenName.trim(), tokenScope)
+      setCreatedToken(token)
+      setTokenName("")
+      await refresh()
+    } catch (err) {
+      toast({ title: "创建令牌失败", description: errorMessage(err), variant: "destructive" })
+    }
+  }
+
+  const handleRevokeToken = async (tokenId: string) => {
+    try {
+      await api.revokeApiToken(tokenId)
+      toast({ title: "令牌已撤销" })
+      await refresh()
+    } catch (err) {
+      toast({ title: "撤销令牌失败", description: errorMessage(err), variant: "destructive" })
+    }
+  }
+
+  const copyToken = async () => {
+    if (!createdToken) return
+    await navigator.clipboard.writeText(createdToken)
+    toast({ title: "令牌已复制" })
+  }
+
+  return (
+    <div className="space-y-8">
+      <div>
+        <div className="mb-4 flex items-center justify-between">
+          <div>
+            <h3 className="text-lg font-semibold text-foreground">账号密码登录</h3>
+            <p className="text-sm text-muted-foreground">在浏览器登录页使用的 Web 访问账号</p>
+          </div>
+          <Badge variant={auth.basicAuth ? "default" : "secondary"}>{auth.basicAuth ? "已启用" : "未启用"}</Badge>
+        </div>
+
+        <div className="space-y-4">
+          <div className="grid gap-2">
+            <Label htmlFor="username">用户名</Label>
+            <Input id="username" value={username} onChange={(e) => setUsername(e.target.value)} />
+          </div>
+
+          <div className="grid gap-2">
+            <Label htmlFor="password">{auth.basicAuth ? "新密码" : "密码"}</Label>
+            <Input id="password" type="password" value={password} onChange={(e) => setPassword(e.target.value)} />
+          </div>
+
+          <div className="grid gap-2">
+            <Label htmlFor="confirmPassword">确认密码</Label>
+            <Input
+              id="confirmPassword"
+              type="password"
+              placeholder="再次输入密码"
+              value={confirmPassword}
+              onChange={(e) => setConfirmPassword(e.target.value)}
+            />
+          </div>
+
+          <div className="grid gap-2">
+            <Label>权限</Label>
+            <ScopeSelect value={basicScope} onChange={setBasicScope} />
+          </div>
+        </div>
+
+        <div className="mt-4 flex justify-end gap-2">
+          {auth.basicAuth && (
+            <Button variant="outline" onClick={handleDisableBasicAuth}>
+              关闭账号登录
+            </Button>
+          )}
+          <Button onClick={handleSaveBasicAuth}>
+            <KeyRound className="mr-2 h-4 w-4" />
+            保存账号
+          </Button>
+        </div>
+      </div>
+
+      <div>
+        <h3 className="text-lg font-semibold text-foreground">API 令牌</h3>
+        <p className="mb-4 text-sm text-muted-foreground">
+          供 sfctl、脚本和监控系统使用，通过 <span className="font-mono">Authorization: Bearer &lt;令牌&gt;</span> 传递
+        </p>
+
+        {createdToken && (
+          <div className="mb-4 rounded-lg border border-primary/50 bg-primary/5 p-4">
+            <p className="text-sm font-medium text-foreground">新令牌已创建，请立即复制，关闭后无法再次查看</p>
+            <div className="mt-2 flex items-center gap-2">
+              <code className="flex-1 break-all rounded bg-muted px-2 py-1 font-mono text-sm">{createdToken}</code>
+              <Button variant="outline" size="sm" onClick={copyToken}>
+                <Copy className="h-4 w-4" />
+              </Button>
+              <Button variant="ghost" size="sm" onClick={() => setCreatedToken(null)}>
+                关闭
+              </Button>
+            </div>
+          </div>
+        )}
+
+        <div className="mb-4 flex items-end gap-2">
+          <div className="grid flex-1 gap-2">
+            <Label htmlFor="tokenName">名称</Label>
+            <Input
+              id="tokenName"
+              placeholder="例如 deploy-script"
+              value={tokenName}
+              onChange={(e) => setTokenName(e.target.value)}
+            />
+          </div>
+          <ScopeSelect value={tokenScope} onChange={setTokenScope} />
+          <Button onClick={handleCreateToken}>
+            <Plus className="mr-2 h-4 w-4" />
+            创建令牌
+          </Button>
+        </div>
+
+        {auth.tokens.length === 0 ? (
+          <p className="text-sm text-muted-foreground">还没有 API 令牌</p>
+        ) : (
+          <Table>
+            <TableHeader>
+              <TableRow>
+                <TableHead>名称</TableHead>
+                <TableHead>权限</TableHead>
+                <TableHead>创建时间</TableHead>
+                <TableHead className="w-16" />
+              </TableRow>
+            </TableHeader>
+            <TableBody>
+              {auth.tokens.map((token) => (
+                <TableRow key={token.id}>
+                  <TableCell className="font-medium">{token.name}</TableCell>
+                  <TableCell>
+                    <Badge variant="outline">{scopeLabels[token.scope]}</Badge>
+                  </TableCell>
+                  <TableCell className="text-muted-foreground">
+                    {token.createdAt ? new Date(token.createdAt).toLocaleString() : "-"}
+                  </TableCell>
+                  <TableCell>
+                    <Button variant="ghost" size="sm" onClick={() => handleRevokeToken(token.id)}>
+                      <Trash2 className="h-4 w-4 text-destructive" />
+                    </Button>
+                  </TableCell>
+                </TableRow>
+              ))}
+            </TableBody>
+          </Table>
+        )}
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,95 @@
+"use client"
+
+import { useEffect, useState } from "react"
+import { useLocation } from "react-router-dom"
+import { Loader2, X } from "lucide-react"
+import { Button } from "@/components/ui/button"
+import { Card } from "@/components/ui/card"
+import { Progress } from "@/components/ui/progress"
+import { useToast } from "@/hooks/use-toast"
+import { api } from "@/lib/api"
+import { listenEvent } from "@/lib/events"
+import type { Job } from "@/types/service"
+
+// 启动时自动启动服务的进度，可以取消
+export function AutoStartProgress() {
+  const location = useLocation()
+  const { toast } = useToast()
+  const [progress, setProgress] = useState<Job | null>(null)
+  const [cancelling, setCancelling] = useState(false)
+
+  const onLoginPage = location.pathname === "/login"
+
+  useEffect(() => {
+    if (onLoginPage) return
+
+    let disposed = false
+    let unlisten: (() => void) | undefined
+
+    const handleProgress = (next: Job) => {
+      if (next.operation.type !== "autoStart") return
+      setProgress(next)
+      if (next.state !== "running") {
+        setCancelling(false)
+        if (next.state === "cancelled") {
+          toast({ title: "已取消自动启动", description: `已启动 ${next.completed}/${next.total} 个服务` })
+        } else if (next.failed.length > 0) {
+          toast({
+            title: "部分服务自动启动失败",
+            description: next.failed.join(", "),
+            variant: "destructive",
+          })
+        }
+      }
+    }
+
+    listenEvent<Job>("job:progress", handleProgress).then((fn) => {
+      if (disposed) fn()
+      else unlisten = fn
+    })
+    // 界面加载前自动启动可能已经开始
+    api.getAutoStartProgress().then((current) => {
+      if (!disposed && current?.state === "running") setProgress(current)
+    }).catch(() => {})
+
+    return () => {
+      disposed = true
+      unlisten?.()
+    }
+  }, [onLoginPage, toast])
+
+  if (onLoginPage || progress?.state !== "running") return null
+
+  const percent = progress.total > 0 ? (progress.completed / progress.total) * 100 : 0
+
+  const handleCancel = async () => {
+    setCancelling(true)
+    try {
+      await api.cancelAutoStart()
+    } catch (error) {
+      setCancelling(false)
+      toast({ title: "取消失败", description: String(error), variant: "destructive" })
+    }
+  }
+
+  return (
+    <Card className="fixed bottom-4 right-4 z-50 w-80 gap-3 p-4 shadow-lg">
+      <div className="flex items-center justify-between gap-2">
+        <div className="flex min-w-0 items-center gap-2">
+          <Loader2 className="h-4 w-4 shrink-0 animate-spin text-primary" />
+          <span className="truncate text-sm font-medium">
+            正在自动启动服务 ({progress.completed}/{progress.total})
+          </span>
+        </div>
+        <Button variant="ghost" size="sm" onClick={handleCancel} disabled={cancelling}>
+          <X className="mr-1 h-3 w-3" />
+          {cancelling ? "取消中" : "取消"}
+        </Button>
+      </div>
+      <Progress value={percent} />
+      {progress.active.length > 0 && (
+        <p className="truncate text-xs text-muted-foreground">正在启动：{progress.active.join(", ")}</p>
+      )}
+    </Card>
+  )
+}
@@ -0,0 +1,92 @@
+"use client"
+
+import { useEffect, useState } from "react"
+import { Loader2 } from "lucide-react"
+import {
+  AlertDialog,
+  AlertDialogAction,
+  AlertDialogCancel,
+  AlertDialogContent,
+  AlertDialogDescription,
+  AlertDialogFooter,
+  AlertDialogHeader,
+  AlertDialogTitle,
+} from "@/components/ui/alert-dialog"
+import { Badge } from "@/components/ui/badge"
+import { api } from "@/lib/api"
+import type { RestartPlan } from "@/types/service"
+
+interface CascadeRestartDialogProps {
+  service: { id: string; name: string } | null
+  // 根据 ID 显示服务名称
+  serviceName: (serviceId: string) => string
+  onOpenChange: (open: boolean) => void
+  onConfirm: (serviceId: string) => void
+}
+
+// 级联重启前展示受影响的服务，确认后再执行
+export function CascadeRestartDialog({ service, serviceName, onOpenChange, onConfirm }: CascadeRestartDialogProps) {
+  const [plan, setPlan] = useState<RestartPlan | null>(null)
+  const [error, setError] = useState<string | null>(null)
+
+  useEffect(() => {
+    setPlan(null)
+    setError(null)
+    if (!service) return
+
+    let disposed = false
+    api
+      .getRestartPlan(service.id)
+      .then((next) => !disposed && setPlan(next))
+      .catch((err) => !disposed && setError(err instanceof Error ? err.message : String(err)))
+    return () => {
+      disposed = true
+    }
+  }, [service])
+
+  return (
+    <AlertDialog open={!!service} onOpenChange={onOpenChange}>
+      <AlertDialogContent>
+        <AlertDialogHeader>
+          <AlertDialogTitle>级联重启 {service?.name}</AlertDialogTitle>
+          <AlertDialogDescription>
+            将按从上到下的顺序停止以下服务，再逐层启动，每层健康检查通过后才会启动下一层。
+          </AlertDialogDescription>
+        </AlertDialogHeader>
+
+        <div className="space-y-3 py-2">
+          {!plan && !error && (
+            <div className="flex justify-center py-4">
+              <Loader2 className="h-5 w-5 animate-spin text-muted-foreground" />
+            </div>
+          )}
+          {error && <p className="text-sm text-destructive">{error}</p>}
+          {plan?.layers.map((layer, index) => (
+            <div key={index} className="flex items-start gap-3">
+              <span className="w-12 shrink-0 pt-0.5 text-xs text-muted-foreground">第 {index + 1} 层</span>
+              <div className="flex flex-wrap gap-1">
+                {layer.map((id) => (
+                  <Badge key={id} variant={index === 0 ? "default" : "secondary"}>
+                    {serviceName(id)}
+                  </Badge>
+                ))}
+              </div>
+            </div>
+          ))}
+          {plan && plan.skipped.length > 0 && (
+            <p className="text-xs text-muted-foreground">
+              未在运行、不会被启动：{plan.skipped.map(serviceName).join("、")}
+            </p>
+          )}
+        </div>
+
+        <AlertDialogFooter>
+          <AlertDialogCancel>取消</AlertDialogCancel>
+          <AlertDialogAction disabled={!plan} onClick={() => service && onConfirm(service.id)}>
+            重启 {plan ? plan.layers.reduce((count, layer) => count + layer.length, 0) : ""} 个服务
+          </AlertDialogAction>
+        </AlertDialogFooter>
+      </AlertDialogContent>
+    </AlertDialog>
+  )
+}
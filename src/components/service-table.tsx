@@ -12,6 +12,7 @@ import { DropdownMenu, DropdownMenuContent, DropdownMenuItem, DropdownMenuTrigge
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip"
 import { Checkbox } from "@/components/ui/checkbox"
 import { toast } from "sonner"
+import { CascadeRestartDialog } from "@/components/cascade-restart-dialog"
 
 interface ServiceTableProps {
   services: Service[]
@@ -20,8 +21,8 @@ interface ServiceTableProps {
   selectedServices?: string[]
   onSelectionChange?: (selected: string[]) => void
   onStart?: (serviceId: string) => void
-  onStop?: (serviceId: string) => void
-  onRestart?: (serviceId: string) => void
+  onStop?: (serviceId: string, dependents?: boolean) => void
+  onRestart?: (serviceId: string, cascade?: boolean) => void
   onEdit?: (serviceId: string) => void
   onDelete?: (serviceId: string) => void
 }
@@ -40,6 +41,7 @@ export function ServiceTable({
 }: ServiceTableProps) {
   // 强制重新渲染以更新相对时间
   const [, setTick] = useState(0)
+  const [cascadeTarget, setCascadeTarget] = useState<Service | null>(null)
 
   useEffect(() => {
     // 每分钟更新一次时间显示
@@ -197,14 +199,14 @@ export function ServiceTable({
     onStart?.(serviceId)
   }
 
-  const handleStop = (serviceId: string, serviceName: string) => {
-    toast.success(`正在停止 ${serviceName}...`)
-    onStop?.(serviceId)
+  const handleStop = (serviceId: string, serviceName: string, dependents = false) => {
+    toast.success(dependents ? `正在停止 ${serviceName} 及依赖它的服务...` : `正在停止 ${serviceName}...`)
+    onStop?.(serviceId, dependents)
   }
 
-  const handleRestart = (serviceId: string, serviceName: string) => {
-    toast.success(`正在重启 ${serviceName}...`)
-    onRestart?.(serviceId)
+  const handleRestart = (serviceId: string, serviceName: string, cascade = false) => {
+    toast.success(cascade ? `正在级联重启 ${serviceName}...` : `正在重启 ${serviceName}...`)
+    onRestart?.(serviceId, cascade)
   }
 
   const isAllSelected = services.length > 0 && selectedServices.length === services.length
@@ -407,6 +409,14 @@ export function ServiceTable({
                           <TooltipContent>更多操作</TooltipContent>
                         </Tooltip>
                         <DropdownMenuContent align="end">
+                          <DropdownMenuItem onClick={() => setCascadeTarget(service)}>
+                            级联重启（含依赖它的服务）
+                          </DropdownMenuItem>
+                          {service.status === "running" && (
+                            <DropdownMenuItem onClick={() => handleStop(service.id, service.name, true)}>
+                              停止（含依赖它的服务）
+                            </DropdownMenuItem>
+                          )}
                           <DropdownMenuItem onClick={() => onEdit?.(service.id)}>编辑服务</DropdownMenuItem>
                           <DropdownMenuItem onClick={() => onDelete?.(service.id)} className="text-destructive">
                             删除服务
@@ -421,6 +431,16 @@ export function ServiceTable({
           )}
         </TableBody>
       </Table>
+
+      <CascadeRestartDialog
+        service={cascadeTarget}
+        serviceName={(id) => services.find((s) => s.id === id)?.name ?? id}
+        onOpenChange={(open) => !open && setCascadeTarget(null)}
+        onConfirm={(id) => {
+          handleRestart(id, cascadeTarget?.name ?? id, true)
+          setCascadeTarget(null)
+        }}
+      />
     </Card>
   )
 }
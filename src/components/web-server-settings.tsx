@@ -0,0 +1,179 @@
+"use client"
+
+import { useEffect, useState } from "react"
+import { AlertCircle, Save } from "lucide-react"
+import { Badge } from "@/components/ui/badge"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { Label } from "@/components/ui/label"
+import { Switch } from "@/components/ui/switch"
+import { useConfig } from "@/hooks/use-config"
+import { api, isTauri } from "@/lib/api"
+import { listenEvent } from "@/lib/events"
+import type { Settings, WebServerStatus } from "@/types/service"
+
+interface ListenForm {
+  serverPort: number
+  bindAddress: string
+  tlsEnabled: boolean
+  certPath: string
+  keyPath: string
+}
+
+const toForm = (settings: Settings): ListenForm => ({
+  serverPort: settings.serverPort,
+  bindAddress: settings.bindAddress ?? "127.0.0.1",
+  tlsEnabled: !!settings.tls,
+  certPath: settings.tls?.certPath ?? "",
+  keyPath: settings.tls?.keyPath ?? "",
+})
+
+// Web 服务的监听设置和运行状态；保存后后端按新设置重新监听，监听失败（端口被占用、证书无法读取等）时在这里显示
+export function WebServerSettings() {
+  const { config, saveConfig } = useConfig()
+  const [form, setForm] = useState<ListenForm | null>(null)
+  const [status, setStatus] = useState<WebServerStatus | null>(null)
+  const [saving, setSaving] = useState(false)
+
+  useEffect(() => {
+    if (config) setForm(toForm(config.settings))
+  }, [config])
+
+  // 浏览器中能打开页面说明 Web 服务正在运行，状态只在桌面端获取
+  useEffect(() => {
+    if (!isTauri()) return
+
+    let disposed = false
+    let unlisten: (() => void) | undefined
+    listenEvent<WebServerStatus>("webserver:status", setStatus).then((fn) => {
+      if (disposed) fn()
+      else unlisten = fn
+    })
+    api.getWebServerStatus().then((current) => {
+      if (!disposed) setStatus(current)
+    }).catch(() => {})
+
+    return () => {
+      disposed = true
+      unlisten?.()
+    }
+  }, [])
+
+  if (!config || !form) return null
+
+  const handleSave = async () => {
+    setSaving(true)
+    try {
+      await saveConfig({
+        ...config,
+        settings: {
+          ...config.settings,
+          serverPort: form.serverPort,
+          bindAddress: form.bindAddress.trim(),
+          tls: form.tlsEnabled ? { certPath: form.certPath.trim(), keyPath: form.keyPath.trim() } : undefined,
+        },
+      })
+    } finally {
+      setSaving(false)
+    }
+  }
+
+  const scheme = form.tlsEnabled ? "https" : "http"
+  const address = status?.address ?? `${form.bindAddress}:${form.serverPort}`
+
+  return (
+    <div className="space-y-6">
+      <div>
+        <div className="mb-4 flex items-center justify-between">
+          <h3 className="text-lg font-semibold text-foreground">Web 服务配置</h3>
+          {status && (
+            <Badge variant={status.running ? "default" : "destructive"}>{status.running ? "运行中" : "未运行"}</Badge>
+          )}
+        </div>
+
+        {status?.error && (
+          <div className="mb-4 flex items-start gap-2 rounded-lg border border-destructive/50 bg-destructive/10 p-4 text-sm text-destructive">
+            <AlertCircle className="mt-0.5 h-4 w-4 shrink-0" />
+            <div>
+              <p className="font-medium">Web 服务启动失败</p>
+              <p className="mt-1 font-mono text-xs">{status.error}</p>
+            </div>
+          </div>
+        )}
+
+        <div className="space-y-4">
+          <div className="grid gap-2">
+            <Label htmlFor="bindAddress">监听地址</Label>
+            <Input
+              id="bindAddress"
+              value={form.bindAddress}
+              onChange={(e) => setForm({ ...form, bindAddress: e.target.value })}
+            />
+            <p className="text-xs text-muted-foreground">127.0.0.1 只允许本机访问，0.0.0.0 允许局域网访问</p>
+          </div>
+
+          <div className="grid gap-2">
+            <Label htmlFor="port">Web 服务端口</Label>
+            <Input
+              id="port"
+              type="number"
+              value={form.serverPort}
+              onChange={(e) => setForm({ ...form, serverPort: Number.parseInt(e.target.value) || 0 })}
+            />
+            <p className="text-xs text-muted-foreground">保存后 Web 服务会按新的地址和端口重新监听</p>
+          </div>
+
+          <div className="flex items-center justify-between">
+            <div className="space-y-0.5">
+              <Label htmlFor="tlsEnabled">启用 HTTPS</Label>
+              <p className="text-sm text-muted-foreground">使用 PEM 格式的证书和私钥</p>
+            </div>
+            <Switch
+              id="tlsEnabled"
+              checked={form.tlsEnabled}
+              onCheckedChange={(checked: boolean) => setForm({ ...form, tlsEnabled: checked })}
+            />
+          </div>
+
+          {form.tlsEnabled && (
+            <>
+              <div className="grid gap-2">
+                <Label htmlFor="certPath">证书文件</Label>
+                <Input
+                  id="certPath"
+                  placeholder="/path/to/cert.pem"
+                  value={form.certPath}
+                  onChange={(e) => setForm({ ...form, certPath: e.target.value })}
+                />
+              </div>
+              <div className="grid gap-2">
+                <Label htmlFor="keyPath">私钥文件</Label>
+                <Input
+                  id="keyPath"
+                  placeholder="/path/to/key.pem"
+                  value={form.keyPath}
+                  onChange={(e) => setForm({ ...form, keyPath: e.target.value })}
+                />
+              </div>
+            </>
+          )}
+
+          <div className="rounded-lg border border-border bg-muted/50 p-4">
+            <p className="text-sm font-medium text-foreground">访问地址</p>
+            <p className="mt-2 font-mono text-sm text-primary">{scheme}://{address}</p>
+          </div>
+        </div>
+      </div>
+
+      <div className="flex justify-end gap-2">
+        <Button variant="outline" onClick={() => setForm(toForm(config.settings))}>
+          重置
+        </Button>
+        <Button onClick={handleSave} disabled={saving}>
+          <Save className="mr-2 h-4 w-4" />
+          保存配置
+        </Button>
+      </div>
+    </div>
+  )
+}
@@ -139,7 +139,8 @@ export function useConfig() {
         console.error("Failed to save config:", err)
         toast({
           title: "保存配置失败",
-          description: "无法保存应用配置，请重试。",
+          // Tauri 命令返回的错误是字符串，REST 请求返回 ApiError
+          description: err instanceof Error ? err.message : typeof err === "string" ? err : "无法保存应用配置，请重试。",
           variant: "destructive",
         })
         return false
@@ -537,9 +538,9 @@ export function useConfig() {
   )
 
   const stopService = useCallback(
-    async (serviceId: string) => {
+    async (serviceId: string, dependents = false) => {
       try {
-        await api.stopTask(serviceId)
+        await api.stopTask(serviceId, dependents)
         toast({
           title: "服务停止中",
           description: "正在停止服务...",
@@ -600,9 +601,9 @@ export function useConfig() {
   )
 
   const restartService = useCallback(
-    async (serviceId: string) => {
+    async (serviceId: string, cascade = false) => {
       try {
-        await api.restartTask(serviceId)
+        await api.restartTask(serviceId, cascade)
         toast({
           title: "服务重启中",
           description: "正在重启服务...",
@@ -1,5 +1,6 @@
 import { useState, useCallback } from 'react'
-import { listen, UnlistenFn } from '@tauri-apps/api/event'
+import type { UnlistenFn } from '@tauri-apps/api/event'
+import { listenEvent } from '@/lib/events'
 
 export interface LogEntry {
   time: string
@@ -80,8 +81,8 @@ export function useLogs() {
     try {
       // 为每个服务监听日志事件
       for (const serviceId of serviceIds) {
-        const unlisten = await listen<string>(`log:${serviceId}`, (event) => {
-          const message = event.payload
+        const unlisten = await listenEvent<string>(`log:${serviceId}`, (payload) => {
+          const message = payload
           const serviceName = serviceNames.get(serviceId) || serviceId
           addLog(serviceId, serviceName, message)
         })
@@ -1,96 +1,288 @@
 import { invoke } from "@tauri-apps/api/core"
-import type { Config, ServerHealth } from "@/types/service"
-import { mockGroups, mockApplications, mockServerHealth } from "@/lib/mock-data"
+import type { ApplicationStatus, AuthSettings, Config, InstanceStatus, Job, EventRecord, RestartPlan, RunRecord, ServerHealth, StartPlan, TokenScope, WebServerStatus } from "@/types/service"
 
-const isTauri = () => typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window
+export const isTauri = () => typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window
+
+const AUTH_STORAGE_KEY = "serviceflow.auth"
+
+// 浏览器模式下访问 Web API 使用的 Authorization 头
+export const getAuthHeader = () => sessionStorage.getItem(AUTH_STORAGE_KEY)
+
+export class ApiError extends Error {
+  constructor(public status: number, message: string) {
+    super(message)
+  }
+}
+
+// 浏览器模式下调用内置 Web 服务器的 REST API
+async function request<T>(method: string, path: string, body?: unknown): Promise<T> {
+  const headers: Record<string, string> = { "X-Requested-With": "ServiceFlow" }
+  const auth = getAuthHeader()
+  if (auth) headers.Authorization = auth
+  if (body !== undefined) headers["Content-Type"] = "application/json"
+
+  const response = await fetch(path, {
+    method,
+    headers,
+    body: body === undefined ? undefined : JSON.stringify(body),
+  })
+
+  if (response.status === 401 && window.location.pathname !== "/login") {
+    window.location.href = "/login"
+  }
+  if (!response.ok) {
+    const error = await response.json().catch(() => null)
+    throw new ApiError(response.status, error?.error ?? `${response.status} ${response.statusText}`)
+  }
+  if (response.status === 204) {
+    return undefined as T
+  }
+  return response.json() as Promise<T>
+}
+
+const encode = encodeURIComponent
+
+// 等待编排任务结束，任务失败时抛出错误
+async function waitForJob(jobId: string): Promise<Job> {
+  for (;;) {
+    const job = await api.getJob(jobId)
+    if (!job) throw new Error(`任务不存在: ${jobId}`)
+    if (job.state === "failed") throw new Error(job.error ?? "操作失败")
+    if (job.state !== "running" && job.state !== "paused") return job
+    await new Promise((resolve) => setTimeout(resolve, 300))
+  }
+}
+
+// 提交编排操作（立即返回任务 ID），然后等待任务完成
+async function submitJob(command: string, args: Record<string, unknown>, path: string): Promise<Job> {
+  const jobId = isTauri()
+    ? await invoke<string>(command, args)
+    : (await request<{ jobId: string }>("POST", path)).jobId
+  return waitForJob(jobId)
+}
+
+// 使用 Authorization 头调用 whoami，成功后保存到 sessionStorage，供之后的请求使用
+async function authenticate(header: string) {
+  const response = await fetch("/api/auth/whoami", {
+    headers: { Authorization: header, "X-Requested-With": "ServiceFlow" },
+  })
+  if (!response.ok) {
+    throw new Error(response.status === 403 ? "权限不足" : header.startsWith("Bearer ") ? "令牌无效" : "用户名或密码错误")
+  }
+  sessionStorage.setItem(AUTH_STORAGE_KEY, header)
+  return response.json() as Promise<{ name: string; scope: TokenScope }>
+}
 
 export const api = {
+  // Auth（仅浏览器模式）
+  login: (username: string, password: string) => authenticate(`Basic ${btoa(`${username}:${password}`)}`),
+  loginWithToken: (token: string) => authenticate(`Bearer ${token}`),
+  logout: () => sessionStorage.removeItem(AUTH_STORAGE_KEY),
+  // WebSocket 连接使用的一次性票据（账号密码不能放在 URL 中）
+  getWsTicket: async () => (await request<{ ticket: string }>("POST", "/api/auth/ws-ticket")).ticket,
+
   // Config
+  // 没有 admin 权限时读取隐藏了环境变量值、启动参数和钩子的配置
   getConfig: async () => {
     if (!isTauri()) {
-      console.warn("Not running in Tauri environment, returning mock config")
-      return {
-        settings: { serverPort: 8899, autoStart: false },
-        groups: mockGroups,
-        applications: mockApplications
-      } as Config
+      return request<Config>("GET", "/api/config").catch((error) => {
+        if (error instanceof ApiError && error.status === 403) {
+          return request<Config>("GET", "/api/config/redacted")
+        }
+        throw error
+      })
     }
     return invoke<Config>("get_config")
   },
   saveConfig: async (config: Config) => {
-    if (!isTauri()) {
-      console.warn("Not running in Tauri environment, mock save config")
-      return
-    }
+    if (!isTauri()) return request<void>("PUT", "/api/config", config)
     return invoke<void>("save_config", { cfg: config })
   },
 
   // Task Management
-  startTask: async (taskId: string) => {
-    if (!isTauri()) return
-    return invoke<void>("start_task", { taskId })
+  startTask: (taskId: string) => submitJob("start_task", { taskId }, `/api/services/${encode(taskId)}/start`),
+  // dependents 为 true 时同时停止依赖该服务的服务
+  stopTask: (taskId: string, dependents = false) =>
+    submitJob(
+      "stop_task",
+      { taskId, dependents },
+      `/api/services/${encode(taskId)}/stop${dependents ? "?dependents=true" : ""}`,
+    ),
+  // cascade 为 true 时同时重启依赖该服务的服务
+  restartTask: (taskId: string, cascade = false) =>
+    submitJob(
+      "restart_task",
+      { taskId, cascade },
+      `/api/services/${encode(taskId)}/restart${cascade ? "?cascade=true" : ""}`,
+    ),
+  getServiceInstances: async (taskId: string) => {
+    if (!isTauri()) {
+      const service = await request<{ instances?: InstanceStatus[] }>("GET", `/api/services/${encode(taskId)}`)
+      return service.instances ?? []
+    }
+    return invoke<InstanceStatus[]>("get_service_instances", { taskId })
+  },
+  // 保存新的实例数并调整正在运行的实例
+  scaleService: async (taskId: string, replicas: number) => {
+    const jobId = isTauri()
+      ? await invoke<string>("scale_service", { taskId, replicas })
+      : (await request<{ jobId: string }>("POST", `/api/services/${encode(taskId)}/scale`, { replicas })).jobId
+    return waitForJob(jobId)
+  },
+  // 最近的运行记录，最新的在前
+  getRunHistory: async (taskId: string, limit?: number) => {
+    if (!isTauri()) {
+      const query = limit ? `?limit=${limit}` : ""
+      return request<RunRecord[]>("GET", `/api/services/${encode(taskId)}/runs${query}`)
+    }
+    return invoke<RunRecord[]>("get_run_history", { taskId, limit })
+  },
+  // 最近的事件（健康检查失败后的自动重启/停止等），最新的在前
+  getEventHistory: async (taskId: string, limit?: number) => {
+    if (!isTauri()) {
+      const query = limit ? `?limit=${limit}` : ""
+      return request<EventRecord[]>("GET", `/api/services/${encode(taskId)}/events${query}`)
+    }
+    return invoke<EventRecord[]>("get_event_history", { taskId, limit })
+  },
+  // 命名端口，自动端口为最近一次分配的端口
+  getServicePorts: async (taskId: string) => {
+    if (!isTauri()) {
+      const service = await request<{ ports?: Record<string, number> }>("GET", `/api/services/${encode(taskId)}`)
+      return service.ports ?? {}
+    }
+    return invoke<Record<string, number>>("get_service_ports", { taskId })
   },
-  stopTask: async (taskId: string) => {
-    if (!isTauri()) return
-    return invoke<void>("stop_task", { taskId })
+  getRestartPlan: async (taskId: string) => {
+    if (!isTauri()) return request<RestartPlan>("GET", `/api/services/${encode(taskId)}/restart-plan`)
+    return invoke<RestartPlan>("get_restart_plan", { taskId })
   },
-  restartTask: async (taskId: string) => {
-    if (!isTauri()) return
-    return invoke<void>("restart_task", { taskId })
+  // 未指定目标时为全部启动
+  planStart: async (target: { serviceId?: string; groupId?: string; applicationId?: string } = {}) => {
+    if (!isTauri()) {
+      const params = new URLSearchParams()
+      if (target.serviceId) params.set("service", target.serviceId)
+      if (target.groupId) params.set("group", target.groupId)
+      if (target.applicationId) params.set("application", target.applicationId)
+      const query = params.toString()
+      return request<StartPlan>("GET", `/api/plan/start${query ? `?${query}` : ""}`)
+    }
+    return invoke<StartPlan>("plan_start", target)
   },
-  startAll: async () => {
-    if (!isTauri()) return
-    return invoke<void>("start_all")
+  startAll: () => submitJob("start_all", {}, "/api/start-all"),
+  stopAllTasks: () => submitJob("stop_all_tasks", {}, "/api/stop-all"),
+  getAutoStartProgress: async () => {
+    if (!isTauri()) return request<Job | null>("GET", "/api/auto-start")
+    return invoke<Job | null>("get_auto_start_progress")
   },
-  stopAllTasks: async () => {
-    if (!isTauri()) return
-    return invoke<void>("stop_all_tasks")
+  cancelAutoStart: async () => {
+    if (!isTauri()) return request<void>("POST", "/api/auto-start/cancel")
+    return invoke<void>("cancel_auto_start")
   },
-  startGroup: async (groupId: string) => {
-    if (!isTauri()) return
-    return invoke<void>("start_group", { groupId })
+  startGroup: (groupId: string) => submitJob("start_group", { groupId }, `/api/groups/${encode(groupId)}/start`),
+  stopGroup: (groupId: string) => submitJob("stop_group", { groupId }, `/api/groups/${encode(groupId)}/stop`),
+  // 滚动重启可能暂停，只返回任务 ID，通过 getJob 查询进度
+  rollingRestartGroup: async (groupId: string, batchSize = 1) => {
+    if (!isTauri()) {
+      const path = `/api/groups/${encode(groupId)}/rolling-restart?batchSize=${batchSize}`
+      return (await request<{ jobId: string }>("POST", path)).jobId
+    }
+    return invoke<string>("rolling_restart_group", { groupId, batchSize })
   },
-  stopGroup: async (groupId: string) => {
-    if (!isTauri()) return
-    return invoke<void>("stop_group", { groupId })
+
+  // Applications
+  startApplication: (applicationId: string) =>
+    submitJob("start_application", { applicationId }, `/api/applications/${encode(applicationId)}/start`),
+  stopApplication: (applicationId: string) =>
+    submitJob("stop_application", { applicationId }, `/api/applications/${encode(applicationId)}/stop`),
+  restartApplication: (applicationId: string) =>
+    submitJob("restart_application", { applicationId }, `/api/applications/${encode(applicationId)}/restart`),
+  getApplicationStatus: async (applicationId: string) => {
+    if (!isTauri()) return request<ApplicationStatus>("GET", `/api/applications/${encode(applicationId)}/status`)
+    return invoke<ApplicationStatus>("get_application_status", { applicationId })
+  },
+
+  // Jobs
+  getJob: async (jobId: string) => {
+    if (!isTauri()) return request<Job>("GET", `/api/jobs/${encode(jobId)}`)
+    return invoke<Job | null>("get_job", { jobId })
+  },
+  listJobs: async () => {
+    if (!isTauri()) return request<Job[]>("GET", "/api/jobs")
+    return invoke<Job[]>("list_jobs")
+  },
+  resumeJob: async (jobId: string) => {
+    if (!isTauri()) return request<void>("POST", `/api/jobs/${encode(jobId)}/resume`)
+    return invoke<void>("resume_job", { jobId })
+  },
+  cancelJob: async (jobId: string) => {
+    if (!isTauri()) return request<void>("POST", `/api/jobs/${encode(jobId)}/cancel`)
+    return invoke<void>("cancel_job", { jobId })
   },
 
   // Status & Info
   isTaskRunning: async (taskId: string) => {
-    if (!isTauri()) return false
+    if (!isTauri()) {
+      const status = await request<{ running: boolean }>("GET", `/api/services/${encode(taskId)}`)
+      return status.running
+    }
     return invoke<boolean>("is_task_running", { taskId })
   },
   getTaskPid: async (taskId: string) => {
-    if (!isTauri()) return 0
+    if (!isTauri()) {
+      const status = await request<{ pid: number | null }>("GET", `/api/services/${encode(taskId)}`)
+      return status.pid ?? 0
+    }
     return invoke<number>("get_task_pid", { taskId })
   },
   getTaskPort: async (pid: number) => {
-    if (!isTauri()) return null
+    if (!isTauri()) return request<number | null>("GET", `/api/processes/${pid}/port`)
     return invoke<number | null>("get_pid_port", { pid })
   },
-  
+
   // Health Check
   getHealthCheckResult: async (taskId: string) => {
-    if (!isTauri()) return null
+    if (!isTauri()) return request<any>("GET", `/api/services/${encode(taskId)}/health`)
     return invoke<any>("get_health_check_result", { taskId })
   },
   restartHealthCheck: async (taskId: string) => {
-    if (!isTauri()) return
+    if (!isTauri()) return request<void>("POST", `/api/services/${encode(taskId)}/health/restart`)
     return invoke<void>("restart_health_check", { taskId })
   },
 
   // Server Health
   getServerHealth: async () => {
-    if (!isTauri()) {
-      // 在非 Tauri 环境下使用 mock 数据方便开发调试
-      return mockServerHealth as ServerHealth
-    }
+    if (!isTauri()) return request<ServerHealth>("GET", "/api/server/health")
     return invoke<ServerHealth>("get_server_health")
   },
 
   // Process Stats
   getProcessStats: async (taskId: string) => {
-    if (!isTauri()) return null
+    if (!isTauri()) return request<any>("GET", `/api/services/${encode(taskId)}/stats`)
     return invoke<any>("get_process_stats", { taskId })
   },
+
+  // API Tokens
+  getAuthSettings: async () => {
+    if (!isTauri()) return request<AuthSettings>("GET", "/api/auth/settings")
+    return invoke<AuthSettings | null>("get_auth_settings")
+  },
+  createApiToken: async (name: string, scope: TokenScope) => {
+    if (!isTauri()) return (await request<{ token: string }>("POST", "/api/auth/tokens", { name, scope })).token
+    return invoke<string>("create_api_token", { name, scope })
+  },
+  revokeApiToken: async (tokenId: string) => {
+    if (!isTauri()) return request<void>("DELETE", `/api/auth/tokens/${encode(tokenId)}`)
+    return invoke<void>("revoke_api_token", { tokenId })
+  },
+  setBasicAuth: async (username: string, password: string | null, scope?: TokenScope) => {
+    if (!isTauri()) return request<void>("PUT", "/api/auth/basic", { username, password, scope })
+    return invoke<void>("set_basic_auth", { username, password, scope })
+  },
+
+  // Web Server
+  getWebServerStatus: async () => {
+    if (!isTauri()) return null
+    return invoke<WebServerStatus>("get_web_server_status")
+  },
 }
@@ -0,0 +1,66 @@
+import { listen, type UnlistenFn } from "@tauri-apps/api/event"
+import { api, getAuthHeader, isTauri } from "@/lib/api"
+
+type Handler = (payload: any) => void
+
+const handlers = new Map<string, Set<Handler>>()
+let socket: WebSocket | null = null
+
+let connecting = false
+
+// WebSocket 地址中的凭据：API 令牌直接使用，账号密码登录时换取一次性票据
+async function socketCredential() {
+  const auth = getAuthHeader()
+  if (!auth) return ""
+  if (auth.startsWith("Bearer ")) return auth.slice("Bearer ".length)
+  return api.getWsTicket()
+}
+
+// 浏览器模式下通过 /ws 接收后端事件，所有订阅共享一个连接
+async function ensureSocket() {
+  if (connecting || (socket && socket.readyState <= WebSocket.OPEN)) return
+
+  connecting = true
+  let credential: string
+  try {
+    credential = await socketCredential()
+  } catch {
+    connecting = false
+    if (handlers.size > 0) setTimeout(ensureSocket, 3000)
+    return
+  }
+  connecting = false
+  if (handlers.size === 0) return
+
+  const protocol = window.location.protocol === "https:" ? "wss:" : "ws:"
+  const query = credential ? `?token=${encodeURIComponent(credential)}` : ""
+  socket = new WebSocket(`${protocol}//${window.location.host}/ws${query}`)
+
+  socket.onmessage = (message) => {
+    const { event, payload } = JSON.parse(message.data)
+    handlers.get(event)?.forEach((handler) => handler(payload))
+  }
+  socket.onclose = () => {
+    socket = null
+    // 仍有订阅时自动重连
+    if (handlers.size > 0) setTimeout(ensureSocket, 3000)
+  }
+}
+
+/** 订阅后端事件，桌面端使用 Tauri 事件，浏览器中使用 WebSocket */
+export async function listenEvent<T>(event: string, handler: (payload: T) => void): Promise<UnlistenFn> {
+  if (isTauri()) {
+    return listen<T>(event, (e) => handler(e.payload))
+  }
+
+  const set = handlers.get(event) ?? new Set<Handler>()
+  set.add(handler)
+  handlers.set(event, set)
+  ensureSocket()
+
+  return () => {
+    set.delete(handler)
+    if (set.size === 0) handlers.delete(event)
+    if (handlers.size === 0) socket?.close()
+  }
+}
@@ -29,8 +29,14 @@ export interface DependencyConfig {
   type: DependencyType
   timeout?: number // 等待依赖服务启动的超时时间（毫秒）
   healthCheckRequired?: boolean // 是否需要等待依赖服务健康检查通过
+  resolution?: ConflictResolution // 冲突依赖的处理方式，默认 error
 }
 
+// 冲突处理方式
+export type ConflictResolution =
+  | "error"   // 冲突服务在运行时拒绝启动
+  | "replace" // 先停止冲突服务，启动失败时恢复
+
 export interface RetryConfig {
   enabled: boolean
   maxRetries: number // 最大重试次数
@@ -63,6 +69,7 @@ export interface ProcessConfig {
   killSignal?: "SIGTERM" | "SIGKILL" | "SIGINT"
   gracefulShutdownTimeout?: number // 优雅关闭超时（毫秒）
   restartOnCrash?: boolean // 崩溃时自动重启
+  detached?: boolean // 分离模式：退出 ServiceFlow 后服务继续运行
   envFile?: string // 环境变量文件路径
 }
 
@@ -79,14 +86,18 @@ export interface HealthCheckConfig {
   enabled: boolean
   type: CheckType
   config: HttpCheckConfig | TcpCheckConfig | CommandCheckConfig
-  interval: number
-  timeout: number
-  retries: number
+  interval: number // 秒
+  timeout: number // 秒
+  retries: number // 单次检查失败后立即重试的次数
   failureThreshold: number // 连续失败多少次标记为 unhealthy
-  successThreshold: number // 连续成功多少次标记为 healthy
+  successThreshold: number // 连续成功多少次标记为 healthy（自动重启次数在此之后清零）
   startPeriod?: number // 启动后多久开始检查（给服务预热时间，毫秒）
+  onUnhealthy?: UnhealthyAction
 }
 
+// 连续失败达到 failureThreshold 后的处理方式，restart 的次数和间隔使用 retryConfig
+export type UnhealthyAction = "none" | "restart" | "stop"
+
 // 旧的健康检查格式（向后兼容）
 export interface OldHealthCheck {
   type: string
@@ -95,6 +106,8 @@ export interface OldHealthCheck {
   url?: string
   interval: number
   timeout: number
+  failureThreshold?: number // 默认 3
+  onUnhealthy?: UnhealthyAction // 默认 none
 }
 
 export interface Service {
@@ -125,6 +138,13 @@ export interface Service {
   processConfig?: ProcessConfig
   healthCheck?: HealthCheckConfig | OldHealthCheck // 支持新旧两种格式
   metrics?: ServiceMetrics
+  replicas?: number // 实例数，设置后每个实例单独运行（ID 为 服务ID#序号）
+  basePort?: number // 第一个实例的端口，第 N 个实例的 ${PORT} 为 basePort + N
+  ports?: Record<string, number | "auto"> // 命名端口，通过 ${port.名称} 或 ${svc.服务ID.port.名称} 引用
+  schedule?: ScheduleConfig // 定时运行
+  kind?: ServiceKind // 默认 daemon
+  hooks?: LifecycleHooks // 生命周期钩子
+  watch?: WatchConfig // 文件变化后自动重启或发送信号
   
   // 运行时信息
   exitCode?: number // 最后退出码
@@ -143,6 +163,8 @@ export interface ServiceGroup {
   order: number
   dependencies: string[] // IDs of other service groups this group depends on
   startupStrategy?: StartupStrategy // 组内服务启动策略
+  maxConcurrency?: number // 组内同时启动的服务数上限
+  atomic?: boolean // 原子启动，任一服务失败时停止本次启动的服务
 }
 
 export interface HealthCheck {
@@ -208,10 +230,207 @@ export interface LogConfig {
   level: "DEBUG" | "INFO" | "WARN" | "ERROR" | "ALL"
 }
 
+export type TokenScope = "read" | "operator" | "admin"
+
+export interface ApiToken {
+  id: string
+  name: string
+  tokenHash: string
+  scope: TokenScope
+  createdAt?: string
+}
+
+export interface AuthSettings {
+  tokens: ApiToken[]
+  basicAuth?: {
+    username: string
+    passwordHash: string
+    scope: TokenScope
+  }
+  publicHealthz: boolean
+}
+
+export interface TlsSettings {
+  certPath: string
+  keyPath: string
+}
+
+export interface WebServerStatus {
+  running: boolean
+  address?: string
+  tls: boolean
+  error?: string
+}
+
+// 应用的汇总状态
+export interface ApplicationStatus {
+  id: string
+  state: "up" | "degraded" | "down"
+  total: number
+  running: string[]
+  unhealthy: string[]
+}
+
+// 级联重启的影响范围，第一层是被重启的服务，之后每层依赖前面的层
+export interface RestartPlan {
+  serviceId: string
+  layers: string[][]
+  skipped: string[]
+}
+
+// 多实例服务中单个实例的状态
+// 生命周期钩子：preStart 失败时放弃启动，其他钩子失败只记录警告
+export interface HookConfig {
+  command: string
+  args?: string[]
+  timeout?: number // 秒，默认 30
+}
+
+export interface LifecycleHooks {
+  preStart?: HookConfig
+  postStart?: HookConfig
+  preStop?: HookConfig
+  postStop?: HookConfig
+}
+
+// 文件监视：文件变化后重启服务或发送信号
+export type WatchAction = "restart" | "signal"
+
+export interface WatchConfig {
+  paths?: string[] // 相对路径相对于 workDir，未设置时监视 workDir
+  include?: string[] // glob，相对于监视的目录
+  exclude?: string[]
+  debounce?: number // 毫秒，默认 500
+  action?: WatchAction // 默认 restart
+  signal?: string // action 为 signal 时发送的信号，默认 HUP
+  cascade?: boolean // 重启时同时重启依赖它的服务，默认 true
+}
+
+// 服务类型：常驻服务或一次性任务（退出码为 0 即为成功）
+export type ServiceKind = "daemon" | "oneshot"
+
+// 定时运行配置
+export type OverlapPolicy = "skip" | "queue" | "kill"
+
+export interface ScheduleConfig {
+  cron: string // 5 段（分 时 日 月 周）或带秒的 6 段
+  timezone?: string // IANA 时区，默认本地时区
+  overlap?: OverlapPolicy // 上一次运行未结束时的处理方式，默认 skip
+  timeout?: number // 单次运行超时（秒）
+}
+
+export type RunStatus = "succeeded" | "failed" | "timedOut" | "killed" | "skipped"
+
+// 一次运行的记录
+export interface RunRecord {
+  id: number
+  serviceId: string
+  trigger: string
+  status: RunStatus
+  exitCode?: number | null
+  startedAt: string
+  durationMs: number
+  logExcerpt: string
+}
+
+// 服务事件，例如健康检查失败后的自动重启
+export interface EventRecord {
+  id: number
+  serviceId: string
+  action: "restart" | "stop" | "restartLimitReached"
+  success: boolean
+  message: string
+  createdAt: string
+}
+
+export interface InstanceStatus {
+  id: string
+  index: number
+  running: boolean
+  pid?: number | null
+  port?: number | null
+  health?: { taskId: string; status: boolean; message: string } | null
+}
+
+// 启动计划（预览，不会启动服务）
+export interface PlannedDependency {
+  serviceId: string
+  type: DependencyType
+  timeout?: number | null
+  healthGate: boolean
+}
+
+export interface PlannedStart {
+  serviceId: string
+  groupId: string
+  dependencies: PlannedDependency[]
+  after: string[]
+  conflicts: { serviceId: string; running: boolean; resolution: ConflictResolution }[]
+  delay: number
+  atomicGroup?: string | null
+}
+
+export interface StartPlan {
+  operation: JobOperation
+  batches: { services: PlannedStart[]; delay: number }[]
+  skipped: { serviceId: string; reason: "alreadyRunning" | "notAutoStart" }[]
+}
+
+// 启动操作中单个服务的结果
+export type ServiceOutcome =
+  | { status: "started" }
+  | { status: "completed" } // 一次性服务运行成功结束
+  | { status: "alreadyRunning" }
+  | { status: "failed"; reason: string }
+  | { status: "skipped"; reason: string }
+  | { status: "rolledBack" }
+  | { status: "rollbackFailed"; reason: string }
+
+// 编排任务（启动、停止等操作）
+export type JobState = "running" | "paused" | "succeeded" | "failed" | "cancelled"
+
+export interface JobOperation {
+  type:
+    | "autoStart"
+    | "startAll"
+    | "stopAll"
+    | "startService"
+    | "stopService"
+    | "stopServiceAndDependents"
+    | "restartService"
+    | "restartServiceCascade"
+    | "startGroup"
+    | "stopGroup"
+    | "startApplication"
+    | "stopApplication"
+    | "restartApplication"
+    | "rollingRestartGroup"
+    | "scaleService"
+  target?: string | { groupId: string; batchSize: number } | { serviceId: string; replicas: number }
+}
+
+export interface Job {
+  id: string
+  operation: JobOperation
+  state: JobState
+  total: number
+  completed: number
+  active: string[]
+  failed: string[]
+  errors: Record<string, string>
+  outcomes: Record<string, ServiceOutcome> // 启动操作中各服务的结果
+  error?: string | null
+  startedAt: string
+  finishedAt?: string | null
+}
+
 export interface Settings {
   serverPort: number
+  bindAddress?: string
+  tls?: TlsSettings
   autoStart: boolean
   theme: string
+  auth?: AuthSettings
 }
 
 export interface Config {